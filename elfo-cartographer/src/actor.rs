@@ -0,0 +1,57 @@
+use std::net::SocketAddr;
+
+use tracing::{error, info};
+
+use elfo_core::{messages::ConfigUpdated, msg, stream::Stream, Context, SourceHandle, Topology};
+
+use crate::{
+    config::Config,
+    hyper,
+    protocol::{ExportTopology, ServerFailed},
+};
+
+pub(crate) async fn exec(mut ctx: Context<Config>, topology: Topology) {
+    let mut listen = ctx.config().listen;
+    let mut server = listen.map(|listen| start_server(&mut ctx, listen, &topology));
+
+    while let Some(envelope) = ctx.recv().await {
+        msg!(match envelope {
+            ConfigUpdated => {
+                let new_listen = ctx.config().listen;
+                if new_listen == listen {
+                    continue;
+                }
+
+                info!(
+                    message = "listen address changed, rerun the server",
+                    old = ?listen,
+                    new = ?new_listen,
+                );
+
+                // Terminate a running server.
+                if let Some(source) = server.take() {
+                    source.terminate();
+                }
+
+                listen = new_listen;
+                server = listen.map(|listen| start_server(&mut ctx, listen, &topology));
+            }
+            (ExportTopology { format }, token) => {
+                ctx.respond(token, topology.export(format));
+            }
+            ServerFailed(err) => {
+                error!(error = %err, "server failed");
+                panic!("server failed, cannot continue");
+            }
+        });
+    }
+}
+
+fn start_server(
+    ctx: &mut Context<Config>,
+    listen: SocketAddr,
+    topology: &Topology,
+) -> Stream<ServerFailed> {
+    let source = Stream::once(hyper::server(listen, topology.clone()));
+    ctx.attach(source)
+}
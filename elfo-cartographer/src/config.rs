@@ -0,0 +1,25 @@
+//! Configuration for the cartographer.
+//!
+//! Note: all types here are exported only for documentation purposes
+//! and are not subject to stable guarantees. However, the config
+//! structure (usually encoded in TOML) follows stable guarantees.
+
+use std::net::SocketAddr;
+
+use serde::Deserialize;
+
+/// The cartographer's config.
+///
+/// # Example
+/// ```toml
+/// [system.cartographers]
+/// listen = "0.0.0.0:9043"
+/// ```
+#[derive(Debug, Deserialize)]
+pub struct Config {
+    /// The address to serve the topology over HTTP.
+    ///
+    /// If not provided, the topology is available only by `ExportTopology`.
+    #[serde(default)]
+    pub listen: Option<SocketAddr>,
+}
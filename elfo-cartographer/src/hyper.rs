@@ -0,0 +1,104 @@
+use std::{convert::Infallible, io, net::SocketAddr, string::ToString, time::Duration};
+
+use http_body_util::Full;
+use hyper::{
+    body::Body, header::CONTENT_TYPE, server::conn, service, Method, Request, Response, StatusCode,
+};
+use hyper_util::rt::{TokioIo, TokioTimer};
+use tokio::{net::TcpListener, time::timeout};
+use tracing::{debug, info, warn};
+
+use elfo_core::{scope, topology::Format, tracing::TraceId, Topology};
+
+use crate::protocol::ServerFailed;
+
+const HEADER_READ_TIMEOUT: Duration = Duration::from_secs(3);
+const SERVE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Runs a simple HTTP server that responds to `GET /topology.dot` and
+/// `GET /topology.json` requests.
+/// * It supports only HTTP/1.
+/// * It doesn't support keep-alive connections.
+/// * It doesn't support TLS.
+/// * It handles requests one by one with some reasonable timeouts.
+pub(crate) async fn server(addr: SocketAddr, topology: Topology) -> ServerFailed {
+    let listener = match TcpListener::bind(addr).await {
+        Ok(listener) => listener,
+        Err(err) => return ServerFailed(format!("cannot bind a listener: {err}")),
+    };
+
+    info!(bind = %addr, "listening TCP connections");
+
+    loop {
+        let (stream, peer) = match listener.accept().await {
+            Ok(pair) => pair,
+            Err(err) => return ServerFailed(format!("cannot accept a connection: {err}")),
+        };
+
+        // The server doesn't support keep-alive connections, so every connection is a
+        // new request. Thus, we can start a new trace right here.
+        scope::set_trace_id(TraceId::generate());
+
+        debug!(peer = %peer, "accepted a TCP connection");
+        let topology = topology.clone();
+
+        let serving = conn::http1::Builder::new()
+            .timer(TokioTimer::new())
+            .keep_alive(false) // KA is meaningless for rare requests.
+            .header_read_timeout(HEADER_READ_TIMEOUT)
+            .serve_connection(
+                TokioIo::new(stream),
+                service::service_fn(move |req| handle(req, topology.clone())),
+            );
+
+        match flat_error(timeout(SERVE_TIMEOUT, serving).await) {
+            Ok(()) => debug!(peer = %peer, "finished serving a HTTP connection"),
+            Err(err) => warn!(
+                message = "failed to serve a HTTP connection",
+                error = %err,
+                peer = %peer,
+            ),
+        }
+    }
+}
+
+type ResBody = Full<io::Cursor<Vec<u8>>>;
+
+// Supports only `GET /topology.dot` and `GET /topology.json` requests.
+async fn handle(
+    req: Request<impl Body>,
+    topology: Topology,
+) -> Result<Response<ResBody>, Infallible> {
+    if req.method() != Method::GET {
+        return Ok(Response::builder()
+            .status(StatusCode::METHOD_NOT_ALLOWED)
+            .body(<_>::default())
+            .unwrap());
+    }
+
+    let (format, content_type) = match req.uri().path() {
+        "/topology.dot" => (Format::Dot, "text/vnd.graphviz"),
+        "/topology.json" => (Format::Json, "application/json"),
+        _ => {
+            return Ok(Response::builder()
+                .status(StatusCode::NOT_FOUND)
+                .body(<_>::default())
+                .unwrap())
+        }
+    };
+
+    let text = topology.export(format);
+
+    Ok(Response::builder()
+        .header(CONTENT_TYPE, content_type)
+        .body(Full::new(io::Cursor::new(text.into_bytes())))
+        .unwrap())
+}
+
+fn flat_error(res: Result<Result<(), impl ToString>, impl ToString>) -> Result<(), String> {
+    match res {
+        Ok(Ok(())) => Ok(()),
+        Ok(Err(err)) => Err(err.to_string()),
+        Err(err) => Err(err.to_string()),
+    }
+}
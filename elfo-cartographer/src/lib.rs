@@ -0,0 +1,48 @@
+//! Exposes the topology as a graph, see [`Topology::export()`].
+//! [Configuration].
+//!
+//! The graph can be requested by the [`ExportTopology`] message or,
+//! if `listen` is configured, over HTTP:
+//! * `GET /topology.dot` returns the graph in the DOT language.
+//! * `GET /topology.json` returns the graph in JSON.
+//!
+//! The graph is built on every request, so groups mounted at runtime
+//! are included.
+//!
+//! [Configuration]: config::Config
+//! [`Topology::export()`]: elfo_core::Topology::export
+
+use std::time::Duration;
+
+use elfo_core::{ActorGroup, Blueprint, RestartParams, RestartPolicy, Topology};
+
+pub use self::protocol::ExportTopology;
+
+pub mod config;
+
+mod actor;
+mod hyper;
+mod protocol;
+
+/// Creates a blueprint.
+///
+/// # Example
+/// ```
+/// # use elfo_core as elfo;
+/// let topology = elfo::Topology::empty();
+/// let cartographers = topology.local("cartographers");
+///
+/// // Usually, it's `elfo::batteries::cartographer::new`.
+/// cartographers.mount(elfo_cartographer::new(&topology));
+/// ```
+pub fn new(topology: &Topology) -> Blueprint {
+    let topology = topology.clone();
+    ActorGroup::new()
+        .config::<config::Config>()
+        .restart_policy(RestartPolicy::on_failure(RestartParams::new(
+            Duration::from_secs(5),
+            Duration::from_secs(30),
+        )))
+        .stop_order(100)
+        .exec(move |ctx| actor::exec(ctx, topology.clone()))
+}
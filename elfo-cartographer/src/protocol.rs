@@ -0,0 +1,22 @@
+use elfo_core::{message, topology::Format};
+
+/// Exports the topology in the specified format.
+/// Responds with [`Topology::export()`].
+///
+/// [`Topology::export()`]: elfo_core::Topology::export
+#[message(ret = String)]
+#[non_exhaustive]
+pub struct ExportTopology {
+    /// The format of the response.
+    pub format: Format,
+}
+
+impl ExportTopology {
+    /// Creates a new request.
+    pub fn new(format: Format) -> Self {
+        Self { format }
+    }
+}
+
+#[message]
+pub(crate) struct ServerFailed(pub(crate) String);
@@ -0,0 +1,57 @@
+//! A smoke integration test for the cartographer.
+
+use eyre::Result;
+use toml::toml;
+
+use elfo_cartographer::ExportTopology;
+use elfo_core::{topology::Format, ActorGroup, Envelope, Topology};
+
+#[tokio::test]
+async fn it_works() -> Result<()> {
+    let topology = Topology::empty();
+    let producers = topology.local("producers");
+    let consumers = topology.local("consumers");
+    producers.route_to_named(&consumers, "events", |_: &Envelope| true);
+    producers.mount(ActorGroup::new().exec(|_| async {}));
+    consumers.mount(ActorGroup::new().exec(|_| async {}));
+
+    let config = toml! {
+        listen = "127.0.0.1:9043"
+    };
+
+    let blueprint = elfo_cartographer::new(&topology);
+    let proxy = elfo_test::proxy(blueprint, config).await;
+
+    let expected_edge = r#""producers" -> "consumers" [label="events"];"#;
+
+    // Export by a request
+
+    let dot = proxy.request(ExportTopology::new(Format::Dot)).await;
+    assert!(dot.contains(expected_edge), "not found in:\n{dot}");
+
+    // Export over HTTP
+
+    let client = reqwest::Client::new();
+    let response = client
+        .get("http://127.0.0.1:9043/topology.dot")
+        .send()
+        .await?;
+    assert_eq!(response.headers()["content-type"], "text/vnd.graphviz");
+    assert_eq!(response.text().await?, dot);
+
+    let response = client
+        .get("http://127.0.0.1:9043/topology.json")
+        .send()
+        .await?;
+    assert_eq!(response.headers()["content-type"], "application/json");
+    let json = response.text().await?;
+    assert!(
+        json.contains(r#""label": "events""#),
+        "not found in:\n{json}"
+    );
+
+    let response = client.get("http://127.0.0.1:9043/metrics").send().await?;
+    assert_eq!(response.status(), 404);
+
+    Ok(())
+}
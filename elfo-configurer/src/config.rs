@@ -0,0 +1,89 @@
+//! Configuration for the configurer.
+//!
+//! Note: all types here are exported only for documentation purposes
+//! and are not subject to stable guarantees. However, the config
+//! structure (usually encoded in TOML) follows stable guarantees.
+
+use std::time::Duration;
+
+use serde::Deserialize;
+
+/// The configurer's config.
+///
+/// Like other groups, the configurer is configured by its own section
+/// in the config file.
+///
+/// # Example
+/// ```toml
+/// [system.configurers]
+/// watch.enabled = true
+/// watch.interval = "5s"
+/// history_size = 20
+/// unmasked_keys = ["system.mailbox", "producers.rate"]
+/// ```
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Watching of the config file.
+    pub watch: WatchConfig,
+    /// How many applied versions of configs are kept for `GetConfigHistory`
+    /// and `RollbackConfig`.
+    ///
+    /// `10` by default.
+    pub history_size: usize,
+    /// Paths whose values are shown in logged diffs and the history,
+    /// e.g. `producers.rate`. A path also covers all nested values, e.g.
+    /// `system.mailbox` or just `producers`.
+    ///
+    /// The configurer doesn't know types of configs and cannot tell secrets
+    /// from other values, so only paths of changed values are shown unless
+    /// they are listed here.
+    ///
+    /// Empty by default.
+    pub unmasked_keys: Vec<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            watch: WatchConfig::default(),
+            history_size: 10,
+            unmasked_keys: Vec::new(),
+        }
+    }
+}
+
+/// Watching of the config file, used only by `from_path()`.
+///
+/// The file is polled, so changes of the file itself and swaps of symlinks
+/// (e.g. Kubernetes ConfigMaps mounted as volumes) are detected. Once the file
+/// stops changing, configs are reloaded like on `ReloadConfigs`.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct WatchConfig {
+    /// Whether to reload configs once the file is changed.
+    ///
+    /// `false` by default.
+    pub enabled: bool,
+    /// How often the file is checked.
+    ///
+    /// `1s` by default.
+    #[serde(with = "humantime_serde")]
+    pub interval: Duration,
+    /// How long the file should stay unchanged before reloading.
+    /// Protects from reading partially written files.
+    ///
+    /// `2s` by default.
+    #[serde(with = "humantime_serde")]
+    pub debounce: Duration,
+}
+
+impl Default for WatchConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval: Duration::from_secs(1),
+            debounce: Duration::from_secs(2),
+        }
+    }
+}
@@ -0,0 +1,252 @@
+use std::fmt::{self, Write};
+
+use serde_value::Value;
+
+use crate::protocol::ConfigChange;
+
+const MASK: &str = "<masked>";
+
+/// Computes changes between two versions of the group's config.
+///
+/// The configurer knows nothing about types of configs and cannot detect
+/// secrets, so values are masked unless their paths are in `unmasked_keys`.
+pub(crate) fn diff(
+    group: &str,
+    old: &Value,
+    new: &Value,
+    unmasked_keys: &[String],
+) -> Vec<ConfigChange> {
+    let mut differ = Differ {
+        group,
+        unmasked_keys,
+        path: Vec::new(),
+        changes: Vec::new(),
+    };
+
+    differ.diff(Some(old), Some(new));
+    differ.changes
+}
+
+/// Describes changes for logs, e.g. `a.b: 1 -> 2, c: - -> "d", token`.
+/// Masked changes are described only by paths.
+pub(crate) fn describe(changes: &[ConfigChange]) -> String {
+    let side = |value: &Option<String>| value.clone().unwrap_or_else(|| "-".into());
+
+    changes
+        .iter()
+        .map(|c| {
+            if c.masked {
+                c.path.clone()
+            } else {
+                format!("{}: {} -> {}", c.path, side(&c.old), side(&c.new))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+struct Differ<'a> {
+    group: &'a str,
+    unmasked_keys: &'a [String],
+    path: Vec<String>,
+    changes: Vec<ConfigChange>,
+}
+
+impl Differ<'_> {
+    fn diff(&mut self, old: Option<&Value>, new: Option<&Value>) {
+        match (old.map(unwrap), new.map(unwrap)) {
+            (Some(Value::Map(old)), Some(Value::Map(new))) => {
+                let mut keys = old.keys().chain(new.keys()).collect::<Vec<_>>();
+                keys.sort();
+                keys.dedup();
+
+                for key in keys {
+                    self.path.push(render_key(key));
+                    self.diff(old.get(key), new.get(key));
+                    self.path.pop();
+                }
+            }
+            (old, new) if old == new => {}
+            (old, new) => {
+                let masked = !self.is_unmasked();
+                let render = |value: &Value| {
+                    if masked {
+                        MASK.to_string()
+                    } else {
+                        render(value)
+                    }
+                };
+
+                self.changes.push(ConfigChange {
+                    group: self.group.into(),
+                    path: self.path.join("."),
+                    old: old.map(render),
+                    new: new.map(render),
+                    masked,
+                });
+            }
+        }
+    }
+
+    /// Checks whether the full path (including the group) or any of its
+    /// prefixes is listed in `unmasked_keys`.
+    fn is_unmasked(&self) -> bool {
+        let mut prefix = self.group.to_string();
+        let mut parts = self.path.iter();
+
+        loop {
+            if self.unmasked_keys.contains(&prefix) {
+                return true;
+            }
+
+            let Some(part) = parts.next() else {
+                return false;
+            };
+
+            prefix.push('.');
+            prefix.push_str(part);
+        }
+    }
+}
+
+fn unwrap(value: &Value) -> &Value {
+    match value {
+        Value::Newtype(value) => unwrap(value),
+        Value::Option(Some(value)) => unwrap(value),
+        value => value,
+    }
+}
+
+fn render_key(key: &Value) -> String {
+    match key {
+        Value::String(key) => key.clone(),
+        key => render(key),
+    }
+}
+
+/// Renders the value in the TOML-like format.
+pub(crate) fn render(value: &Value) -> String {
+    let mut out = String::new();
+    write_value(&mut out, value).expect("cannot write to a string");
+    out
+}
+
+fn write_value(out: &mut String, value: &Value) -> fmt::Result {
+    match value {
+        Value::Bool(v) => write!(out, "{v}"),
+        Value::U8(v) => write!(out, "{v}"),
+        Value::U16(v) => write!(out, "{v}"),
+        Value::U32(v) => write!(out, "{v}"),
+        Value::U64(v) => write!(out, "{v}"),
+        Value::I8(v) => write!(out, "{v}"),
+        Value::I16(v) => write!(out, "{v}"),
+        Value::I32(v) => write!(out, "{v}"),
+        Value::I64(v) => write!(out, "{v}"),
+        Value::F32(v) => write!(out, "{v}"),
+        Value::F64(v) => write!(out, "{v}"),
+        Value::Char(v) => write!(out, "{:?}", v.to_string()),
+        Value::String(v) => write!(out, "{v:?}"),
+        Value::Unit | Value::Option(None) => write!(out, "()"),
+        Value::Option(Some(v)) | Value::Newtype(v) => write_value(out, v),
+        Value::Seq(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push_str(", ");
+                }
+                write_value(out, item)?;
+            }
+            out.push(']');
+            Ok(())
+        }
+        Value::Map(map) => {
+            out.push('{');
+            for (i, (key, value)) in map.iter().enumerate() {
+                out.push_str(if i > 0 { ", " } else { " " });
+                write!(out, "{} = ", render_key(key))?;
+                write_value(out, value)?;
+            }
+            out.push_str(if map.is_empty() { "}" } else { " }" });
+            Ok(())
+        }
+        Value::Bytes(bytes) => write!(out, "{bytes:?}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value(toml: &str) -> Value {
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn changes() {
+        let old = value(
+            r#"
+            a = 1
+            b = "foo"
+            nested = { x = [1, 2], y = true }
+            removed = 1.5
+            db = { password = "old", url = "pg://a" }
+            "#,
+        );
+        let new = value(
+            r#"
+            a = 1
+            b = "bar"
+            nested = { x = [1, 3], y = true }
+            added = { k = "v" }
+            db = { password = "new", url = "pg://b" }
+            "#,
+        );
+
+        let unmasked_keys =
+            ["group.added", "group.b", "group.nested", "group.removed"].map(String::from);
+        let changes = diff("group", &old, &new, &unmasked_keys)
+            .into_iter()
+            .map(|c| {
+                assert_eq!(c.group, "group");
+                assert_eq!(c.masked, c.path.starts_with("db."));
+                (c.path, c.old, c.new)
+            })
+            .collect::<Vec<_>>();
+
+        let s = |s: &str| Some(s.to_string());
+        assert_eq!(
+            changes,
+            [
+                ("added".into(), None, s(r#"{ k = "v" }"#)),
+                ("b".into(), s(r#""foo""#), s(r#""bar""#)),
+                ("db.password".into(), s(MASK), s(MASK)),
+                ("db.url".into(), s(MASK), s(MASK)),
+                ("nested.x".into(), s("[1, 2]"), s("[1, 3]")),
+                ("removed".into(), s("1.5"), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn masked_by_default() {
+        let old = value("api_key = \"a\"\ndb = { dsn = \"b\", size = 1 }");
+        let new = value("api_key = \"c\"\ndb = { dsn = \"d\", size = 2 }");
+
+        let changes = diff("group", &old, &new, &[]);
+        assert_eq!(describe(&changes), "api_key, db.dsn, db.size");
+
+        let changes = diff("group", &old, &new, &["group.db.size".into()]);
+        assert_eq!(describe(&changes), "api_key, db.dsn, db.size: 1 -> 2");
+
+        // The whole group.
+        let changes = diff("group", &old, &new, &["group".into()]);
+        assert_eq!(
+            describe(&changes),
+            r#"api_key: "a" -> "c", db.dsn: "b" -> "d", db.size: 1 -> 2"#
+        );
+
+        // Only full keys are matched.
+        let changes = diff("group", &old, &new, &["group.db.si".into(), "db".into()]);
+        assert_eq!(describe(&changes), "api_key, db.dsn, db.size");
+    }
+}
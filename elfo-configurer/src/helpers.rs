@@ -1,3 +1,6 @@
+use std::hash::{Hash, Hasher};
+
+use fxhash::FxHasher64;
 use serde_value::Value;
 
 pub(crate) fn lookup_value<'a>(mut value: &'a Value, path: &str) -> Option<&'a Value> {
@@ -30,6 +33,55 @@ pub(crate) fn add_defaults(config: Option<Value>, default: &Value) -> Value {
     }
 }
 
+/// Files larger than this are hashed by metadata only, secrets are small.
+const MAX_HASHED_FILE_SIZE: u64 = 64 * 1024;
+
+/// Hashes the config, including current values of secrets referenced by
+/// `{ file = "..." }` and `{ env = "..." }`, so rotated secrets are resent
+/// even if the config itself isn't changed. See `elfo::config::Secret`.
+///
+/// The configurer doesn't know types of configs, so any map of this shape is
+/// considered a reference. To avoid reading arbitrary large files, files are
+/// hashed by their size and modification time, and their content is hashed
+/// only if they're smaller than `MAX_HASHED_FILE_SIZE`.
+pub(crate) fn hash_config(config: &Value) -> u64 {
+    fn hash_file(path: &str, hasher: &mut FxHasher64) {
+        let Ok(metadata) = std::fs::metadata(path) else {
+            return;
+        };
+
+        metadata.len().hash(hasher);
+        metadata.modified().ok().hash(hasher);
+
+        if metadata.is_file() && metadata.len() <= MAX_HASHED_FILE_SIZE {
+            std::fs::read(path).ok().hash(hasher);
+        }
+    }
+
+    fn visit(value: &Value, hasher: &mut FxHasher64) {
+        match value {
+            Value::Map(map) if map.len() == 1 => match map.iter().next() {
+                Some((Value::String(key), Value::String(path))) if key == "file" => {
+                    hash_file(path, hasher);
+                }
+                Some((Value::String(key), Value::String(name))) if key == "env" => {
+                    std::env::var_os(name).hash(hasher);
+                }
+                _ => map.values().for_each(|value| visit(value, hasher)),
+            },
+            Value::Map(map) => map.values().for_each(|value| visit(value, hasher)),
+            Value::Seq(items) => items.iter().for_each(|value| visit(value, hasher)),
+            Value::Option(Some(value)) | Value::Newtype(value) => visit(value, hasher),
+            _ => {}
+        }
+    }
+
+    let mut hasher = FxHasher64::default();
+    config.hash(&mut hasher);
+    visit(config, &mut hasher);
+    hasher.finish()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -166,6 +218,27 @@ mod tests {
         );
     }
 
+    #[test]
+    fn hashes_referenced_secrets() {
+        let path = std::env::temp_dir().join(format!("elfo-hash-secret-{}", std::process::id()));
+        let config = toml::from_str::<Value>(&format!(
+            "secret = {{ file = {:?} }}",
+            path.display().to_string()
+        ))
+        .unwrap();
+
+        let missing = hash_config(&config);
+        std::fs::write(&path, "a").unwrap();
+        let first = hash_config(&config);
+        std::fs::write(&path, "b").unwrap();
+        let second = hash_config(&config);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_ne!(missing, first);
+        assert_ne!(first, second);
+        assert_eq!(hash_config(&config), missing);
+    }
+
     /// The `toml` crate prior to v0.6 merges sections incorrectly,
     /// now it should work fine. Added to prevent regression.
     /// See #30 for details.
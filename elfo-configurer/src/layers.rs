@@ -0,0 +1,387 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use futures::{future::BoxFuture, FutureExt};
+use serde_value::Value;
+use tokio::fs;
+
+const INCLUDE_KEY: &str = "include";
+const ENV_SEPARATOR: &str = "__";
+
+/// Layers of configs merged into one, see [`from_layers()`].
+///
+/// Layers are merged in the following order, each next layer overrides
+/// values of previous ones:
+/// 1. The base file.
+/// 2. Overlay files in the order of [`Layers::overlay()`] calls.
+/// 3. Environment variables, if enabled by [`Layers::env_overrides()`].
+///
+/// Each file can include other files by the top-level `include` array.
+/// Paths are relative to the including file. Included files are merged before
+/// the including one, so the including file overrides them.
+///
+/// # Example
+/// ```toml
+/// # config.toml
+/// include = ["logging.toml", "groups/producers.toml"]
+///
+/// [producers]
+/// rate = 100
+/// ```
+///
+/// [`from_layers()`]: crate::from_layers
+#[derive(Debug, Clone)]
+pub struct Layers {
+    files: Vec<PathBuf>,
+    env_prefix: Option<String>,
+}
+
+impl Layers {
+    /// Creates layers with the provided base file.
+    pub fn new(base: impl AsRef<Path>) -> Self {
+        Self {
+            files: vec![base.as_ref().to_path_buf()],
+            env_prefix: None,
+        }
+    }
+
+    /// Adds a file overriding previous layers, e.g. `config.prod.toml`.
+    pub fn overlay(mut self, path: impl AsRef<Path>) -> Self {
+        self.files.push(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Enables overriding values by environment variables, e.g.
+    /// `ELFO__PRODUCERS__RATE=200` for the `ELFO` prefix.
+    ///
+    /// Parts of names are separated by `__` and lowercased, so
+    /// `ELFO__SYSTEM__LOGGERS__SINK` changes `sink` of `system.loggers`.
+    /// Values are parsed as TOML values, falling back to strings.
+    pub fn env_overrides(mut self, prefix: impl Into<String>) -> Self {
+        self.env_prefix = Some(prefix.into());
+        self
+    }
+
+    pub(crate) fn files(&self) -> &[PathBuf] {
+        &self.files
+    }
+
+    pub(crate) async fn load(&self) -> Result<Merged, String> {
+        let mut merged = Merged::default();
+
+        for path in &self.files {
+            merged.merge_file(path, &mut Vec::new()).await?;
+        }
+
+        if let Some(prefix) = &self.env_prefix {
+            let mut vars = std::env::vars().collect::<Vec<_>>();
+            vars.sort();
+
+            for (name, value) in vars {
+                merged.merge_env(prefix, &name, &value);
+            }
+        }
+
+        Ok(merged)
+    }
+}
+
+/// Configs merged from layers.
+pub(crate) struct Merged {
+    pub(crate) value: Value,
+    /// All read files, including included ones.
+    pub(crate) files: Vec<PathBuf>,
+    /// Names of merged layers in the merging order.
+    layers: Vec<String>,
+    /// Layers that provided values, by paths of values.
+    origins: BTreeMap<Vec<String>, String>,
+}
+
+impl Default for Merged {
+    fn default() -> Self {
+        Self::from(Value::Map(BTreeMap::new()))
+    }
+}
+
+impl From<Value> for Merged {
+    fn from(value: Value) -> Self {
+        Self {
+            value,
+            files: Vec::new(),
+            layers: Vec::new(),
+            origins: BTreeMap::new(),
+        }
+    }
+}
+
+impl Merged {
+    /// Returns layers providing values of the group, including ones of
+    /// the `common` section. The latest layers go first.
+    pub(crate) fn layers_of(&self, group: &str) -> Vec<String> {
+        let group = group.split('.').map(String::from).collect::<Vec<_>>();
+        let mut layers = Vec::<String>::new();
+
+        let relevant = |path: &Vec<String>| {
+            path.first().is_some_and(|part| part == "common") || path.starts_with(&group)
+        };
+
+        for (path, layer) in &self.origins {
+            if relevant(path) && !layers.contains(layer) {
+                layers.push(layer.clone());
+            }
+        }
+
+        let position = |layer: &String| self.layers.iter().position(|l| l == layer);
+        layers.sort_by_key(|layer| std::cmp::Reverse(position(layer)));
+        layers
+    }
+
+    fn merge_file<'a>(
+        &'a mut self,
+        path: &'a Path,
+        chain: &'a mut Vec<PathBuf>,
+    ) -> BoxFuture<'a, Result<(), String>> {
+        async move {
+            let layer = path.display().to_string();
+            let describe = |error: &dyn std::fmt::Display| format!("{layer}: {error}");
+
+            // The same file can be included by different paths, e.g. `./a.toml`.
+            let canonical = fs::canonicalize(path).await.map_err(|err| describe(&err))?;
+
+            if chain.contains(&canonical) {
+                return Err(describe(&"circular include"));
+            }
+
+            let content = fs::read_to_string(path)
+                .await
+                .map_err(|err| describe(&err))?;
+            let mut value: Value = toml::from_str(&content).map_err(|err| describe(&err))?;
+            let includes = take_includes(&mut value).map_err(|err| describe(&err))?;
+
+            chain.push(canonical);
+            let dir = path.parent().unwrap_or_else(|| Path::new(""));
+            for include in includes {
+                self.merge_file(&dir.join(include), chain).await?;
+            }
+            chain.pop();
+
+            self.files.push(path.to_path_buf());
+            self.layers.push(layer.clone());
+            merge(
+                &mut self.value,
+                value,
+                &mut Vec::new(),
+                &mut |path, leaf| record(&mut self.origins, path, leaf, &layer),
+            );
+
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn merge_env(&mut self, prefix: &str, name: &str, raw: &str) {
+        let Some(path) = name
+            .strip_prefix(prefix)
+            .and_then(|rest| rest.strip_prefix(ENV_SEPARATOR))
+        else {
+            return;
+        };
+
+        let path = path
+            .split(ENV_SEPARATOR)
+            .map(str::to_lowercase)
+            .collect::<Vec<_>>();
+
+        if path.iter().any(String::is_empty) {
+            return;
+        }
+
+        let value = path.iter().rev().fold(parse_env_value(raw), |value, part| {
+            let mut map = BTreeMap::new();
+            map.insert(Value::String(part.clone()), value);
+            Value::Map(map)
+        });
+
+        self.layers.push(name.into());
+        merge(
+            &mut self.value,
+            value,
+            &mut Vec::new(),
+            &mut |path, leaf| record(&mut self.origins, path, leaf, name),
+        );
+    }
+}
+
+fn take_includes(value: &mut Value) -> Result<Vec<String>, &'static str> {
+    let Value::Map(map) = value else {
+        return Ok(Vec::new());
+    };
+
+    match map.remove(&Value::String(INCLUDE_KEY.into())) {
+        None => Ok(Vec::new()),
+        Some(Value::Seq(items)) => items
+            .into_iter()
+            .map(|item| match item {
+                Value::String(path) => Ok(path),
+                _ => Err("`include` must be an array of strings"),
+            })
+            .collect(),
+        Some(_) => Err("`include` must be an array of strings"),
+    }
+}
+
+fn parse_env_value(raw: &str) -> Value {
+    #[derive(serde::Deserialize)]
+    struct Wrapper {
+        value: Value,
+    }
+
+    toml::from_str::<Wrapper>(&format!("value = {raw}"))
+        .map(|wrapper| wrapper.value)
+        .unwrap_or_else(|_| Value::String(raw.into()))
+}
+
+/// Merges `src` into `dst` recursively, calls `on_replace` for each replaced
+/// value with a flag whether it's a leaf (not a map).
+fn merge(
+    dst: &mut Value,
+    src: Value,
+    path: &mut Vec<String>,
+    on_replace: &mut impl FnMut(&[String], bool),
+) {
+    match (dst, src) {
+        (Value::Map(dst), Value::Map(src)) => {
+            for (key, value) in src {
+                let part = match &key {
+                    Value::String(part) => part.clone(),
+                    other => format!("{other:?}"),
+                };
+
+                path.push(part);
+                match dst.get_mut(&key) {
+                    Some(dst) => merge(dst, value, path, on_replace),
+                    None => {
+                        let mut placeholder = Value::Map(BTreeMap::new());
+                        merge(&mut placeholder, value, path, on_replace);
+                        dst.insert(key, placeholder);
+                    }
+                }
+                path.pop();
+            }
+        }
+        (dst, src) => {
+            let is_leaf = !matches!(src, Value::Map(_));
+            on_replace(path, is_leaf);
+
+            if is_leaf {
+                *dst = src;
+            } else {
+                *dst = Value::Map(BTreeMap::new());
+                merge(dst, src, path, on_replace);
+            }
+        }
+    }
+}
+
+fn record(
+    origins: &mut BTreeMap<Vec<String>, String>,
+    path: &[String],
+    is_leaf: bool,
+    layer: &str,
+) {
+    // Values under the replaced one are gone.
+    origins.retain(|p, _| !p.starts_with(path));
+
+    if is_leaf {
+        origins.insert(path.to_vec(), layer.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lookup<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+        crate::helpers::lookup_value(value, path)
+    }
+
+    fn write(dir: &Path, name: &str, content: &str) -> PathBuf {
+        let path = dir.join(name);
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[tokio::test]
+    async fn merging() {
+        let dir = std::env::temp_dir().join(format!("elfo-layers-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        write(&dir, "fragment.toml", "[a]\nx = 1\ny = 1\n[b]\nx = 1");
+        let base = write(
+            &dir,
+            "base.toml",
+            "include = [\"fragment.toml\"]\n[a]\ny = 2\nz = 2",
+        );
+        let prod = write(&dir, "prod.toml", "[a]\nz = 3\n[c.d]\nx = 3");
+
+        std::env::set_var("ELFO_TEST__A__X", "4");
+        std::env::set_var("ELFO_TEST__C__D__X", "\"1s\"");
+        std::env::set_var("ELFO_TEST__C__D__Y", "not toml");
+
+        let merged = Layers::new(&base)
+            .overlay(&prod)
+            .env_overrides("ELFO_TEST")
+            .load()
+            .await
+            .unwrap();
+
+        let int = |v| Some(Value::I64(v));
+        let string = |v: &str| Some(Value::String(v.into()));
+
+        assert_eq!(lookup(&merged.value, "a.x").cloned(), int(4));
+        assert_eq!(lookup(&merged.value, "a.y").cloned(), int(2));
+        assert_eq!(lookup(&merged.value, "a.z").cloned(), int(3));
+        assert_eq!(lookup(&merged.value, "b.x").cloned(), int(1));
+        assert_eq!(lookup(&merged.value, "c.d.x").cloned(), string("1s"));
+        assert_eq!(lookup(&merged.value, "c.d.y").cloned(), string("not toml"));
+        assert!(lookup(&merged.value, "include").is_none());
+
+        let name = |file: &str| dir.join(file).display().to_string();
+        assert_eq!(merged.files, [dir.join("fragment.toml"), base, prod]);
+        assert_eq!(
+            merged.layers_of("a"),
+            ["ELFO_TEST__A__X", &name("prod.toml"), &name("base.toml")]
+        );
+        assert_eq!(merged.layers_of("b"), [name("fragment.toml")]);
+        assert_eq!(
+            merged.layers_of("c.d"),
+            ["ELFO_TEST__C__D__Y", "ELFO_TEST__C__D__X"]
+        );
+        assert!(merged.layers_of("unknown").is_empty());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn circular_include() {
+        let dir = std::env::temp_dir().join(format!("elfo-layers-circular-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        write(&dir, "a.toml", "include = [\"b.toml\"]");
+        let b = write(&dir, "b.toml", "include = [\"a.toml\"]");
+
+        let error = Layers::new(&b).load().await.err().unwrap();
+        assert!(error.ends_with("b.toml: circular include"), "{error}");
+
+        // Cycles are detected regardless of spelling of paths.
+        write(&dir, "a.toml", "include = [\"./sub/../b.toml\"]");
+        std::fs::create_dir_all(dir.join("sub")).unwrap();
+
+        let error = Layers::new(&b).load().await.err().unwrap();
+        assert!(error.ends_with("b.toml: circular include"), "{error}");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
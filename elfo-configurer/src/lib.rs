@@ -1,10 +1,14 @@
-//! Loads and validates configs from a file or a fixture.
+//! Loads and validates configs from a file, layers of files or a fixture.
 //! Usually, it's used as an entrypoint in the topology.
+//! [Configuration].
+//!
+//! [Configuration]: config::Config
 
 use std::{
+    collections::VecDeque,
     future::Future,
     path::{Path, PathBuf},
-    time::Duration,
+    time::{Duration, SystemTime},
 };
 
 use futures::future;
@@ -16,18 +20,28 @@ use tracing::{error, info, warn};
 
 use elfo_core::{
     config::AnyConfig,
+    message,
     messages::{
-        EntrypointError, StartEntrypoint, StartEntrypointRejected, UpdateConfig, ValidateConfig,
+        ConfigUpdated, EntrypointError, GroupMounted, GroupUnmounted, StartEntrypoint,
+        StartEntrypointRejected, UpdateConfig, ValidateConfig,
     },
     msg, scope,
     signal::{Signal, SignalKind},
+    time::Interval,
     ActorGroup, ActorStatus, Addr, Blueprint, Context, RestartParams, RestartPolicy, Topology,
 };
 
-pub use self::protocol::*;
+pub use self::{layers::Layers, protocol::*};
 
+use self::{config::Config, layers::Merged, watcher::Watcher};
+
+pub mod config;
+
+mod diff;
 mod helpers;
+mod layers;
 mod protocol;
+mod watcher;
 
 // How often warn if a group is updating a config too long.
 const WARN_INTERVAL: Duration = Duration::from_secs(5);
@@ -57,6 +71,8 @@ pub fn fixture(topology: &Topology, config: impl for<'de> Deserializer<'de>) ->
 
 /// Creates a blueprint for a configurer that reads the provided TOML file.
 ///
+/// The file can be watched for changes, see [`config::WatchConfig`].
+///
 /// # Example
 /// ```
 /// # use elfo_core as elfo;
@@ -72,9 +88,104 @@ pub fn from_path(topology: &Topology, path_to_config: impl AsRef<Path>) -> Bluep
     blueprint(topology, source)
 }
 
+/// Creates a blueprint for a configurer that merges the provided layers:
+/// a base file, overlays and environment variables. See [`Layers`].
+///
+/// Errors of validation contain layers providing values of rejected configs,
+/// see [`ReloadConfigsError::layers`].
+///
+/// # Example
+/// ```
+/// # use elfo_core as elfo;
+/// use elfo_configurer::Layers;
+///
+/// let topology = elfo::Topology::empty();
+/// let configurers = topology.local("configurers");
+/// let examples = topology.local("examples");
+///
+/// let layers = Layers::new("config.toml")
+///     .overlay("config.prod.toml")
+///     .env_overrides("ELFO");
+///
+/// // Usually, it's `elfo::batteries::configurer::from_layers`.
+/// configurers.mount(elfo_configurer::from_layers(&topology, layers));
+/// ```
+pub fn from_layers(topology: &Topology, layers: Layers) -> Blueprint {
+    let source = ConfigSource::Layers(layers);
+    blueprint(topology, source)
+}
+
+/// Checks the TOML file against configs of all mounted groups without
+/// starting actors, e.g. to lint production configs in CI.
+///
+/// Each group's section (with defaults from the `common` section) is decoded
+/// into the group's config, including the `system` section. Unlike
+/// [`ReloadConfigs`], actors don't get `ValidateConfig`, so invariants checked
+/// by actors themselves aren't checked.
+///
+/// Errors of reading and parsing the file are reported for the entrypoint.
+///
+/// Referenced secrets are read, so they must be accessible. Otherwise, wrap
+/// the call in `elfo::config::skip_secrets()` to use placeholders instead.
+///
+/// # Example
+/// ```no_run
+/// # use elfo_core as elfo;
+/// let topology = elfo::Topology::empty();
+/// // Build the topology like in `main()`.
+///
+/// if let Err(errors) = elfo_configurer::validate_file(&topology, "config.toml") {
+///     for error in errors {
+///         eprintln!("{}: {}", error.group, error.reason);
+///     }
+///     std::process::exit(1);
+/// }
+/// ```
+pub fn validate_file(
+    topology: &Topology,
+    path: impl AsRef<Path>,
+) -> Result<(), Vec<ReloadConfigsError>> {
+    let path = path.as_ref();
+    let file_error = |error: &dyn std::fmt::Display| {
+        let entrypoint = topology.locals().find(|group| group.is_entrypoint);
+        vec![ReloadConfigsError {
+            group: entrypoint.map(|group| group.name).unwrap_or_default(),
+            reason: format!("{}: {error}", path.display()),
+            layers: Vec::new(),
+        }]
+    };
+
+    let content = std::fs::read_to_string(path).map_err(|err| file_error(&err))?;
+    let config: Value = toml::from_str(&content).map_err(|err| file_error(&err))?;
+
+    let groups = topology
+        .locals()
+        .map(|group| (group.name.clone(), group))
+        .collect::<FxHashMap<_, _>>();
+
+    let errors = match_configs(topology, &config)
+        .into_iter()
+        .filter_map(|item| {
+            let reason = groups[&item.group_name].check_config(&item.config).err()?;
+            Some(ReloadConfigsError {
+                group: item.group_name,
+                reason,
+                layers: Vec::new(),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
 fn blueprint(topology: &Topology, source: ConfigSource) -> Blueprint {
     let topology = topology.clone();
     ActorGroup::new()
+        .config::<Config>()
         .stop_order(100)
         .restart_policy(RestartPolicy::on_failure(RestartParams::new(
             Duration::from_secs(5),
@@ -84,16 +195,27 @@ fn blueprint(topology: &Topology, source: ConfigSource) -> Blueprint {
 }
 
 struct Configurer {
-    ctx: Context,
+    ctx: Context<Config>,
     topology: Topology,
     source: ConfigSource,
     /// Stores hashes of configs per group.
     versions: FxHashMap<String, u64>,
+    /// Stores applied configs per group to compute diffs.
+    applied: FxHashMap<String, Value>,
+    /// Applied versions with whole configs, the latest go last.
+    history: VecDeque<(ConfigVersion, Value)>,
+    last_version: u64,
+    /// Detects changes of the config file, `None` for fixtures.
+    watcher: Option<Watcher>,
 }
 
+#[message]
+struct WatchTick;
+
 #[derive(Clone)]
 enum ConfigSource {
     File(PathBuf),
+    Layers(Layers),
     Fixture(Result<Value, String>),
 }
 
@@ -102,20 +224,34 @@ struct ConfigWithMeta {
     group_name: String,
     addr: Addr,
     config: AnyConfig,
+    value: Value,
     hash: u64,
 }
 
 impl Configurer {
-    fn new(ctx: Context, topology: Topology, source: ConfigSource) -> Self {
+    fn new(ctx: Context<Config>, topology: Topology, source: ConfigSource) -> Self {
+        let watcher = match &source {
+            ConfigSource::File(path) => Some(Watcher::new(vec![path.clone()])),
+            ConfigSource::Layers(layers) => Some(Watcher::new(layers.files().to_vec())),
+            ConfigSource::Fixture(_) => None,
+        };
+
         Self {
             ctx,
             topology,
             source,
             versions: FxHashMap::default(),
+            applied: FxHashMap::default(),
+            history: VecDeque::new(),
+            last_version: 0,
+            watcher,
         }
     }
 
     async fn main(mut self) {
+        // Groups mounted at runtime are configured on `GroupMounted`.
+        self.topology.subscribe_to_changes(self.ctx.group());
+
         let mut validated_configs = false;
         let mut first_envelope = match self.ctx.recv().await {
             Some(e) => {
@@ -128,7 +264,14 @@ impl Configurer {
                         ) -> Vec<EntrypointError> {
                             errors
                                 .into_iter()
-                                .map(|e| EntrypointError::new(e.group, e.reason))
+                                .map(|e| {
+                                    let reason = if e.layers.is_empty() {
+                                        e.reason
+                                    } else {
+                                        format!("{} (layers: {})", e.reason, e.layers.join(", "))
+                                    };
+                                    EntrypointError::new(e.group, reason)
+                                })
                                 .collect()
                         }
 
@@ -142,7 +285,7 @@ impl Configurer {
                             }
                             return;
                         } else {
-                            match self.load_and_update_configs(true).await {
+                            match self.load_and_update_configs(true, None).await {
                                 Ok(_) => self.ctx.respond(token, Ok(())),
                                 Err(errors) => {
                                     self.ctx.respond(
@@ -169,7 +312,7 @@ impl Configurer {
 
         // Reload and validate configs in case the actor was restarted by the
         // supervisor.
-        if !validated_configs && self.load_and_update_configs(true).await.is_err() {
+        if !validated_configs && self.load_and_update_configs(true, None).await.is_err() {
             panic!("configs are invalid at startup");
         }
 
@@ -178,6 +321,9 @@ impl Configurer {
         let signal = Signal::new(SignalKind::UnixUser2, ReloadConfigs::forcing());
         self.ctx.attach(signal);
 
+        let watch_interval = self.ctx.attach(Interval::new(WatchTick));
+        self.update_watching(&watch_interval);
+
         while let Some(envelope) = match first_envelope.take() {
             e @ Some(..) => e,
             None => self.ctx.recv().await,
@@ -185,64 +331,174 @@ impl Configurer {
             msg!(match envelope {
                 (ReloadConfigs { force }, token) => {
                     let response = self
-                        .load_and_update_configs(force)
+                        .load_and_update_configs(force, None)
+                        .await
+                        .map_err(|errors| ReloadConfigsRejected { errors });
+
+                    self.ctx.respond(token, response);
+                }
+                GroupMounted { name, .. } => {
+                    // Errors are already logged, actors just aren't started.
+                    let _ = self.load_and_update_configs(true, Some(&name)).await;
+                }
+                GroupUnmounted { name, .. } => {
+                    self.versions.remove(&name);
+                    self.applied.remove(&name);
+                }
+                (GetConfigHistory, token) => {
+                    let versions = self.history.iter().map(|(v, _)| v.clone()).collect();
+                    self.ctx.respond(token, ConfigHistory { versions });
+                }
+                (RollbackConfig { version }, token) => {
+                    let response = self
+                        .rollback_configs(version)
                         .await
                         .map_err(|errors| ReloadConfigsRejected { errors });
 
                     self.ctx.respond(token, response);
                 }
+                ConfigUpdated => self.update_watching(&watch_interval),
+                WatchTick => {
+                    let debounce = self.ctx.config().watch.debounce;
+                    let watcher = self.watcher.as_mut().expect("watching without a file");
+
+                    if watcher.check(debounce).await {
+                        info!("the config file is changed");
+                        // Errors are already logged and reflected in the status.
+                        let _ = self.load_and_update_configs(false, None).await;
+                    }
+                }
             })
         }
     }
 
-    async fn load_configs(&self) -> Result<Value, Vec<ReloadConfigsError>> {
+    fn update_watching(&self, interval: &Interval<WatchTick>) {
+        let config = &self.ctx.config().watch;
+
+        if config.enabled && self.watcher.is_some() {
+            interval.start(config.interval);
+        } else {
+            interval.stop();
+        }
+    }
+
+    async fn load_configs(&self) -> Result<Merged, Vec<ReloadConfigsError>> {
         let config = match &self.source {
             ConfigSource::File(path) => {
                 info!(message = "loading a config", path = %path.to_string_lossy());
-                load_raw_config(path).await
+                load_raw_config(path).await.map(Merged::from)
+            }
+            ConfigSource::Layers(layers) => {
+                info!(message = "loading layered configs", ?layers);
+                layers.load().await
             }
             ConfigSource::Fixture(value) => {
                 info!("using a fixture");
-                value.clone()
+                value.clone().map(Merged::from)
             }
         };
 
-        let config = match config {
+        let mut config = match config {
             Ok(config) => config,
             Err(error) => {
                 error!(%error, "invalid config");
                 return Err(vec![ReloadConfigsError {
                     group: scope::meta().group.clone(),
                     reason: error,
+                    layers: Vec::new(),
                 }]);
             }
         };
 
-        Deserialize::deserialize(config).map_err(|error| {
+        config.value = Deserialize::deserialize(config.value).map_err(|error| {
             error!(%error, "invalid config");
             vec![ReloadConfigsError {
                 group: scope::meta().group.clone(),
                 reason: error.to_string(),
+                layers: Vec::new(),
             }]
-        })
+        })?;
+
+        Ok(config)
     }
 
     async fn load_and_check_configs(&self) -> Result<(), Vec<ReloadConfigsError>> {
-        let configs = self.load_configs().await?;
+        let merged = self.load_configs().await?;
 
         // Here we rely on the fact that the first `ValidateConfig` message is consumed
         // by the supervisor and no actors are actually started.
-        let configs = match_configs(&self.topology, &configs);
-        self.validate_all(&configs).await
+        let configs = match_configs(&self.topology, &merged.value);
+        self.validate_all(&configs, &merged).await
     }
 
     async fn load_and_update_configs(
         &mut self,
         force: bool,
+        only_group: Option<&str>,
     ) -> Result<(), Vec<ReloadConfigsError>> {
-        let configs = self.load_configs().await?;
+        let result = self.do_load_and_update_configs(force, only_group).await;
+        self.report_status(&result);
+        result
+    }
 
-        let mut configs = match_configs(&self.topology, &configs);
+    async fn rollback_configs(&mut self, version: u64) -> Result<(), Vec<ReloadConfigsError>> {
+        let Some((_, value)) = self.history.iter().find(|(v, _)| v.version == version) else {
+            error!(version, "cannot roll back configs to an unknown version");
+            return Err(vec![ReloadConfigsError {
+                group: scope::meta().group.clone(),
+                reason: format!("unknown version {version}"),
+                layers: Vec::new(),
+            }]);
+        };
+
+        info!(version, "rolling back configs");
+        let merged = Merged::from(value.clone());
+        let result = self
+            .update_configs(merged, false, None, Some(version))
+            .await;
+        self.report_status(&result);
+        result
+    }
+
+    fn report_status(&self, result: &Result<(), Vec<ReloadConfigsError>>) {
+        // Invalid configs are kept being reported until a successful reload.
+        let status = match result {
+            Ok(()) => ActorStatus::NORMAL,
+            Err(_) => ActorStatus::ALARMING.with_details("configs are invalid"),
+        };
+        self.ctx.set_status(status);
+    }
+
+    async fn do_load_and_update_configs(
+        &mut self,
+        force: bool,
+        only_group: Option<&str>,
+    ) -> Result<(), Vec<ReloadConfigsError>> {
+        if let Some(watcher) = &mut self.watcher {
+            watcher.on_loading().await;
+        }
+
+        let merged = self.load_configs().await?;
+
+        if let (Some(watcher), ConfigSource::Layers(_)) = (&mut self.watcher, &self.source) {
+            watcher.on_loaded(merged.files.clone()).await;
+        }
+
+        self.update_configs(merged, force, only_group, None).await
+    }
+
+    async fn update_configs(
+        &mut self,
+        merged: Merged,
+        force: bool,
+        only_group: Option<&str>,
+        rollback_to: Option<u64>,
+    ) -> Result<(), Vec<ReloadConfigsError>> {
+        let mut configs = match_configs(&self.topology, &merged.value);
+
+        if let Some(group) = only_group {
+            configs.retain(|c| c.group_name == group);
+        }
 
         // Filter out up-to-date configs if needed.
         if !force {
@@ -266,9 +522,8 @@ impl Configurer {
         let status = ActorStatus::NORMAL.with_details("validating");
         self.ctx.set_status(status);
 
-        if let Err(errors) = self.validate_all(&configs).await {
+        if let Err(errors) = self.validate_all(&configs, &merged).await {
             error!("config validation failed");
-            self.ctx.set_status(ActorStatus::NORMAL);
             return Err(errors);
         }
 
@@ -277,7 +532,10 @@ impl Configurer {
         self.ctx.set_status(status);
         self.update_all(&configs).await;
 
-        self.ctx.set_status(ActorStatus::NORMAL);
+        // Groups mounted at runtime don't produce new versions.
+        if only_group.is_none() {
+            self.push_version(&configs, merged.value, rollback_to);
+        }
 
         // Update versions.
         let updated_groups: Vec<String> = configs
@@ -285,7 +543,10 @@ impl Configurer {
             .inspect(|config| {
                 self.versions.insert(config.group_name.clone(), config.hash);
             })
-            .map(|config| config.group_name)
+            .map(|config| {
+                self.applied.insert(config.group_name.clone(), config.value);
+                config.group_name
+            })
             .collect();
 
         info!(
@@ -296,9 +557,47 @@ impl Configurer {
         Ok(())
     }
 
+    fn push_version(&mut self, configs: &[ConfigWithMeta], value: Value, rollback_to: Option<u64>) {
+        let config = self.ctx.config();
+        let mut changes = Vec::new();
+
+        for item in configs {
+            let Some(old) = self.applied.get(&item.group_name) else {
+                continue;
+            };
+
+            let group_changes =
+                diff::diff(&item.group_name, old, &item.value, &config.unmasked_keys);
+
+            if !group_changes.is_empty() {
+                info!(
+                    message = "group's config is changed",
+                    group = %item.group_name,
+                    changes = %diff::describe(&group_changes),
+                );
+            }
+
+            changes.extend(group_changes);
+        }
+
+        self.last_version += 1;
+        let version = ConfigVersion {
+            version: self.last_version,
+            applied_at: SystemTime::now(),
+            rollback_to,
+            changes,
+        };
+
+        self.history.push_back((version, value));
+        while self.history.len() > config.history_size {
+            self.history.pop_front();
+        }
+    }
+
     async fn validate_all(
         &self,
         configs: &[ConfigWithMeta],
+        merged: &Merged,
     ) -> Result<(), Vec<ReloadConfigsError>> {
         let futures = configs
             .iter()
@@ -330,8 +629,14 @@ impl Configurer {
                 Ok(Err(reject)) => Some((group, reject.reason)),
             })
             // TODO: include actor keys in the error message.
-            .inspect(|(group, reason)| error!(%group, %reason, "invalid config"))
-            .map(|(group, reason)| ReloadConfigsError { group, reason })
+            .map(|(group, reason)| ReloadConfigsError {
+                layers: merged.layers_of(&group),
+                group,
+                reason,
+            })
+            .inspect(|e| {
+                error!(group = %e.group, reason = %e.reason, layers = ?e.layers, "invalid config")
+            })
             .collect::<Vec<_>>();
 
         if errors.is_empty() {
@@ -390,8 +695,9 @@ fn match_configs(topology: &Topology, config: &Value) -> Vec<ConfigWithMeta> {
             ConfigWithMeta {
                 group_name: group.name.clone(),
                 addr: group.addr,
-                hash: fxhash::hash64(&group_config),
-                config: AnyConfig::from_value(group_config),
+                hash: helpers::hash_config(&group_config),
+                config: AnyConfig::from_value(group_config.clone()),
+                value: group_config,
             }
         })
         .collect();
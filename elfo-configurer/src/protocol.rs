@@ -1,3 +1,5 @@
+use std::time::SystemTime;
+
 use elfo_core::message;
 
 /// The request to reload configs and send changed ones.
@@ -41,12 +43,98 @@ pub struct ReloadConfigsError {
     pub group: String,
     /// The reason why the config is rejected.
     pub reason: String,
+    /// Layers that provided values of the group's config, the latest go
+    /// first. Empty if configs aren't layered, see [`from_layers()`].
+    ///
+    /// It's an approximation: errors don't point to values, so all layers
+    /// contributing to the group's config are listed, even if only one of
+    /// them provided an invalid value.
+    ///
+    /// [`from_layers()`]: crate::from_layers
+    #[serde(default)]
+    pub layers: Vec<String>,
 }
 
 impl ReloadConfigsError {
     /// Creates a new error.
     #[cfg(feature = "test-util")]
     pub fn new(group: String, reason: String) -> Self {
-        Self { group, reason }
+        Self {
+            group,
+            reason,
+            layers: Vec::new(),
+        }
+    }
+}
+
+/// The request to get recently applied versions of configs, the latest go
+/// last. The number of kept versions is limited by `history_size`.
+#[message(ret = ConfigHistory)]
+#[derive(Default)]
+#[non_exhaustive]
+pub struct GetConfigHistory;
+
+/// The response to `GetConfigHistory`.
+#[message(part)]
+#[non_exhaustive]
+pub struct ConfigHistory {
+    /// Applied versions, the latest go last.
+    pub versions: Vec<ConfigVersion>,
+}
+
+/// An applied version of configs.
+#[message(part)]
+#[non_exhaustive]
+pub struct ConfigVersion {
+    /// The number of the version, starting from `1`.
+    pub version: u64,
+    /// When the version was applied.
+    pub applied_at: SystemTime,
+    /// The version which this one rolls back to, if it's created by
+    /// `RollbackConfig`.
+    pub rollback_to: Option<u64>,
+    /// Changes of configs comparing to the previous version.
+    /// Configs of groups applied for the first time aren't included.
+    pub changes: Vec<ConfigChange>,
+}
+
+/// A change of a value in configs.
+///
+/// Values are rendered in the TOML-like format. Only values of paths listed
+/// in `unmasked_keys` are shown, others are masked as `<masked>`.
+#[message(part)]
+#[non_exhaustive]
+pub struct ConfigChange {
+    /// The group whose config is changed.
+    pub group: String,
+    /// The path to the changed value inside the group's config,
+    /// e.g. `db.pool.size`.
+    pub path: String,
+    /// The previous value, `None` if the value is added.
+    pub old: Option<String>,
+    /// The new value, `None` if the value is removed.
+    pub new: Option<String>,
+    /// Whether values are masked.
+    #[serde(default)]
+    pub masked: bool,
+}
+
+/// The request to apply configs of the specified version from the history.
+/// If the validation stage is failed or the version is unknown,
+/// `ReloadConfigsRejected` is returned.
+///
+/// Rolled back configs are kept until the next reloading, e.g. by
+/// `ReloadConfigs` or once the config file is changed.
+#[message(ret = Result<(), ReloadConfigsRejected>)]
+#[non_exhaustive]
+pub struct RollbackConfig {
+    /// The version to roll back to, see `ConfigVersion::version`.
+    pub version: u64,
+}
+
+impl RollbackConfig {
+    /// Creates a new request.
+    pub fn new(version: u64) -> Self {
+        Self { version }
     }
 }
@@ -0,0 +1,94 @@
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use futures::future;
+use tokio::{fs, time::Instant};
+
+/// Detects changes of config files by polling their metadata.
+pub(crate) struct Watcher {
+    paths: Vec<PathBuf>,
+    /// The state of files at the last loading.
+    loaded: Vec<Option<Fingerprint>>,
+    /// The changed state and when it was noticed.
+    changed: Option<(Vec<Option<Fingerprint>>, Instant)>,
+}
+
+#[derive(Clone, PartialEq, Eq)]
+struct Fingerprint {
+    /// Changes if a symlink is swapped, e.g. in case of ConfigMaps.
+    target: PathBuf,
+    /// Changes if the file is replaced, even if `modified` is the same
+    /// due to the coarse resolution of timestamps.
+    #[cfg(unix)]
+    inode: u64,
+    modified: Option<SystemTime>,
+    len: u64,
+}
+
+impl Watcher {
+    pub(crate) fn new(paths: Vec<PathBuf>) -> Self {
+        Self {
+            paths,
+            loaded: Vec::new(),
+            changed: None,
+        }
+    }
+
+    /// Should be called right before loading files.
+    pub(crate) async fn on_loading(&mut self) {
+        self.loaded = fingerprints(&self.paths).await;
+        self.changed = None;
+    }
+
+    /// Should be called after loading files, if the set of files depends on
+    /// their content (e.g. because of includes).
+    pub(crate) async fn on_loaded(&mut self, paths: Vec<PathBuf>) {
+        if paths != self.paths {
+            self.paths = paths;
+            self.loaded = fingerprints(&self.paths).await;
+        }
+    }
+
+    /// Returns `true` if files are changed and then have stayed unchanged
+    /// for at least `debounce`.
+    pub(crate) async fn check(&mut self, debounce: Duration) -> bool {
+        let actual = fingerprints(&self.paths).await;
+
+        if actual == self.loaded {
+            self.changed = None;
+            return false;
+        }
+
+        // Files can be missing for a while during replacement.
+        if actual.iter().any(Option::is_none) {
+            return false;
+        }
+
+        match &self.changed {
+            Some((changed, since)) if *changed == actual => since.elapsed() >= debounce,
+            _ => {
+                self.changed = Some((actual, Instant::now()));
+                debounce.is_zero()
+            }
+        }
+    }
+}
+
+async fn fingerprints(paths: &[PathBuf]) -> Vec<Option<Fingerprint>> {
+    future::join_all(paths.iter().map(|path| fingerprint(path))).await
+}
+
+async fn fingerprint(path: &Path) -> Option<Fingerprint> {
+    let target = fs::canonicalize(path).await.ok()?;
+    let metadata = fs::metadata(&target).await.ok()?;
+
+    Some(Fingerprint {
+        target,
+        #[cfg(unix)]
+        inode: std::os::unix::fs::MetadataExt::ino(&metadata),
+        modified: metadata.modified().ok(),
+        len: metadata.len(),
+    })
+}
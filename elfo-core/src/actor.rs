@@ -4,22 +4,29 @@ use std::{
 };
 
 use futures_intrusive::sync::ManualResetEvent;
-use metrics::{decrement_gauge, increment_counter, increment_gauge};
-use parking_lot::RwLock;
+use metrics::{counter, decrement_gauge, increment_counter, increment_gauge};
+use parking_lot::{Mutex, RwLock};
 use serde::{Deserialize, Serialize};
 use tracing::{error, info, warn};
 
+use elfo_utils::unlikely;
+
 use crate::{
     actor_status::{ActorStatus, ActorStatusKind, AtomicActorStatusKind},
     envelope::Envelope,
     errors::{SendError, TrySendError},
     group::TerminationPolicy,
-    mailbox::{config::MailboxConfig, Mailbox, RecvResult},
+    mailbox::{
+        config::{MailboxConfig, OverflowPolicy, RateLimitConfig},
+        Mailbox, RecvResult,
+    },
+    message::{AnyMessage, Message},
     messages::{ActorStatusReport, Terminate},
     msg,
     request_table::RequestTable,
-    restarting::RestartPolicy,
+    restarting::{CircuitState, RestartPolicy},
     scope,
+    stash::Stasher,
     subscription::SubscriptionManager,
     Addr,
 };
@@ -57,6 +64,11 @@ pub struct ActorStartInfo {
     /// The cause for the actor start, indicating why the actor is being
     /// initialized.
     pub cause: ActorStartCause,
+    /// How many times the actor with the same key has been restarted,
+    /// regardless of the cause. `0` for the first start.
+    pub restart_count: u64,
+    // Messages aren't `Sync`, but `Context` must be.
+    recovered_state: Option<Arc<Mutex<AnyMessage>>>,
 }
 
 /// An enum representing various causes for an actor to start.
@@ -69,26 +81,67 @@ pub enum ActorStartCause {
     OnMessage,
     /// The actor started due to the restart policy.
     Restarted,
+    /// The actor was restarted because another actor in the same group
+    /// failed, according to [`SupervisionStrategy`].
+    ///
+    /// [`SupervisionStrategy`]: crate::SupervisionStrategy
+    SiblingFailed {
+        /// The key of the failed actor.
+        key: String,
+    },
+    /// The actor was restarted because the failure of an actor in another
+    /// group has been escalated, see [`Local::escalate_to()`].
+    ///
+    /// [`Local::escalate_to()`]: crate::topology::Local::escalate_to
+    Escalated {
+        /// The name of the group containing the failed actor.
+        group: String,
+    },
 }
 
 impl ActorStartInfo {
     pub(crate) fn on_group_mounted() -> Self {
         Self {
             cause: ActorStartCause::GroupMounted,
+            restart_count: 0,
+            recovered_state: None,
         }
     }
 
     pub(crate) fn on_message() -> Self {
         Self {
             cause: ActorStartCause::OnMessage,
+            restart_count: 0,
+            recovered_state: None,
         }
     }
 
-    pub(crate) fn on_restart() -> Self {
+    pub(crate) fn on_restart(cause: ActorStartCause, restart_count: u64) -> Self {
         Self {
-            cause: ActorStartCause::Restarted,
+            cause,
+            restart_count,
+            recovered_state: None,
         }
     }
+
+    pub(crate) fn with_recovered_state(self, recovered_state: Option<AnyMessage>) -> Self {
+        Self {
+            recovered_state: recovered_state.map(|state| Arc::new(Mutex::new(state))),
+            ..self
+        }
+    }
+
+    /// Returns a copy of the state stashed by the previous incarnation of the
+    /// actor using [`Context::stash_state()`], if it has the type `S`.
+    ///
+    /// [`Context::stash_state()`]: crate::Context::stash_state
+    pub fn recovered_state<S: Message>(&self) -> Option<S> {
+        self.recovered_state
+            .as_ref()?
+            .lock()
+            .downcast_ref::<S>()
+            .cloned()
+    }
 }
 
 impl ActorStartCause {
@@ -96,8 +149,15 @@ impl ActorStartCause {
         matches!(self, ActorStartCause::GroupMounted)
     }
 
+    /// Returns `true` if the actor was restarted for any reason: due to the
+    /// restart policy, the supervision strategy or an escalated failure.
     pub fn is_restarted(&self) -> bool {
-        matches!(self, ActorStartCause::Restarted)
+        matches!(
+            self,
+            ActorStartCause::Restarted
+                | ActorStartCause::SiblingFailed { .. }
+                | ActorStartCause::Escalated { .. }
+        )
     }
 
     pub fn is_on_message(&self) -> bool {
@@ -116,12 +176,18 @@ pub(crate) struct Actor {
     control: RwLock<Control>,
     finished: ManualResetEvent, // TODO: remove in favor of `status_subscription`?
     status_subscription: Arc<SubscriptionManager>,
+    /// The order of the first start of the actor with the same key in the group.
+    start_order: u64,
+    stasher: Stasher,
 }
 
 struct Control {
     status: ActorStatus,
     /// If `None`, a group's policy will be used.
     restart_policy: Option<RestartPolicy>,
+    circuit: CircuitState,
+    /// Set if the actor is stopped by the supervisor in order to be restarted.
+    forced_restart: Option<ActorStartCause>,
     /// A mailbox capacity set in the config.
     mailbox_capacity_config: usize,
     /// Explicitly set mailbox capacity via `Context::set_mailbox_capacity()`.
@@ -135,24 +201,44 @@ impl Actor {
         mailbox_config: &MailboxConfig,
         termination_policy: TerminationPolicy,
         status_subscription: Arc<SubscriptionManager>,
+        stasher: Stasher,
     ) -> Self {
         Actor {
+            stasher,
             status_kind: AtomicActorStatusKind::from(ActorStatusKind::Initializing),
+            mailbox: Mailbox::new(mailbox_config, meta.clone()),
             meta,
             termination_policy,
-            mailbox: Mailbox::new(mailbox_config),
             request_table: RequestTable::new(addr),
             control: RwLock::new(Control {
                 status: ActorStatus::INITIALIZING,
                 restart_policy: None,
+                circuit: CircuitState::Closed,
+                forced_restart: None,
                 mailbox_capacity_config: mailbox_config.capacity,
                 mailbox_capacity_override: None,
             }),
             finished: ManualResetEvent::new(false),
             status_subscription,
+            start_order: 0,
         }
     }
 
+    pub(crate) fn with_start_order(mut self, start_order: u64) -> Self {
+        self.start_order = start_order;
+        self
+    }
+
+    pub(crate) fn start_order(&self) -> u64 {
+        self.start_order
+    }
+
+    /// Sets the initial state of the circuit breaker, used before `on_start()`.
+    pub(crate) fn with_circuit(mut self, circuit: CircuitState) -> Self {
+        self.control.get_mut().circuit = circuit;
+        self
+    }
+
     pub(crate) fn on_start(&self) {
         increment_gauge!("elfo_active_actors", 1.,
             "status" => ActorStatusKind::Initializing.as_str());
@@ -199,11 +285,36 @@ impl Actor {
     }
 
     pub(crate) async fn recv(&self) -> RecvResult {
-        self.mailbox.recv().await
+        let result = self.mailbox.recv().await;
+        self.emit_mailbox_counters();
+        result
     }
 
     pub(crate) fn try_recv(&self) -> Option<RecvResult> {
-        self.mailbox.try_recv()
+        let result = self.mailbox.try_recv();
+        self.emit_mailbox_counters();
+        result
+    }
+
+    // Messages are dropped and limited in the sender's scope, so metrics are
+    // emitted by the receiver in order to be attributed to the right actor.
+    // Also, the supervisor emits them on `Ping` in the actor's scope, because
+    // stuck actors don't receive messages.
+    #[inline]
+    pub(crate) fn emit_mailbox_counters(&self) {
+        let dropped = self.mailbox.take_dropped();
+        if unlikely(dropped > 0) {
+            counter!("elfo_dropped_messages_total", dropped);
+        }
+
+        let rate_limited = self.mailbox.take_rate_limited();
+        if unlikely(rate_limited > 0) {
+            counter!("elfo_rate_limited_messages_total", rate_limited);
+        }
+    }
+
+    pub(crate) fn mailbox_len(&self) -> usize {
+        self.mailbox.len()
     }
 
     pub(crate) fn request_table(&self) -> &RequestTable {
@@ -220,6 +331,14 @@ impl Actor {
         self.update_mailbox_capacity();
     }
 
+    pub(crate) fn set_mailbox_overflow_policy(&self, on_overflow: OverflowPolicy) {
+        self.mailbox.set_overflow_policy(on_overflow);
+    }
+
+    pub(crate) fn set_mailbox_rate_limit(&self, config: &RateLimitConfig) {
+        self.mailbox.set_rate_limit(config);
+    }
+
     fn update_mailbox_capacity(&self) {
         let control = self.control.read();
 
@@ -281,6 +400,62 @@ impl Actor {
         //       or use another actor to listen all statuses for this.
     }
 
+    pub(crate) fn set_circuit(&self, circuit: CircuitState) {
+        let mut control = self.control.write();
+        let prev_circuit = mem::replace(&mut control.circuit, circuit);
+
+        if circuit == prev_circuit {
+            return;
+        }
+
+        self.send_status_to_subscribers(&control);
+        drop(control);
+
+        match circuit {
+            CircuitState::Open => warn!(?circuit, "circuit breaker changed"),
+            _ => info!(?circuit, "circuit breaker changed"),
+        }
+    }
+
+    pub(crate) fn stash_state(&self, state: AnyMessage) {
+        self.stasher.save(state);
+    }
+
+    pub(crate) fn stasher(&self) -> Stasher {
+        self.stasher.clone()
+    }
+
+    /// Stops the actor by closing its mailbox in order to restart it with the
+    /// provided cause. Messages already in the mailbox are still handled.
+    /// Returns `false` if the actor is already finished.
+    pub(crate) fn force_restart(&self, cause: ActorStartCause) -> bool {
+        let mut control = self.control.write();
+        if control.status.kind().is_finished() || control.forced_restart.is_some() {
+            return false;
+        }
+
+        control.forced_restart = Some(cause);
+        drop(control);
+
+        self.close();
+        true
+    }
+
+    pub(crate) fn take_forced_restart(&self) -> Option<ActorStartCause> {
+        self.control.write().forced_restart.take()
+    }
+
+    /// Closes the mailbox and takes all queued messages.
+    /// Used if the actor hasn't drained its mailbox in time.
+    pub(crate) fn discard_queued(&self) -> Vec<Envelope> {
+        self.close();
+        self.mailbox.take_all()
+    }
+
+    pub(crate) fn meta(&self) -> &Arc<ActorMeta> {
+        &self.meta
+    }
+
     #[cold]
     #[inline(never)]
     pub(crate) fn close(&self) -> bool {
@@ -297,6 +472,7 @@ impl Actor {
         f(ActorStatusReport {
             meta: self.meta.clone(),
             status: control.status.clone(),
+            circuit: control.circuit,
         })
     }
 
@@ -304,6 +480,7 @@ impl Actor {
         self.status_subscription.send(ActorStatusReport {
             meta: self.meta.clone(),
             status: control.status.clone(),
+            circuit: control.circuit,
         });
     }
 }
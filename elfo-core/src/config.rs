@@ -4,6 +4,7 @@
 
 use std::{
     any::{Any, TypeId},
+    cell::Cell,
     fmt, mem,
     ops::Deref,
     str::FromStr,
@@ -14,7 +15,11 @@ use derive_more::From;
 use serde::{de, de::value::Error as DeError, Deserialize, Deserializer, Serialize, Serializer};
 use serde_value::{Value, ValueDeserializer};
 
-use crate::{local::Local, panic};
+use crate::{
+    local::Local,
+    panic,
+    scope::{self, SerdeMode},
+};
 
 /// Represents any user-defined config.
 ///
@@ -83,7 +88,12 @@ impl AnyConfig {
     }
 
     pub(crate) fn decode<C: Config>(&self) -> Result<AnyConfig, String> {
-        match panic::sync_catch(|| self.do_decode::<C>()) {
+        let refs = match SECRET_REFS.with(Cell::get) {
+            SecretRefs::Skip => SecretRefs::Skip,
+            _ => SecretRefs::Resolve,
+        };
+
+        match panic::sync_catch(|| with_secret_refs(refs, || self.do_decode::<C>())) {
             Ok(Ok(config)) => Ok(config),
             Ok(Err(err)) => Err(err),
             Err(panic) => Err(panic),
@@ -188,6 +198,51 @@ impl<'de> Deserializer<'de> for AnyConfig {
     }
 }
 
+// === ConfigSpec ===
+
+/// Type-erased operations with the group's config type, used without
+/// starting actors, e.g. to validate configs offline.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ConfigSpec {
+    decode: fn(&AnyConfig) -> Result<AnyConfig, String>,
+    #[cfg(feature = "schema")]
+    schema: Option<fn(&mut schemars::SchemaGenerator) -> schemars::Schema>,
+}
+
+impl ConfigSpec {
+    pub(crate) fn of<C: Config>() -> Self {
+        Self {
+            decode: AnyConfig::decode::<C>,
+            #[cfg(feature = "schema")]
+            schema: None,
+        }
+    }
+
+    #[cfg(feature = "schema")]
+    pub(crate) fn with_schema<C: Config + schemars::JsonSchema>(mut self) -> Self {
+        self.schema = Some(|generator| generator.subschema_for::<C>());
+        self
+    }
+
+    pub(crate) fn check(&self, config: &AnyConfig) -> Result<(), String> {
+        (self.decode)(config).map(drop)
+    }
+
+    #[cfg(feature = "schema")]
+    pub(crate) fn schema(
+        &self,
+        generator: &mut schemars::SchemaGenerator,
+    ) -> Option<schemars::Schema> {
+        self.schema.map(|schema| schema(generator))
+    }
+}
+
+impl Default for ConfigSpec {
+    fn default() -> Self {
+        Self::of::<()>()
+    }
+}
+
 // === SystemConfig ===
 
 pub mod system {
@@ -203,7 +258,8 @@ pub mod system {
 
     pub use crate::{
         dumping::config as dumping, logging::config as logging, mailbox::config as mailbox,
-        restarting::config as restart_policy, telemetry::config as telemetry,
+        restarting::config as restart_policy, shutdown::config as shutdown,
+        telemetry::config as telemetry,
     };
 
     /// The `system.*` section in configs.
@@ -216,8 +272,10 @@ pub mod system {
     /// system.dumping.max_rate = 10_000
     /// system.telemetry.per_actor_key = true
     /// system.restart_policy.when = "Never"
+    /// system.shutdown.drain_timeout = "5s"
     /// ```
     #[derive(Debug, Default, Deserialize)]
+    #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
     #[serde(default)]
     pub struct SystemConfig {
         /// Mailbox configuration.
@@ -230,6 +288,8 @@ pub mod system {
         pub telemetry: telemetry::TelemetryConfig,
         /// Restarting configuration.
         pub restart_policy: restart_policy::RestartPolicyConfig,
+        /// Shutdown configuration.
+        pub shutdown: shutdown::ShutdownConfig,
     }
 }
 
@@ -241,7 +301,19 @@ pub(crate) use system::SystemConfig;
 /// So, it's useful for storing sensitive data like credentials.
 ///
 /// * `Debug` and `Display` instances prints `<secret>` instead of real value.
-/// * `Deserialize` expects a real value.
+/// * `Deserialize` expects a real value or a reference to it:
+///   * `{ file = "/run/secrets/x" }` reads the file, trailing newlines are
+///     trimmed.
+///   * `{ env = "VAR" }` reads the environment variable.
+///
+///   Referenced values are deserialized from strings. If `T` isn't a string,
+///   the value is parsed as JSON, e.g. `42` or `true`. They are read every
+///   time the config is decoded, so reloading configs picks up rotated
+///   secrets.
+///
+///   References are resolved only while configs are decoded by actor groups,
+///   other deserialization (e.g. of messages received from other nodes)
+///   rejects them. See also [`skip_secrets()`].
 /// * `Serialize` depends on the current [serde mode]:
 ///   * In the `Network` mode it's serialized as the real value.
 ///   * In the `Dumping` and `Normal` modes it's serialized as `"<secret>"`.
@@ -257,6 +329,14 @@ pub(crate) use system::SystemConfig;
 ///     credentials: Secret<String>,
 /// }
 /// ```
+///
+/// ```toml
+/// credentials = "plain text"
+/// # or
+/// credentials = { file = "/run/secrets/credentials" }
+/// # or
+/// credentials = { env = "CREDENTIALS" }
+/// ```
 #[derive(Clone, Copy, PartialEq, Eq, Default, From)]
 pub struct Secret<T>(T);
 
@@ -297,16 +377,290 @@ impl<T: FromStr> FromStr for Secret<T> {
 
 impl<'de, T: Deserialize<'de>> Deserialize<'de> for Secret<T> {
     fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
-        T::deserialize(deserializer).map(Self)
+        let value = Value::deserialize(deserializer)?;
+        let Some(source) = SecretSource::parse(&value) else {
+            return T::deserialize(ValueDeserializer::<D::Error>::new(value)).map(Self);
+        };
+
+        if scope::serde_mode() == SerdeMode::Network {
+            let message = format!("secret reference to {source} cannot be received over network");
+            return Err(de::Error::custom(message));
+        }
+
+        match SECRET_REFS.with(Cell::get) {
+            SecretRefs::Resolve => {}
+            SecretRefs::Reject => {
+                let message = format!("secret reference to {source} outside configs");
+                return Err(de::Error::custom(message));
+            }
+            SecretRefs::Skip => {
+                return PLACEHOLDERS
+                    .iter()
+                    .find_map(|placeholder| {
+                        let de = ValueDeserializer::<D::Error>::new(placeholder());
+                        T::deserialize(de).ok()
+                    })
+                    .map(Self)
+                    .ok_or_else(|| {
+                        de::Error::custom(format!("no placeholder for secret from {source}"))
+                    });
+            }
+        }
+
+        let content = source.read().map_err(de::Error::custom)?;
+
+        // Referenced values are strings, but `T` can be a number, a bool, etc.
+        let parsed = serde_json::from_str::<Value>(&content)
+            .ok()
+            .filter(|value| !matches!(value, Value::String(_)));
+
+        T::deserialize(ValueDeserializer::<D::Error>::new(Value::String(content)))
+            .or_else(|err| match parsed {
+                Some(parsed) => T::deserialize(ValueDeserializer::<D::Error>::new(parsed)),
+                None => Err(err),
+            })
+            .map(Self)
+            .map_err(|err| de::Error::custom(format!("invalid secret from {source}: {err}")))
+    }
+}
+
+thread_local! {
+    static SECRET_REFS: Cell<SecretRefs> = const { Cell::new(SecretRefs::Reject) };
+}
+
+/// How `Secret` handles references to values stored outside configs.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SecretRefs {
+    /// Outside config decoding, e.g. in messages.
+    Reject,
+    /// Read referenced values, while configs are decoded.
+    Resolve,
+    /// Use placeholders instead of referenced values, see [`skip_secrets()`].
+    Skip,
+}
+
+fn with_secret_refs<R>(refs: SecretRefs, f: impl FnOnce() -> R) -> R {
+    // We use a guard here to restore the current mode even on panics.
+    struct Guard(SecretRefs);
+    impl Drop for Guard {
+        fn drop(&mut self) {
+            SECRET_REFS.with(|cell| cell.set(self.0));
+        }
+    }
+
+    let refs = SECRET_REFS.with(|cell| cell.replace(refs));
+    let _guard = Guard(refs);
+    f()
+}
+
+/// Runs the function, replacing references to secrets in decoded configs
+/// with placeholders instead of reading them. The first placeholder accepted
+/// by the secret's type is used: an empty string, `0`, `false`, an empty
+/// sequence, an empty map or none.
+///
+/// Useful to validate configs where secrets aren't accessible, e.g. to wrap
+/// `elfo::batteries::configurer::validate_file()` in CI.
+pub fn skip_secrets<R>(f: impl FnOnce() -> R) -> R {
+    with_secret_refs(SecretRefs::Skip, f)
+}
+
+const PLACEHOLDERS: &[fn() -> Value] = &[
+    || Value::String(String::new()),
+    || Value::U64(0),
+    || Value::Bool(false),
+    || Value::Seq(Vec::new()),
+    || Value::Map(Default::default()),
+    || Value::Option(None),
+];
+
+/// A reference to the secret's value stored outside configs.
+enum SecretSource<'a> {
+    File(&'a str),
+    Env(&'a str),
+}
+
+impl<'a> SecretSource<'a> {
+    fn parse(value: &'a Value) -> Option<Self> {
+        let Value::Map(map) = value else {
+            return None;
+        };
+
+        if map.len() != 1 {
+            return None;
+        }
+
+        match map.iter().next()? {
+            (Value::String(key), Value::String(arg)) if key == "file" => Some(Self::File(arg)),
+            (Value::String(key), Value::String(arg)) if key == "env" => Some(Self::Env(arg)),
+            _ => None,
+        }
+    }
+
+    fn read(&self) -> Result<String, String> {
+        match self {
+            Self::File(path) => std::fs::read_to_string(path)
+                .map(|content| content.trim_end_matches(['\r', '\n']).into())
+                .map_err(|err| format!("cannot read the secret from {self}: {err}")),
+            Self::Env(name) => std::env::var(name)
+                .map_err(|err| format!("cannot read the secret from {self}: {err}")),
+        }
+    }
+}
+
+impl fmt::Display for SecretSource<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::File(path) => write!(f, "`{path}`"),
+            Self::Env(name) => write!(f, "`{name}` env var"),
+        }
+    }
+}
+
+#[cfg(feature = "schema")]
+impl<T: schemars::JsonSchema> schemars::JsonSchema for Secret<T> {
+    fn inline_schema() -> bool {
+        true
+    }
+
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        format!("Secret_of_{}", T::schema_name()).into()
+    }
+
+    fn json_schema(generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        let reference = |key: &str| {
+            schemars::json_schema!({
+                "type": "object",
+                "properties": { key: { "type": "string" } },
+                "required": [key],
+                "additionalProperties": false,
+            })
+        };
+
+        schemars::json_schema!({
+            "anyOf": [generator.subschema_for::<T>(), reference("file"), reference("env")],
+        })
     }
 }
 
 impl<T: Serialize> Serialize for Secret<T> {
     fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
-        if crate::scope::serde_mode() != crate::scope::SerdeMode::Network {
+        if scope::serde_mode() != SerdeMode::Network {
             serializer.serialize_str("<secret>")
         } else {
             self.0.serialize(serializer)
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secret(toml: &str) -> Result<Secret<String>, String> {
+        #[derive(Deserialize)]
+        struct Config {
+            secret: Secret<String>,
+        }
+
+        let value: Value = toml::from_str(toml).unwrap();
+        with_secret_refs(SecretRefs::Resolve, || Config::deserialize(value))
+            .map(|config| config.secret)
+            .map_err(|err| err.to_string())
+    }
+
+    #[test]
+    fn secret_sources() {
+        assert_eq!(*secret(r#"secret = "plain""#).unwrap(), "plain");
+
+        let path = std::env::temp_dir().join(format!("elfo-secret-{}", std::process::id()));
+        std::fs::write(&path, "from file\n").unwrap();
+        let toml = format!("secret = {{ file = {:?} }}", path.display().to_string());
+        assert_eq!(*secret(&toml).unwrap(), "from file");
+
+        // Rotated secrets are read again.
+        std::fs::write(&path, "rotated").unwrap();
+        assert_eq!(*secret(&toml).unwrap(), "rotated");
+        std::fs::remove_file(&path).unwrap();
+        let error = secret(&toml).unwrap_err();
+        assert!(error.starts_with("cannot read the secret"), "{error}");
+
+        std::env::set_var("ELFO_TEST_SECRET", "from env");
+        let toml = r#"secret = { env = "ELFO_TEST_SECRET" }"#;
+        assert_eq!(*secret(toml).unwrap(), "from env");
+
+        let toml = r#"secret = { env = "ELFO_TEST_MISSING_SECRET" }"#;
+        let error = secret(toml).unwrap_err();
+        assert!(error.starts_with("cannot read the secret"), "{error}");
+
+        // Other maps are passed as is.
+        let toml = r#"secret = { file = "x", env = "y" }"#;
+        let error = secret(toml).unwrap_err();
+        assert!(error.starts_with("Invalid type map"), "{error}");
+    }
+
+    #[test]
+    fn non_string_secrets() {
+        #[derive(Deserialize)]
+        struct Config {
+            port: Secret<u16>,
+        }
+
+        let port = |toml: &str| {
+            let value: Value = toml::from_str(toml).unwrap();
+            with_secret_refs(SecretRefs::Resolve, || Config::deserialize(value))
+                .map(|config| *config.port)
+                .map_err(|err| err.to_string())
+        };
+
+        std::env::set_var("ELFO_TEST_SECRET_PORT", "8080");
+        let toml = r#"port = { env = "ELFO_TEST_SECRET_PORT" }"#;
+        assert_eq!(port(toml), Ok(8080));
+
+        std::env::set_var("ELFO_TEST_SECRET_BAD_PORT", "http");
+        let error = port(r#"port = { env = "ELFO_TEST_SECRET_BAD_PORT" }"#).unwrap_err();
+        assert!(
+            error.starts_with("invalid secret from `ELFO_TEST_SECRET_BAD_PORT` env var"),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn secret_refs() {
+        #[derive(Debug, Deserialize)]
+        #[allow(dead_code)]
+        struct Config {
+            secret: Secret<String>,
+            port: Secret<u16>,
+        }
+
+        std::env::set_var("ELFO_TEST_SECRET_REFS", "42");
+        let toml = r#"
+            secret = { env = "ELFO_TEST_SECRET_REFS" }
+            port = { env = "ELFO_TEST_SECRET_REFS" }
+        "#;
+        let value: Value = toml::from_str(toml).unwrap();
+        let decode = || AnyConfig::from_value(value.clone()).decode::<Config>();
+
+        // Resolved only while configs are decoded.
+        let config = decode().unwrap();
+        assert_eq!(*config.get_user::<Config>().port, 42);
+        let error = Config::deserialize(value.clone()).unwrap_err().to_string();
+        assert!(error.contains("outside configs"), "{error}");
+
+        // Never resolved in messages received over network.
+        let error = with_secret_refs(SecretRefs::Resolve, || {
+            scope::with_serde_mode(SerdeMode::Network, || Config::deserialize(value.clone()))
+        })
+        .unwrap_err()
+        .to_string();
+        assert!(error.contains("cannot be received over network"), "{error}");
+
+        // Replaced with placeholders if skipped.
+        std::env::remove_var("ELFO_TEST_SECRET_REFS");
+        assert!(decode().is_err());
+        let config = skip_secrets(decode).unwrap();
+        let config = config.get_user::<Config>();
+        assert_eq!(*config.secret, "");
+        assert_eq!(*config.port, 0);
+    }
+}
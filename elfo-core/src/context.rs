@@ -1,9 +1,10 @@
 use std::{future::poll_fn, marker::PhantomData, pin::Pin, sync::Arc, task::Poll};
 
-use futures::{pin_mut, Stream};
+use futures::{channel::mpsc, pin_mut, Stream, StreamExt as _};
 use idr_ebr::EbrGuard;
 use once_cell::sync::Lazy;
-use tracing::{info, trace};
+use tokio::time::{Duration, Instant};
+use tracing::{info, trace, warn};
 
 use elfo_utils::unlikely;
 
@@ -19,14 +20,17 @@ use crate::{
     envelope::{Envelope, MessageKind},
     errors::{RequestError, SendError, TryRecvError, TrySendError},
     mailbox::RecvResult,
-    message::{Message, Request},
+    message::{AnyMessage, Message, Request, StreamRequest},
     messages, msg,
     object::{BorrowedObject, Object, OwnedObject},
-    request_table::ResponseToken,
+    request_table::{RequestId, RequestTable, ResponseToken, Responses},
+    response_sink::{ResponseSink, StreamChunk},
     restarting::RestartPolicy,
     routers::Singleton,
     scope,
     source::{SourceHandle, Sources, UnattachedSource},
+    stream::Emitter,
+    typed_addr::Recipient,
     ActorStatusKind,
 };
 
@@ -173,6 +177,43 @@ impl<C, K> Context<C, K> {
         ward!(self.actor.as_ref().and_then(|o| o.as_actor())).set_restart_policy(policy.into());
     }
 
+    /// Stashes the state to recover it after a restart of the actor using
+    /// [`ActorStartInfo::recovered_state()`]. Each call replaces the state
+    /// stashed previously.
+    ///
+    /// The state is kept by the [`StashBackend`] of the group per actor's key
+    /// (in memory by default) and discarded once the actor isn't going to be
+    /// restarted anymore. The backend is called in the background, so if it's
+    /// slower than the actor, only the last stashed state is saved.
+    ///
+    /// # Example
+    /// ```
+    /// # use elfo_core as elfo;
+    /// # use elfo::message;
+    /// #[message]
+    /// #[derive(Default)]
+    /// struct Totals {
+    ///     count: u64,
+    /// }
+    ///
+    /// # async fn exec(mut ctx: elfo::Context) {
+    /// let mut totals = ctx
+    ///     .start_info()
+    ///     .recovered_state::<Totals>()
+    ///     .unwrap_or_default();
+    ///
+    /// while let Some(_envelope) = ctx.recv().await {
+    ///     totals.count += 1;
+    ///     ctx.stash_state(totals.clone());
+    /// }
+    /// # }
+    /// ```
+    ///
+    /// [`StashBackend`]: crate::stash::StashBackend
+    pub fn stash_state<S: Message>(&self, state: S) {
+        ward!(self.actor.as_ref().and_then(|o| o.as_actor())).stash_state(AnyMessage::new(state));
+    }
+
     /// Closes the mailbox, that leads to returning `None` from `recv()` and
     /// `try_recv()` after handling all available messages in the mailbox.
     ///
@@ -390,6 +431,9 @@ impl<C, K> Context<C, K> {
     /// for result in ctx.request(SomeCommand).all().resolve().await {
     ///     // ...
     /// }
+    ///
+    /// // Request and wait for two successful responses.
+    /// let responses = ctx.request(SomeCommand).all().quorum(2).resolve().await?;
     /// ```
     ///
     /// [inter-group routing]: https://actoromicon.rs/ch04-01-routing.html
@@ -399,6 +443,8 @@ impl<C, K> Context<C, K> {
     }
 
     /// Returns a request builder to send a request to the specified recipient.
+    /// If the recipient is [`TypedAddr`], only requests of its protocol are
+    /// accepted.
     ///
     /// # Example
     /// ```ignore
@@ -410,13 +456,15 @@ impl<C, K> Context<C, K> {
     ///     // ...
     /// }
     /// ```
+    ///
+    /// [`TypedAddr`]: crate::TypedAddr
     #[inline]
     pub fn request_to<R: Request>(
         &self,
-        recipient: Addr,
+        recipient: impl Recipient<R>,
         request: R,
     ) -> RequestBuilder<'_, C, K, R, Any> {
-        RequestBuilder::new(self, request).to(recipient)
+        RequestBuilder::new(self, request).to(recipient.into_addr())
     }
 
     async fn do_send_async<M: Message>(
@@ -484,6 +532,9 @@ impl<C, K> Context<C, K> {
     /// Sends a message to the specified recipient.
     /// Waits if the recipient's mailbox is full.
     ///
+    /// The recipient is either [`Addr`] or [`TypedAddr`], which accepts only
+    /// messages of its protocol. The same is true for other `*_to` methods.
+    ///
     /// It's possible to send requests if the response is not needed.
     ///
     /// Returns `Err` if the message hasn't reached any mailboxes.
@@ -508,11 +559,14 @@ impl<C, K> Context<C, K> {
     /// }
     /// # }
     /// ```
+    ///
+    /// [`TypedAddr`]: crate::TypedAddr
     pub async fn send_to<M: Message>(
         &self,
-        recipient: Addr,
+        recipient: impl Recipient<M>,
         message: M,
     ) -> Result<(), SendError<M>> {
+        let recipient = recipient.into_addr();
         let kind = MessageKind::regular(self.actor_addr);
         self.do_send_to(recipient, message, kind, |object, envelope| {
             Object::send(object, recipient, envelope)
@@ -550,9 +604,10 @@ impl<C, K> Context<C, K> {
     /// ```
     pub fn try_send_to<M: Message>(
         &self,
-        recipient: Addr,
+        recipient: impl Recipient<M>,
         message: M,
     ) -> Result<(), TrySendError<M>> {
+        let recipient = recipient.into_addr();
         let kind = MessageKind::regular(self.actor_addr);
         self.do_send_to(recipient, message, kind, |object, envelope| {
             object
@@ -592,9 +647,10 @@ impl<C, K> Context<C, K> {
     /// ```
     pub fn unbounded_send_to<M: Message>(
         &self,
-        recipient: Addr,
+        recipient: impl Recipient<M>,
         message: M,
     ) -> Result<(), SendError<M>> {
+        let recipient = recipient.into_addr();
         let kind = MessageKind::regular(self.actor_addr);
         self.do_send_to(recipient, message, kind, |object, envelope| {
             object
@@ -663,6 +719,23 @@ impl<C, K> Context<C, K> {
         object.respond(token, Ok(envelope));
     }
 
+    /// Creates a sink to respond to the streaming request with many items.
+    ///
+    /// ```ignore
+    /// msg!(match envelope {
+    ///     (ListOrders, token) => {
+    ///         let mut sink = ctx.response_sink(token);
+    ///         for order in orders {
+    ///             sink.send(order).await?;
+    ///         }
+    ///         sink.close().await?;
+    ///     }
+    /// })
+    /// ```
+    pub fn response_sink<R: StreamRequest>(&self, token: ResponseToken<R>) -> ResponseSink<R> {
+        ResponseSink::new(self.pruned(), token)
+    }
+
     /// Receives the next envelope from the mailbox or sources.
     /// If the envelope isn't available, the method waits for the next one.
     /// If the mailbox is closed, `None` is returned.
@@ -834,6 +907,112 @@ impl<C, K> Context<C, K> {
         }
     }
 
+    /// Receives up to `limit` envelopes from the mailbox or sources and
+    /// appends them to `buffer`. If no envelopes are available, the method
+    /// waits for the next one, then takes all available ones from the mailbox
+    /// without waiting. Returns the number of received envelopes.
+    ///
+    /// If the mailbox is closed or `limit` is zero, `0` is returned.
+    ///
+    /// System messages (`UpdateConfig`, `Terminate`, `Ping`, etc.) are handled
+    /// in the same way as by [`Context::recv()`]. The current trace ID is
+    /// taken from the last received envelope.
+    ///
+    /// # Budget
+    ///
+    /// Unlike `recv()`, the actor's budget is consumed once per call instead
+    /// of once per envelope. See [`coop`] for details.
+    ///
+    /// # Cancel safety
+    ///
+    /// This method is cancel safe in the same way as `recv()`.
+    ///
+    /// # Panics
+    ///
+    /// If the method is called again after `0` is returned because the
+    /// mailbox is closed.
+    ///
+    /// # Example
+    /// ```
+    /// # use elfo_core as elfo;
+    /// # async fn exec(mut ctx: elfo::Context) {
+    /// # fn handle_batch(_batch: impl Iterator<Item = elfo::Envelope>) {}
+    /// let mut batch = Vec::with_capacity(64);
+    ///
+    /// while ctx.recv_many(&mut batch, 64).await > 0 {
+    ///     handle_batch(batch.drain(..));
+    /// }
+    /// # }
+    /// ```
+    pub async fn recv_many(&mut self, buffer: &mut Vec<Envelope>, limit: usize) -> usize
+    where
+        C: 'static,
+    {
+        if limit == 0 {
+            return 0;
+        }
+
+        let Some(envelope) = self.recv().await else {
+            return 0;
+        };
+
+        buffer.push(envelope);
+        1 + self.take_available(buffer, limit - 1)
+    }
+
+    /// Receives up to `limit` envelopes from the mailbox or sources without
+    /// waiting and appends them to `buffer`. Returns the number of received
+    /// envelopes. If no envelopes are available, `Err(TryRecvError::Empty)` is
+    /// returned. If the mailbox is closed, `Err(TryRecvError::Closed)` is
+    /// returned.
+    ///
+    /// If `limit` is zero, `Ok(0)` is returned.
+    ///
+    /// See [`Context::recv_many()`] for details.
+    ///
+    /// # Panics
+    ///
+    /// If the method is called again after `Err(TryRecvError::Closed)`.
+    pub async fn try_recv_many(
+        &mut self,
+        buffer: &mut Vec<Envelope>,
+        limit: usize,
+    ) -> Result<usize, TryRecvError>
+    where
+        C: 'static,
+    {
+        if limit == 0 {
+            return Ok(0);
+        }
+
+        buffer.push(self.try_recv().await?);
+        Ok(1 + self.take_available(buffer, limit - 1))
+    }
+
+    // Takes envelopes from the mailbox only, sources are polled by the caller.
+    fn take_available(&mut self, buffer: &mut Vec<Envelope>, limit: usize) -> usize
+    where
+        C: 'static,
+    {
+        let mut count = 0;
+
+        while count < limit {
+            let actor = ward!(self.actor.as_ref().and_then(|o| o.as_actor()), break);
+
+            // The closed mailbox is handled by the next call of `recv_many()`.
+            let Some(RecvResult::Data(envelope)) = actor.try_recv() else {
+                break;
+            };
+
+            if let Some(envelope) = self.post_recv(envelope) {
+                buffer.push(envelope);
+                count += 1;
+            }
+        }
+
+        count
+    }
+
     /// Retrieves information related to the start of the actor.
     ///
     /// # Panics
@@ -890,6 +1069,12 @@ impl<C, K> Context<C, K> {
     {
         scope::set_trace_id(envelope.trace_id());
 
+        // The requester doesn't wait for expired or cancelled requests anymore.
+        if unlikely(is_abandoned_request(&envelope)) {
+            on_abandoned_request(envelope);
+            return None;
+        }
+
         let envelope = msg!(match envelope {
             (messages::UpdateConfig { config }, token) => {
                 self.config = config.get_user::<C>().clone();
@@ -923,10 +1108,29 @@ impl<C, K> Context<C, K> {
                 self.respond(token, ());
                 None
             }
+            (StreamChunk { request_id, items }, token) => {
+                self.on_stream_chunk(RequestId::from_ffi(request_id), items, token);
+                None
+            }
             envelope => Some(envelope),
         })
     }
 
+    fn on_stream_chunk(
+        &self,
+        request_id: RequestId,
+        items: Vec<AnyMessage>,
+        token: ResponseToken<StreamChunk>,
+    ) {
+        let actor = ward!(self.actor.as_ref().and_then(|o| o.as_actor()));
+
+        // If the stream is already ended, the token is dropped here,
+        // so the responder is notified that items aren't needed anymore.
+        if let Some(sender) = actor.request_table().stream(request_id) {
+            let _ = sender.unbounded_send((items, token));
+        }
+    }
+
     /// This is a part of private API for now.
     /// We should provide a way to handle it asynchronous.
     #[doc(hidden)]
@@ -1031,6 +1235,53 @@ fn e2m<M: Message>(envelope: Envelope) -> M {
     envelope.unpack().expect("invalid message").0
 }
 
+fn is_abandoned_request(envelope: &Envelope) -> bool {
+    match envelope.message_kind() {
+        MessageKind::RequestAny(token) | MessageKind::RequestAll(token) => {
+            token.is_expired() || token.is_cancelled()
+        }
+        _ => false,
+    }
+}
+
+#[cold]
+fn on_abandoned_request(envelope: Envelope) {
+    let (message, kind) = envelope.unpack::<AnyMessage>().expect("impossible");
+    let token = match kind {
+        MessageKind::RequestAny(token) | MessageKind::RequestAll(token) => token,
+        _ => unreachable!(),
+    };
+
+    if token.is_cancelled() {
+        trace!("< {:?} (cancelled, skipped)", message);
+        // Nobody waits for the response, so just drop the token.
+        drop(token);
+    } else {
+        trace!("< {:?} (expired, skipped)", message);
+        token.expire();
+    }
+}
+
+/// Used by the `#[actor]` macro for messages without handlers.
+#[doc(hidden)]
+#[cold]
+pub fn on_unhandled_message(envelope: Envelope) {
+    let message = envelope.message();
+
+    // System messages are sent to actors regardless of their protocols.
+    if message.protocol() == env!("CARGO_PKG_NAME") {
+        // `Terminate` reaches actors only if they handle it manually,
+        // see `TerminationPolicy::manually()`, so it cannot be ignored silently.
+        if message.is::<messages::Terminate>() {
+            warn!("unhandled `Terminate`, the actor isn't going to stop");
+        }
+        return;
+    }
+
+    // If it's a request, the requester gets `RequestError::Failed`.
+    warn!(message = message.name(), "unhandled message, discarded");
+}
+
 #[cold]
 fn on_input_closed(stage: &mut Stage, actor: &Actor) {
     if !actor.status_kind().is_terminating() {
@@ -1102,11 +1353,15 @@ pub struct RequestBuilder<'c, C, K, R, M> {
     context: &'c Context<C, K>,
     request: R,
     to: Option<Addr>,
+    deadline: Option<Instant>,
+    quorum: Option<usize>,
     marker: PhantomData<M>,
 }
 
 pub struct Any;
 pub struct All;
+pub struct Quorum;
+pub struct FirstOk;
 
 impl<'c, C, K, R> RequestBuilder<'c, C, K, R, Any> {
     fn new(context: &'c Context<C, K>, request: R) -> Self {
@@ -1114,16 +1369,69 @@ impl<'c, C, K, R> RequestBuilder<'c, C, K, R, Any> {
             context,
             request,
             to: None,
+            deadline: None,
+            quorum: None,
             marker: PhantomData,
         }
     }
 
     #[inline]
     pub fn all(self) -> RequestBuilder<'c, C, K, R, All> {
+        self.into_mode(None)
+    }
+}
+
+impl<'c, C, K, R> RequestBuilder<'c, C, K, R, All> {
+    /// Waits only for `n` successful responses instead of all of them.
+    /// Once they are received, other recipients find their requests cancelled,
+    /// see [`ResponseToken::is_cancelled()`].
+    ///
+    /// Useful for replicated groups, including multicast routing and
+    /// remote groups.
+    ///
+    /// # Panics
+    /// If `n` is zero.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let responses = ctx
+    ///     .request(Write { key, value })
+    ///     .all()
+    ///     .quorum(2)
+    ///     .resolve()
+    ///     .await?;
+    /// ```
+    #[inline]
+    pub fn quorum(self, n: usize) -> RequestBuilder<'c, C, K, R, Quorum> {
+        assert!(n > 0, "the quorum must be positive");
+        self.into_mode(Some(n))
+    }
+
+    /// Waits only for the first successful response.
+    /// Once it's received, other recipients find their requests cancelled,
+    /// see [`ResponseToken::is_cancelled()`].
+    ///
+    /// Unlike `ctx.request(..).resolve()`, the request is sent as a request
+    /// for all responses, so it's delivered to all recipients.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let response = ctx.request(Read { key }).all().first_ok().resolve().await?;
+    /// ```
+    #[inline]
+    pub fn first_ok(self) -> RequestBuilder<'c, C, K, R, FirstOk> {
+        self.into_mode(Some(1))
+    }
+}
+
+impl<'c, C, K, R, M> RequestBuilder<'c, C, K, R, M> {
+    fn into_mode<M2>(self, quorum: Option<usize>) -> RequestBuilder<'c, C, K, R, M2> {
         RequestBuilder {
             context: self.context,
             request: self.request,
             to: self.to,
+            deadline: self.deadline,
+            quorum,
             marker: PhantomData,
         }
     }
@@ -1137,6 +1445,85 @@ impl<C, K, R: Request, M> RequestBuilder<'_, C, K, R, M> {
         self
     }
 
+    /// Limits the time of waiting for responses.
+    ///
+    /// If responses aren't received in time, `resolve()` returns
+    /// `RequestError::Timeout` for missing ones. The deadline is also sent
+    /// along with the request, so recipients (including remote ones) skip
+    /// the request if it's expired before being handled.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let response = ctx
+    ///     .request(SomeCommand)
+    ///     .timeout(Duration::from_secs(5))
+    ///     .resolve()
+    ///     .await?;
+    /// ```
+    #[inline]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        // Too distant deadlines are considered absent.
+        self.deadline = Instant::now().checked_add(timeout);
+        self
+    }
+
+    /// Limits the time of waiting for responses by the provided instant.
+    /// See [`RequestBuilder::timeout()`] for details.
+    ///
+    /// # Stability
+    ///
+    /// This method is unstable, because it accepts [`tokio::time::Instant`],
+    /// which will be replaced in the future to support other runtimes.
+    #[stability::unstable]
+    #[inline]
+    pub fn deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    async fn do_resolve(self, collect_all: bool) -> Result<Responses, RequestError>
+    where
+        C: 'static,
+    {
+        // TODO: use `context.actor` after removing pruned contexts.
+        let this = self.context.actor_addr;
+        let object = self.context.book.get_owned(this).expect("invalid addr");
+        let actor = object.as_actor().expect("can be called only on actors");
+        let request_table = actor.request_table();
+        let deadline = self.deadline;
+
+        let token = request_table.new_request(
+            self.context.book.clone(),
+            scope::trace_id(),
+            collect_all,
+            self.quorum,
+            deadline,
+        );
+        let request_id = token.request_id();
+        let kind = if collect_all {
+            MessageKind::RequestAll(token)
+        } else {
+            MessageKind::RequestAny(token)
+        };
+
+        // Cancels the request if the future is dropped or the request isn't sent.
+        let _guard = RequestGuard {
+            request_table,
+            request_id,
+        };
+
+        let sent = match deadline {
+            Some(deadline) => tokio::time::timeout_at(deadline, self.do_send(kind)).await,
+            None => Ok(self.do_send(kind).await),
+        };
+
+        match sent {
+            Ok(true) => Ok(request_table.wait(request_id, deadline).await),
+            Ok(false) => Err(RequestError::Failed),
+            Err(_) => Err(RequestError::Timeout),
+        }
+    }
+
     async fn do_send(self, kind: MessageKind) -> bool {
         if let Some(recipient) = self.to {
             let res = self
@@ -1159,23 +1546,7 @@ impl<C, K, R: Request, M> RequestBuilder<'_, C, K, R, M> {
 impl<C: 'static, K, R: Request> RequestBuilder<'_, C, K, R, Any> {
     /// Waits for the response.
     pub async fn resolve(self) -> Result<R::Response, RequestError> {
-        // TODO: use `context.actor` after removing pruned contexts.
-        let this = self.context.actor_addr;
-        let object = self.context.book.get_owned(this).expect("invalid addr");
-        let actor = object.as_actor().expect("can be called only on actors");
-        let token =
-            actor
-                .request_table()
-                .new_request(self.context.book.clone(), scope::trace_id(), false);
-        let request_id = token.request_id();
-        let kind = MessageKind::RequestAny(token);
-
-        if !self.do_send(kind).await {
-            actor.request_table().cancel_request(request_id);
-            return Err(RequestError::Failed);
-        }
-
-        let mut responses = actor.request_table().wait(request_id).await;
+        let mut responses = self.do_resolve(false).await?;
         debug_assert_eq!(responses.len(), 1);
         prepare_response::<R>(responses.pop().expect("missing response"))
     }
@@ -1184,29 +1555,192 @@ impl<C: 'static, K, R: Request> RequestBuilder<'_, C, K, R, Any> {
 impl<C: 'static, K, R: Request> RequestBuilder<'_, C, K, R, All> {
     /// Waits for the responses.
     pub async fn resolve(self) -> Vec<Result<R::Response, RequestError>> {
-        // TODO: use `context.actor` after removing pruned contexts.
-        let this = self.context.actor_addr;
-        let object = self.context.book.get_owned(this).expect("invalid addr");
+        match self.do_resolve(true).await {
+            Ok(responses) => responses.into_iter().map(prepare_response::<R>).collect(),
+            Err(err) => vec![Err(err)],
+        }
+    }
+}
+
+impl<C: 'static, K, R: Request> RequestBuilder<'_, C, K, R, Quorum> {
+    /// Waits for the quorum of successful responses.
+    ///
+    /// If the quorum isn't reached, returns [`RequestError::Timeout`] if the
+    /// deadline has been reached, and [`RequestError::Failed`] otherwise.
+    pub async fn resolve(self) -> Result<Vec<R::Response>, RequestError> {
+        let quorum = self.quorum.expect("quorum must be set");
+        let responses = self.do_resolve(true).await?;
+        resolve_quorum::<R>(responses, quorum)
+    }
+}
+
+impl<C: 'static, K, R: Request> RequestBuilder<'_, C, K, R, FirstOk> {
+    /// Waits for the first successful response.
+    ///
+    /// If there is no such response, returns [`RequestError::Timeout`] if the
+    /// deadline has been reached, and [`RequestError::Failed`] otherwise.
+    pub async fn resolve(self) -> Result<R::Response, RequestError> {
+        let responses = self.do_resolve(true).await?;
+        let mut responses = resolve_quorum::<R>(responses, 1)?;
+        debug_assert_eq!(responses.len(), 1);
+        Ok(responses.pop().expect("missing response"))
+    }
+}
+
+impl<C: 'static, K, R: StreamRequest> RequestBuilder<'_, C, K, R, Any> {
+    /// Sends the request and returns a source emitting items of the streaming
+    /// response, followed by [`StreamEnded`]. The source should be attached
+    /// to the context to receive items.
+    ///
+    /// The next chunk of items is sent by the responder only after the
+    /// previous one is emitted by the source, so the mailbox isn't flooded.
+    ///
+    /// # Example
+    /// ```ignore
+    /// ctx.attach(ctx.request(ListOrders).stream());
+    ///
+    /// while let Some(envelope) = ctx.recv().await {
+    ///     msg!(match envelope {
+    ///         Order => { /* ... */ },
+    ///         StreamEnded => { /* ... */ },
+    ///     });
+    /// }
+    /// ```
+    ///
+    /// [`StreamEnded`]: messages::StreamEnded
+    pub fn stream(self) -> UnattachedSource<crate::stream::Stream<AnyMessage>> {
+        let context = self.context.pruned();
+        let request = self.request;
+        let to = self.to;
+        let deadline = self.deadline;
+
+        crate::stream::Stream::generate(move |mut emitter| async move {
+            let name = request.name().to_string();
+            let builder = RequestBuilder {
+                context: &context,
+                request,
+                to,
+                deadline,
+                quorum: None,
+                marker: PhantomData::<Any>,
+            };
+
+            let is_complete = builder.do_stream(&mut emitter).await;
+            let message = messages::StreamEnded {
+                request: name,
+                is_complete,
+            };
+            emitter.emit(message).await;
+        })
+    }
+
+    /// Returns `true` if the stream is completed successfully.
+    async fn do_stream(self, emitter: &mut Emitter) -> bool {
+        let context = self.context;
+        let object = context
+            .book
+            .get_owned(context.actor_addr)
+            .expect("invalid addr");
         let actor = object.as_actor().expect("can be called only on actors");
-        let token =
-            actor
-                .request_table()
-                .new_request(self.context.book.clone(), scope::trace_id(), true);
+        let request_table = actor.request_table();
+        let deadline = self.deadline;
+
+        let token = request_table.new_request(
+            context.book.clone(),
+            scope::trace_id(),
+            false,
+            None,
+            deadline,
+        );
         let request_id = token.request_id();
-        let kind = MessageKind::RequestAll(token);
 
-        if !self.do_send(kind).await {
-            actor.request_table().cancel_request(request_id);
-            return vec![Err(RequestError::Failed)];
+        let (tx, mut rx) = mpsc::unbounded();
+        request_table.register_stream(request_id, tx);
+        let _guard = StreamGuard {
+            request_table,
+            request_id,
+        };
+
+        let kind = MessageKind::RequestAny(token);
+        let sent = match deadline {
+            Some(deadline) => tokio::time::timeout_at(deadline, self.do_send(kind)).await,
+            None => Ok(self.do_send(kind).await),
+        };
+
+        if !matches!(sent, Ok(true)) {
+            return false;
         }
 
-        actor
-            .request_table()
-            .wait(request_id)
-            .await
-            .into_iter()
-            .map(prepare_response::<R>)
-            .collect()
+        let end = request_table.wait(request_id, deadline);
+        pin_mut!(end);
+
+        loop {
+            tokio::select! {
+                biased;
+                Some((items, token)) = rx.next() => {
+                    for item in items {
+                        emitter.emit(item).await;
+                    }
+
+                    // Allow the responder to send the next chunk.
+                    context.respond(token, ());
+                }
+                responses = &mut end => {
+                    return responses.into_iter().all(|response| response.is_ok());
+                }
+            }
+        }
+    }
+}
+
+/// Cancels the request if it's still in progress, so responders can find out
+/// that nobody waits for responses anymore.
+struct RequestGuard<'a> {
+    request_table: &'a RequestTable,
+    request_id: RequestId,
+}
+
+impl Drop for RequestGuard<'_> {
+    fn drop(&mut self) {
+        self.request_table.cancel_request(self.request_id);
+    }
+}
+
+/// Unregisters the stream and cancels the request if the source is dropped.
+struct StreamGuard<'a> {
+    request_table: &'a RequestTable,
+    request_id: RequestId,
+}
+
+impl Drop for StreamGuard<'_> {
+    fn drop(&mut self) {
+        self.request_table.unregister_stream(self.request_id);
+        self.request_table.cancel_request(self.request_id);
+    }
+}
+
+/// Returns successful responses if there are enough of them.
+fn resolve_quorum<R: Request>(
+    responses: Responses,
+    quorum: usize,
+) -> Result<Vec<R::Response>, RequestError> {
+    let mut is_timeout = false;
+    let mut succeeded = Vec::with_capacity(quorum);
+
+    for response in responses {
+        match response {
+            Ok(envelope) => succeeded.push(prepare_response::<R>(Ok(envelope))?),
+            Err(RequestError::Timeout) => is_timeout = true,
+            Err(_) => {}
+        }
+    }
+
+    if succeeded.len() >= quorum {
+        Ok(succeeded)
+    } else if is_timeout {
+        Err(RequestError::Timeout)
+    } else {
+        Err(RequestError::Failed)
     }
 }
 
@@ -17,6 +17,9 @@ struct InHandling {
 
 static STARTUP_LABELS: &[Label] = &[Label::from_static_parts("message", "<Startup>")];
 static EMPTY_MAILBOX_LABELS: &[Label] = &[Label::from_static_parts("message", "<EmptyMailbox>")];
+static BATCH_LABELS: &[Label] = &[Label::from_static_parts("message", "<Batch>")];
+static HIGH_LANE_LABELS: &[Label] = &[Label::from_static_parts("lane", "high")];
+static NORMAL_LANE_LABELS: &[Label] = &[Label::from_static_parts("lane", "normal")];
 
 impl Stats {
     pub(super) fn empty() -> Self {
@@ -34,9 +37,9 @@ impl Stats {
         self.emit_handling_time();
     }
 
+    /// Called for every received envelope, including ones received in a batch
+    /// by `recv_many()` without calling `on_recv()` in between.
     pub(super) fn on_received_envelope(&mut self, envelope: &Envelope) {
-        debug_assert!(self.in_handling.is_none());
-
         let recorder = ward!(metrics::try_recorder());
         let key = Key::from_static_name("elfo_message_waiting_time_seconds");
         let now = Instant::now();
@@ -45,7 +48,21 @@ impl Stats {
         let value = now.secs_f64_since(envelope.created_time());
         recorder.record_histogram(&key, value);
 
-        self.in_handling = Some(InHandling::new(envelope.message().labels(), now));
+        let message = envelope.message();
+        let lane_labels = if message.is_high_priority() {
+            HIGH_LANE_LABELS
+        } else {
+            NORMAL_LANE_LABELS
+        };
+        let key = Key::from_static_parts("elfo_received_messages_total", lane_labels);
+        recorder.increment_counter(&key, 1);
+
+        // Envelopes of a batch are handled together, so the handling time is
+        // recorded once for the whole batch, starting from its first envelope.
+        self.in_handling = Some(match self.in_handling.take() {
+            Some(batch) => InHandling::new(BATCH_LABELS, batch.start_time),
+            None => InHandling::new(message.labels(), now),
+        });
     }
 
     pub(super) fn on_empty_mailbox(&mut self) {
@@ -13,6 +13,7 @@ use serde::Deserialize;
 /// system.dumping.max_rate = 1_000
 /// ```
 #[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(default)]
 pub struct DumpingConfig {
     /// Whether dumping is disabled.
@@ -7,7 +7,10 @@ use smallbox::{smallbox, SmallBox};
 use elfo_utils::time::SystemTime;
 
 use super::{extract_name::extract_name, sequence_no::SequenceNo};
-use crate::{actor::ActorMeta, envelope, scope, thread::ThreadId, tracing::TraceId, Message};
+use crate::{
+    actor::ActorMeta, envelope, message::AnyMessage, scope, thread::ThreadId, tracing::TraceId,
+    Message,
+};
 
 // === Dump ===
 
@@ -114,6 +117,21 @@ impl DumpBuilder {
         self.do_finish(smallbox!(message))
     }
 
+    /// Like [`DumpBuilder::finish()`], but for a type-erased message.
+    /// The name and the protocol are taken from the message if not provided.
+    #[stability::unstable]
+    pub fn finish_any(&mut self, message: &AnyMessage) -> Dump {
+        if self.message_name.is_none() {
+            self.message_name = Some(message.name().into());
+        }
+
+        if self.message_protocol.is_empty() {
+            self.message_protocol = message.protocol();
+        }
+
+        self.do_finish(message._erase())
+    }
+
     fn do_finish(&mut self, message: ErasedMessage) -> Dump {
         let (meta, trace_id, sequence_no) = scope::with(|scope| {
             (
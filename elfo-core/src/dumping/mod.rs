@@ -28,6 +28,12 @@ pub(crate) use self::{
 #[stability::unstable]
 pub const INTERNAL_CLASS: &str = "internal";
 
+/// The class of messages dropped by the mailbox of the receiver according to
+/// `system.mailbox.on_overflow`.
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable")))]
+#[stability::unstable]
+pub const DROPPED_CLASS: &str = "dropped";
+
 pub mod config;
 
 mod control;
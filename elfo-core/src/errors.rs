@@ -161,6 +161,9 @@ pub enum RequestError {
     /// Receiver has got the request, but ignored it.
     #[display("request ignored")]
     Ignored,
+    /// The deadline of the request has passed before the response.
+    #[display("request timed out")]
+    Timeout,
 }
 
 // === TryRecvError ===
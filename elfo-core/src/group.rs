@@ -3,8 +3,9 @@ use std::{fmt::Debug, future::Future, marker::PhantomData, sync::Arc};
 use futures::future::BoxFuture;
 
 use crate::{
-    addr::{NodeLaunchId, NodeNo},
-    config::Config,
+    actor::ActorStartCause,
+    addr::{Addr, NodeLaunchId, NodeNo},
+    config::{Config, ConfigSpec},
     context::Context,
     envelope::Envelope,
     exec::{Exec, ExecResult},
@@ -12,6 +13,8 @@ use crate::{
     restarting::RestartPolicy,
     routers::Router,
     runtime::RuntimeManager,
+    shutdown::{config::ShutdownConfig, ShutdownReport},
+    stash::{MemoryStash, StashBackend},
     supervisor::Supervisor,
 };
 
@@ -19,8 +22,11 @@ use crate::{
 pub struct ActorGroup<R, C> {
     restart_policy: RestartPolicy,
     termination_policy: TerminationPolicy,
+    supervision_strategy: SupervisionStrategy,
+    stash: Arc<dyn StashBackend>,
     stop_order: i8,
     router: R,
+    config: ConfigSpec,
     _config: PhantomData<C>,
 }
 
@@ -30,8 +36,11 @@ impl ActorGroup<(), ()> {
         Self {
             restart_policy: RestartPolicy::default(),
             termination_policy: TerminationPolicy::default(),
+            supervision_strategy: SupervisionStrategy::default(),
+            stash: Arc::new(MemoryStash::default()),
             router: (),
             stop_order: 0,
+            config: ConfigSpec::default(),
             _config: PhantomData,
         }
     }
@@ -42,8 +51,11 @@ impl<R, C> ActorGroup<R, C> {
         ActorGroup {
             restart_policy: self.restart_policy,
             termination_policy: self.termination_policy,
+            supervision_strategy: self.supervision_strategy,
+            stash: self.stash,
             router: self.router,
             stop_order: self.stop_order,
+            config: ConfigSpec::of::<C1>(),
             _config: PhantomData,
         }
     }
@@ -64,18 +76,38 @@ impl<R, C> ActorGroup<R, C> {
         self
     }
 
+    /// The behaviour on failures of actors in the group.
+    ///
+    /// `SupervisionStrategy::OneForOne` is used by default.
+    pub fn supervision_strategy(mut self, strategy: SupervisionStrategy) -> Self {
+        self.supervision_strategy = strategy;
+        self
+    }
+
+    /// Sets the backend keeping states stashed by [`Context::stash_state()`].
+    ///
+    /// `MemoryStash` is used by default.
+    pub fn stash_backend(mut self, backend: impl StashBackend) -> Self {
+        self.stash = Arc::new(backend);
+        self
+    }
+
     /// Installs a router.
     pub fn router<R1: Router<C>>(self, router: R1) -> ActorGroup<R1, C> {
         ActorGroup {
             restart_policy: self.restart_policy,
             termination_policy: self.termination_policy,
+            supervision_strategy: self.supervision_strategy,
+            stash: self.stash,
             router,
             stop_order: self.stop_order,
+            config: self.config,
             _config: self._config,
         }
     }
 
     /// Specifies the order of stopping among other groups.
+    /// Can be overridden by `system.shutdown.phase` in the config.
     ///
     /// Actors in groups with lower values are stopped first.
     /// Actors in groups with higher values start stopping when all actors in
@@ -110,7 +142,8 @@ impl<R, C> ActorGroup<R, C> {
                           node_no: NodeNo,
                           node_launch_id: NodeLaunchId,
                           name: String,
-                          rt_manager: RuntimeManager| {
+                          rt_manager: RuntimeManager,
+                          escalations: Vec<Addr>| {
             let addr = ctx.group();
             let sv = Arc::new(Supervisor::new(
                 ctx,
@@ -121,6 +154,9 @@ impl<R, C> ActorGroup<R, C> {
                 self.router,
                 self.restart_policy,
                 self.termination_policy,
+                self.supervision_strategy,
+                self.stash,
+                escalations,
                 rt_manager,
             ));
 
@@ -130,10 +166,24 @@ impl<R, C> ActorGroup<R, C> {
         Blueprint {
             mount: Box::new(mount),
             stop_order: self.stop_order,
+            config: self.config,
         }
     }
 }
 
+#[cfg(feature = "schema")]
+impl<R, C: Config + schemars::JsonSchema> ActorGroup<R, C> {
+    /// Includes the schema of the group's config into
+    /// [`Topology::config_schema()`]. Otherwise, any value is allowed,
+    /// except the `system` section.
+    ///
+    /// [`Topology::config_schema()`]: crate::Topology::config_schema
+    pub fn config_schema(mut self) -> Self {
+        self.config = self.config.with_schema::<C>();
+        self
+    }
+}
+
 struct Handle<R: Router<C>, C, X>(Arc<Supervisor<R, C, X>>);
 
 impl<R, C, X> GroupHandle for Handle<R, C, X>
@@ -150,12 +200,26 @@ where
     fn finished(&self) -> BoxFuture<'static, ()> {
         self.0.finished()
     }
+
+    fn restart_all(&self, cause: ActorStartCause) {
+        self.0.restart_all(cause)
+    }
+
+    fn shutdown_config(&self) -> ShutdownConfig {
+        self.0.shutdown_config()
+    }
+
+    fn on_drain_timeout(&self, report: &mut ShutdownReport) {
+        self.0.on_drain_timeout(report)
+    }
 }
 
+type Mount = dyn FnOnce(Context, NodeNo, NodeLaunchId, String, RuntimeManager, Vec<Addr>) -> Object;
+
 pub struct Blueprint {
-    pub(crate) mount:
-        Box<dyn FnOnce(Context, NodeNo, NodeLaunchId, String, RuntimeManager) -> Object>,
+    pub(crate) mount: Box<Mount>,
     pub(crate) stop_order: i8,
+    pub(crate) config: ConfigSpec,
 }
 
 /// The behaviour on the `Terminate` message.
@@ -198,3 +262,27 @@ impl TerminationPolicy {
 
     // TODO: add `stop_spawning`?
 }
+
+/// The behaviour on failures of actors in a group.
+///
+/// Only failures leading to restarts of the failed actor are taken into
+/// account. Other actors are stopped by closing their mailboxes and restarted
+/// immediately regardless of the restart policy with
+/// [`ActorStartCause::SiblingFailed`].
+///
+/// If a failed actor isn't going to be restarted anymore, the failure is
+/// escalated to dependent groups instead, see [`Local::escalate_to()`].
+///
+/// [`Local::escalate_to()`]: crate::topology::Local::escalate_to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SupervisionStrategy {
+    /// Only the failed actor is restarted.
+    #[default]
+    OneForOne,
+    /// All actors in the group are restarted.
+    OneForAll,
+    /// The failed actor and all actors started after it are restarted.
+    /// The order is defined by the first start of actors with the same key.
+    RestForOne,
+}
@@ -1,10 +1,12 @@
 use std::{
     future::Future,
+    mem,
     sync::Arc,
     time::{Duration, SystemTime},
 };
 
 use futures::future::join_all;
+use parking_lot::Mutex;
 use tokio::{
     pin, select,
     time::{sleep, timeout},
@@ -26,12 +28,14 @@ use crate::{
     config::SystemConfig,
     context::Context,
     demux::Demux,
-    errors::{RequestError, StartError, StartGroupError},
+    errors::{StartError, StartGroupError},
     message,
     messages::{StartEntrypoint, Terminate, UpdateConfig},
     object::Object,
     scope::{Scope, ScopeGroupShared},
+    shutdown::ShutdownReport,
     signal::{Signal, SignalKind},
+    stash::{MemoryStash, Stashers},
     subscription::SubscriptionManager,
     topology::{Topology, SYSTEM_INIT_GROUP_NO},
     tracing::TraceId,
@@ -58,7 +62,7 @@ async fn start_entrypoints(ctx: &Context, topology: &Topology, is_check_only: bo
             match response {
                 Ok(Ok(())) => Ok(()),
                 Ok(Err(e)) => Err(StartError::single(group.name.clone(), e.reason)),
-                Err(RequestError::Ignored) | Err(RequestError::Failed) => Err(StartError::single(
+                Err(_) => Err(StartError::single(
                     group.name.clone(),
                     "config cannot be delivered to the entrypoint".into(),
                 )),
@@ -81,7 +85,7 @@ async fn start_entrypoints(ctx: &Context, topology: &Topology, is_check_only: bo
                         .collect();
                     Err(StartError::multiple(group_errors))
                 }
-                Err(RequestError::Ignored) | Err(RequestError::Failed) => Err(StartError::single(
+                Err(_) => Err(StartError::single(
                     group.name,
                     "starting message cannot be delivered to the entrypoint".into(),
                 )),
@@ -114,6 +118,16 @@ pub async fn start(topology: Topology) {
 
 /// The same as `start()`, but returns an error rather than panics.
 pub async fn try_start(topology: Topology) -> Result<()> {
+    try_start_with_report(topology).await.map(drop)
+}
+
+/// The same as `try_start()`, but also returns a report about actors
+/// that haven't finished in time during the shutdown.
+///
+/// See [`ShutdownConfig`] for details.
+///
+/// [`ShutdownConfig`]: crate::config::system::shutdown::ShutdownConfig
+pub async fn try_start_with_report(topology: Topology) -> Result<ShutdownReport> {
     check_messages_uniqueness()?;
 
     #[cfg(feature = "test-util")]
@@ -136,7 +150,7 @@ pub async fn check_only(topology: Topology) -> Result<()> {
 
     // The logger is not supposed to be initialized in this mode, so we do not wait
     // for it before exiting.
-    do_start(topology, true, terminate).await
+    do_start(topology, true, do_terminate).await.map(drop)
 }
 
 /// Checks that all messages are unique by `(protocol, name)` pair.
@@ -184,6 +198,7 @@ pub async fn do_start<F: Future>(
         &<_>::default(),
         <_>::default(),
         Arc::new(SubscriptionManager::new(ctx.clone())),
+        Stashers::new(Arc::new(MemoryStash::default())).get(meta.clone()),
     );
 
     let scope_shared = ScopeGroupShared::new(topology.node_no(), topology.launch_id(), addr);
@@ -213,11 +228,11 @@ struct TerminateSystem;
 #[message]
 struct CheckMemoryUsageTick;
 
-// TODO: make these values configurable.
-const SEND_CLOSING_TERMINATE_AFTER: Duration = Duration::from_secs(25);
-const STOP_GROUP_TERMINATION_AFTER: Duration = Duration::from_secs(35);
+/// How long to wait for groups after the drain deadline.
+// TODO: make it configurable.
+const STOP_GROUP_TERMINATION_AFTER: Duration = Duration::from_secs(10);
 
-async fn exec(mut ctx: Context, topology: Topology) {
+async fn exec(mut ctx: Context, topology: Topology) -> ShutdownReport {
     emit_start_time();
 
     ctx.attach(Signal::new(SignalKind::UnixTerminate, TerminateSystem));
@@ -277,12 +292,13 @@ async fn exec(mut ctx: Context, topology: Topology) {
 
     ctx.set_status(ActorStatus::TERMINATING);
 
-    let termination = terminate(ctx.pruned(), topology);
+    let report = Mutex::new(ShutdownReport::default());
+    let termination = terminate(ctx.pruned(), topology, &report);
     pin!(termination);
 
     loop {
         select! {
-            _ = &mut termination => return,
+            _ = &mut termination => return mem::take(&mut report.lock()),
             Some(envelope) = ctx.recv() => {
                 if !envelope.is::<TerminateSystem>() {
                     continue;
@@ -294,53 +310,92 @@ async fn exec(mut ctx: Context, topology: Topology) {
                 } else {
                     // `Ctrl-C` has been pressed again. Terminate immediately.
                     // TODO: `Terminate::closing` on second `Ctrl-C`
-                    return;
+                    return mem::take(&mut report.lock());
                 }
             }
         }
     }
 }
 
-async fn terminate(ctx: Context, topology: Topology) {
-    let mut stop_order_list = topology
+#[doc(hidden)]
+pub async fn do_terminate(ctx: Context, topology: Topology) -> ShutdownReport {
+    let report = Mutex::new(ShutdownReport::default());
+    terminate(ctx, topology, &report).await;
+    report.into_inner()
+}
+
+async fn terminate(ctx: Context, topology: Topology, report: &Mutex<ShutdownReport>) {
+    let groups = topology
         .locals()
-        .map(|group| group.stop_order)
+        .map(|group| {
+            let config = ctx
+                .book()
+                .get_owned(group.addr)
+                .and_then(|object| object.as_group().map(|group| group.shutdown_config()))
+                .unwrap_or_default();
+
+            let phase = config.phase.unwrap_or(group.stop_order);
+            (phase, config.drain_timeout, group)
+        })
         .collect::<Vec<_>>();
 
-    stop_order_list.sort_unstable();
-    stop_order_list.dedup();
+    let mut phases = groups.iter().map(|(phase, ..)| *phase).collect::<Vec<_>>();
+    phases.sort_unstable();
+    phases.dedup();
+
+    for phase in phases {
+        info!(%phase, "terminating groups");
 
-    for stop_order in stop_order_list {
-        info!(%stop_order, "terminating groups");
-        terminate_groups(&ctx, &topology, stop_order).await;
+        let futures = groups
+            .iter()
+            .filter(|(group_phase, _, _)| *group_phase == phase)
+            .map(|(_, drain_timeout, group)| {
+                terminate_group(&ctx, group.addr, &group.name, *drain_timeout, report)
+            });
+
+        join_all(futures).await;
     }
-}
 
-async fn terminate_groups(ctx: &Context, topology: &Topology, stop_order: i8) {
-    let futures = topology
-        .locals()
-        .filter(|group| group.stop_order == stop_order)
-        .map(|group| async move {
-            let started_at = Instant::now();
-            select! {
-                _ = terminate_group(ctx, group.addr, group.name.clone(), started_at) => {},
-                _ = watch_group(ctx, group.addr, group.name, started_at) => {},
-            }
-        })
-        .collect::<Vec<_>>();
+    let report = report.lock();
+    if !report.is_clean() {
+        warn!(
+            message = "some actors haven't finished in time",
+            actors = ?report.missed_deadline,
+            undelivered = report.undelivered_count(),
+        );
+    }
+}
 
-    join_all(futures).await;
+pub(crate) async fn terminate_group(
+    ctx: &Context,
+    addr: Addr,
+    name: &str,
+    drain_timeout: Duration,
+    report: &Mutex<ShutdownReport>,
+) {
+    let started_at = Instant::now();
+    select! {
+        _ = do_terminate_group(ctx, addr, name, drain_timeout, started_at, report) => {},
+        _ = watch_group(ctx, addr, name, started_at) => {},
+    }
 }
 
-async fn terminate_group(ctx: &Context, addr: Addr, name: String, started_at: Instant) {
+async fn do_terminate_group(
+    ctx: &Context,
+    addr: Addr,
+    name: &str,
+    drain_timeout: Duration,
+    started_at: Instant,
+    report: &Mutex<ShutdownReport>,
+) {
     // Terminate::default
 
     info!(group = %name, "sending polite Terminate");
     let fut = ctx.send_to(addr, Terminate::default());
 
-    if timeout(SEND_CLOSING_TERMINATE_AFTER, fut).await.is_ok() {
+    if timeout(drain_timeout, fut).await.is_ok() {
         let elapsed = started_at.elapsed();
-        if let Some(delta) = SEND_CLOSING_TERMINATE_AFTER.checked_sub(elapsed) {
+        if let Some(delta) = drain_timeout.checked_sub(elapsed) {
             sleep(delta).await;
         }
     } else {
@@ -350,15 +405,23 @@ async fn terminate_group(ctx: &Context, addr: Addr, name: String, started_at: In
     // Terminate::closing
 
     warn!(
-        message = "actor group hasn't finished yet, sending closing Terminate",
+        message = "actor group hasn't finished yet, discarding queued messages",
         group = %name,
         elapsed = ?started_at.elapsed(),
     );
+
+    if let Some(object) = ctx.book().get_owned(addr) {
+        if let Some(group) = object.as_group() {
+            group.on_drain_timeout(&mut report.lock());
+        }
+    }
+
+    let stop_after = drain_timeout + STOP_GROUP_TERMINATION_AFTER;
     let fut = ctx.send_to(addr, Terminate::closing());
 
     if timeout(STOP_GROUP_TERMINATION_AFTER, fut).await.is_ok() {
         let elapsed = started_at.elapsed();
-        if let Some(delta) = STOP_GROUP_TERMINATION_AFTER.checked_sub(elapsed) {
+        if let Some(delta) = stop_after.checked_sub(elapsed) {
             sleep(delta).await;
         }
     } else {
@@ -372,7 +435,7 @@ async fn terminate_group(ctx: &Context, addr: Addr, name: String, started_at: In
     );
 }
 
-async fn watch_group(ctx: &Context, addr: Addr, name: String, started_at: Instant) {
+async fn watch_group(ctx: &Context, addr: Addr, name: &str, started_at: Instant) {
     ctx.finished(addr).await;
 
     info!(
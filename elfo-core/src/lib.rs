@@ -17,15 +17,20 @@ pub use crate::{
     config::Config,
     context::{Context, RequestBuilder},
     envelope::Envelope,
-    group::{ActorGroup, Blueprint, TerminationPolicy},
+    group::{ActorGroup, Blueprint, SupervisionStrategy, TerminationPolicy},
     local::{Local, MoveOwnership},
-    message::{AnyMessage, AnyMessageRef, Message, Request},
+    message::{AnyMessage, AnyMessageRef, Message, Request, StreamRequest},
     request_table::{RequestId, ResponseToken},
-    restarting::{RestartParams, RestartPolicy},
+    response_sink::ResponseSink,
+    restarting::{CircuitState, RestartParams, RestartPolicy},
+    shutdown::{ShutdownReport, UndeliveredMessages},
     source::{SourceHandle, UnattachedSource},
     topology::Topology,
+    typed_addr::{Handles, Protocol, Recipient, TypedAddr},
+};
+pub use elfo_macros::{
+    actor_core as actor, message_core as message, msg_core as msg, protocol_core as protocol,
 };
-pub use elfo_macros::{message_core as message, msg_core as msg};
 
 #[macro_use]
 mod macros;
@@ -41,6 +46,7 @@ pub mod messages;
 pub mod routers;
 pub mod scope;
 pub mod signal;
+pub mod stash;
 pub mod stream;
 #[cfg(feature = "unstable-stuck-detection")]
 pub mod stuck_detection;
@@ -69,23 +75,28 @@ pub mod remote;
 #[cfg(all(feature = "network", not(feature = "unstable")))]
 mod remote;
 mod request_table;
+mod response_sink;
 mod restarting;
 mod runtime;
+mod shutdown;
 mod source;
 mod subscription;
 mod supervisor;
 mod telemetry;
 mod thread;
+mod typed_addr;
 
 #[doc(hidden)]
 pub mod _priv {
     pub use crate::{
         address_book::AddressBook,
+        context::on_unhandled_message,
         envelope::{EnvelopeBorrowed, EnvelopeOwned, MessageKind},
-        init::do_start,
+        init::{do_start, do_terminate},
         message::*,
         object::{GroupVisitor, Object, OwnedObject},
         permissions::{AtomicPermissions, Permissions},
+        request_table::Cancellation,
     };
     pub use erased_serde;
     pub use idr_ebr::EbrGuard;
@@ -14,12 +14,14 @@ use tracing::level_filters::LevelFilter;
 /// system.logging.max_rate_per_level = 1_000
 /// ```
 #[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(default)]
 pub struct LoggingConfig {
     /// Maximum level of logging.
     ///
     /// `Info` by default.
     #[serde(deserialize_with = "deserialize_level_filter")]
+    #[cfg_attr(feature = "schema", schemars(with = "PrettyLevelFilter"))]
     pub max_level: LevelFilter,
     /// Maximum rate of logging per level.
     ///
@@ -42,16 +44,6 @@ where
 {
     use PrettyLevelFilter::*;
 
-    #[derive(Deserialize)]
-    pub(crate) enum PrettyLevelFilter {
-        Trace,
-        Debug,
-        Info,
-        Warn,
-        Error,
-        Off,
-    }
-
     let pretty = PrettyLevelFilter::deserialize(deserializer)?;
 
     Ok(match pretty {
@@ -63,3 +55,14 @@ where
         Off => LevelFilter::OFF,
     })
 }
+
+#[derive(Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+enum PrettyLevelFilter {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+    Off,
+}
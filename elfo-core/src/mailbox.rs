@@ -6,10 +6,14 @@
 //! 2. Supports both bounded and unbounded usage.
 //! 3. The capacity is configurable on the fly.
 //! 4. Preallocates no additional memory.
+//! 5. Has two lanes: the high-priority one for messages marked by
+//!    `#[message(priority = "high")]` and the normal one for all others.
+//!    The high-priority lane is always drained first.
+//! 6. The rate of incoming messages in the normal lane can be limited.
 //!
-//! A simplified structure can be pictured in the following way:
+//! A simplified structure of each lane can be pictured in the following way:
 //! ```text
-//!   mailbox                       envelopes
+//!     lane                        envelopes
 //! ┌─────────┐    ┌►┌───────┐    ┌►┌───────┐    ┌►┌───────┐◄─┐
 //! │  head   ├────┘ │  lnk  ├────┘ │  lnk  ├────┘ │  lnk  │  │
 //! ├─────────┤      ├───────┤      ├───────┤      ├───────┤  │
@@ -25,23 +29,38 @@
 //!             └─────────────────────────────────────────────┘
 //! ```
 
-use std::ptr::{self, NonNull};
+use std::{
+    ptr::{self, NonNull},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 
 use cordyceps::{
     mpsc_queue::{Links, MpscQueue},
     Linked,
 };
-use parking_lot::Mutex;
+use fxhash::FxHashMap;
+use once_cell::sync::Lazy;
+use parking_lot::{Mutex, RwLock};
 use tokio::sync::{Notify, Semaphore, TryAcquireError};
 
-use elfo_utils::CachePadded;
+use elfo_utils::{CachePadded, RateLimit, RateLimiter};
 
+use self::config::{ExceedPolicy, OverflowPolicy, RateLimitConfig};
 use crate::{
+    actor::ActorMeta,
+    dumping::{Direction, Dump, Dumper, DROPPED_CLASS},
     envelope::{Envelope, EnvelopeHeader},
     errors::{SendError, TrySendError},
+    message::Message,
     tracing::TraceId,
 };
 
+static DUMPER: Lazy<Dumper> = Lazy::new(|| Dumper::new(DROPPED_CLASS));
+
 // === MailboxConfig ===
 
 pub mod config {
@@ -49,17 +68,23 @@ pub mod config {
     //!
     //! [Config]: MailboxConfig
 
+    use std::{collections::BTreeMap, num::NonZeroU64};
+
     /// Mailbox configuration.
     ///
     /// # Example
     /// ```toml
     /// [some_group]
     /// system.mailbox.capacity = 1000
+    /// system.mailbox.on_overflow = "DropOldest"
+    /// system.mailbox.rate_limit.max_rate = 10_000
     /// ```
     #[derive(Debug, PartialEq, serde::Deserialize)]
+    #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
     #[serde(default)]
     pub struct MailboxConfig {
         /// The maximum number of messages that can be stored in the mailbox.
+        /// Applied to each lane (high-priority and normal) separately.
         ///
         /// Can be overriden by actor using [`Context::set_mailbox_capacity()`].
         ///
@@ -67,13 +92,96 @@ pub mod config {
         ///
         /// [`Context::set_mailbox_capacity()`]: crate::Context::set_mailbox_capacity
         pub capacity: usize,
+        /// What to do if the mailbox is full.
+        ///
+        /// Applied only to the normal lane, the high-priority lane always
+        /// behaves like [`OverflowPolicy::Block`].
+        ///
+        /// `Block` by default.
+        pub on_overflow: OverflowPolicy,
+        /// Limits the rate of incoming messages.
+        ///
+        /// Unlimited by default.
+        pub rate_limit: RateLimitConfig,
     }
 
     impl Default for MailboxConfig {
         fn default() -> Self {
-            Self { capacity: 100 }
+            Self {
+                capacity: 100,
+                on_overflow: OverflowPolicy::default(),
+                rate_limit: RateLimitConfig::default(),
+            }
         }
     }
+
+    /// What to do with a message sent to the full mailbox.
+    ///
+    /// Dropped messages are counted by the `elfo_dropped_messages_total` metric
+    /// of the receiving actor and dumped with the `dropped` class.
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+    #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+    pub enum OverflowPolicy {
+        /// `send()` waits until there is free space, `try_send()` fails.
+        #[default]
+        Block,
+        /// The sent message is dropped, sending is considered successful.
+        DropNewest,
+        /// The oldest message in the mailbox is dropped to make room for
+        /// the sent one, sending is considered successful.
+        DropOldest,
+        /// Both `send()` and `try_send()` fail immediately.
+        Reject,
+    }
+
+    /// Limits the rate of messages sent to each actor of the group.
+    ///
+    /// Messages are checked by senders before they are enqueued. Applied only
+    /// to the normal lane, messages of the high-priority lane and ones sent by
+    /// `unbounded_send()` are never limited.
+    ///
+    /// Limited messages are counted by the `elfo_rate_limited_messages_total`
+    /// metric of the receiving actor.
+    ///
+    /// # Example
+    /// ```toml
+    /// [some_group]
+    /// system.mailbox.rate_limit.max_rate = 10_000
+    /// system.mailbox.rate_limit.per_message = { SomeHeavyRequest = 100 }
+    /// system.mailbox.rate_limit.on_exceed = "Drop"
+    /// ```
+    #[derive(Debug, Default, PartialEq, serde::Deserialize)]
+    #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+    #[serde(default)]
+    pub struct RateLimitConfig {
+        /// The maximum number of messages per second, must be positive.
+        ///
+        /// `None` (unlimited) by default.
+        pub max_rate: Option<NonZeroU64>,
+        /// The maximum number of messages per second by message names,
+        /// must be positive. Such messages are also limited by `max_rate`.
+        ///
+        /// Empty by default.
+        pub per_message: BTreeMap<String, NonZeroU64>,
+        /// What to do if the limit is exceeded.
+        ///
+        /// `Throttle` by default.
+        pub on_exceed: ExceedPolicy,
+    }
+
+    /// What to do with a message exceeding [`RateLimitConfig`].
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+    #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+    pub enum ExceedPolicy {
+        /// `send()` waits until the rate allows sending, `try_send()` fails.
+        #[default]
+        Throttle,
+        /// The sent message is dropped, sending is considered successful.
+        ///
+        /// Dropped messages are also counted by the `elfo_dropped_messages_total`
+        /// metric and dumped with the `dropped` class.
+        Drop,
+    }
 }
 
 // === Mailbox ===
@@ -114,95 +222,187 @@ unsafe impl Linked<Link> for EnvelopeHeader {
 }
 
 pub(crate) struct Mailbox {
-    /// A storage for envelopes based on an intrusive linked list.
-    /// Note: `cordyceps` uses terms "head" and "tail" in the opposite way.
-    queue: MpscQueue<EnvelopeHeader>,
+    /// A lane for messages marked by `#[message(priority = "high")]`,
+    /// e.g. `UpdateConfig`, `Terminate` and `Ping`. Drained first.
+    high: Lane,
 
-    /// A notifier of senders about the availability of new messages.
-    // TODO: replace with a custom semaphore based on `async-event` (10-15% faster).
-    tx_semaphore: Semaphore,
+    /// A lane for all other messages.
+    normal: Lane,
 
     /// A notifier of a receiver about the availability of new messages.
     // TODO: replace with `diatomic-waker` (3-5% faster).
     rx_notify: CachePadded<Notify>,
 
+    /// The number of messages dropped according to `OverflowPolicy`
+    /// or `ExceedPolicy` since the last call of `take_dropped()`.
+    dropped: AtomicU64,
+
+    /// Limits the rate of messages in the normal lane.
+    rate_limits: RateLimits,
+
+    /// The number of messages exceeding `RateLimitConfig`
+    /// since the last call of `take_rate_limited()`.
+    rate_limited: AtomicU64,
+
     /// Use `Mutex` here for synchronization on close/configure.
     control: Mutex<Control>,
+
+    /// The receiver, used to attribute dumps of dropped messages.
+    meta: Arc<ActorMeta>,
+}
+
+struct Lane {
+    /// A storage for envelopes based on an intrusive linked list.
+    /// Note: `cordyceps` uses terms "head" and "tail" in the opposite way.
+    queue: MpscQueue<EnvelopeHeader>,
+
+    /// A notifier of senders about the availability of new messages.
+    // TODO: replace with a custom semaphore based on `async-event` (10-15% faster).
+    tx_semaphore: Semaphore,
+
+    /// A real capacity of the lane, changed only under the `control` lock.
+    /// It's atomic to calculate the length without locking.
+    capacity: AtomicUsize,
 }
 
 struct Control {
     /// A trace ID that should be assigned once the mailbox is closed.
     closed_trace_id: Option<TraceId>,
-    /// A real capacity of the mailbox.
-    capacity: usize,
+    /// What to do if the normal lane is full.
+    on_overflow: OverflowPolicy,
+    /// What to do if the rate limit is exceeded.
+    on_exceed: ExceedPolicy,
+}
+
+struct RateLimits {
+    /// `false` if nothing is limited, allows to avoid locking in the common case.
+    is_enabled: AtomicBool,
+    /// Limits all messages.
+    total: RateLimiter,
+    /// Limits specific messages by their names.
+    per_message: RwLock<FxHashMap<String, RateLimiter>>,
 }
 
 impl Mailbox {
-    pub(crate) fn new(config: &config::MailboxConfig) -> Self {
+    pub(crate) fn new(config: &config::MailboxConfig, meta: Arc<ActorMeta>) -> Self {
         let capacity = clamp_capacity(config.capacity);
 
         Self {
-            queue: MpscQueue::new_with_stub(Envelope::stub()),
-            tx_semaphore: Semaphore::new(capacity),
+            high: Lane::new(capacity),
+            normal: Lane::new(capacity),
             rx_notify: CachePadded::new(Notify::new()),
+            dropped: AtomicU64::new(0),
+            rate_limits: RateLimits::new(&config.rate_limit),
+            rate_limited: AtomicU64::new(0),
             control: Mutex::new(Control {
                 closed_trace_id: None,
-                capacity,
+                on_overflow: config.on_overflow,
+                on_exceed: config.rate_limit.on_exceed,
             }),
+            meta,
         }
     }
 
+    /// Sets the capacity of each lane.
     pub(crate) fn set_capacity(&self, capacity: usize) {
-        let mut control = self.control.lock();
-
-        if capacity == control.capacity {
-            return;
-        }
+        let _control = self.control.lock();
+        self.high.set_capacity(capacity);
+        self.normal.set_capacity(capacity);
+    }
 
-        if capacity < control.capacity {
-            let delta = control.capacity - capacity;
-            let real_delta = self.tx_semaphore.forget_permits(delta);
+    pub(crate) fn set_overflow_policy(&self, on_overflow: OverflowPolicy) {
+        self.control.lock().on_overflow = on_overflow;
+    }
 
-            // Note that we cannot reduce the number of active permits
-            // (relates to messages that already stored in the queue) in tokio impl.
-            // Sadly, in such cases, we violate provided `capacity`.
-            debug_assert!(real_delta <= delta);
-            control.capacity -= real_delta;
-        } else {
-            let real_delta = clamp_capacity(capacity) - control.capacity;
-            self.tx_semaphore.add_permits(real_delta);
-            control.capacity += real_delta;
-        }
+    pub(crate) fn set_rate_limit(&self, config: &RateLimitConfig) {
+        let mut control = self.control.lock();
+        control.on_exceed = config.on_exceed;
+        self.rate_limits.configure(config);
     }
 
     pub(crate) async fn send(&self, envelope: Envelope) -> Result<(), SendError<Envelope>> {
-        let permit = match self.tx_semaphore.acquire().await {
+        let lane = self.lane(&envelope);
+
+        if let Err(mut delay) = self.check_rate_limit(lane, &envelope) {
+            match self.exceed_policy() {
+                ExceedPolicy::Throttle => loop {
+                    tokio::time::sleep(delay).await;
+
+                    match self.rate_limits.acquire(envelope.message().name()) {
+                        Ok(()) => break,
+                        Err(next_delay) => delay = next_delay,
+                    }
+                },
+                ExceedPolicy::Drop => {
+                    self.on_dropped(envelope);
+                    return Ok(());
+                }
+            }
+        }
+
+        let permit = match lane.tx_semaphore.try_acquire() {
             Ok(permit) => permit,
-            Err(_) => return Err(SendError(envelope)),
+            Err(TryAcquireError::Closed) => return Err(SendError(envelope)),
+            Err(TryAcquireError::NoPermits) => match self.overflow_policy(lane) {
+                OverflowPolicy::Block => match lane.tx_semaphore.acquire().await {
+                    Ok(permit) => permit,
+                    Err(_) => return Err(SendError(envelope)),
+                },
+                OverflowPolicy::DropNewest => {
+                    self.on_dropped(envelope);
+                    return Ok(());
+                }
+                OverflowPolicy::DropOldest => {
+                    return self.replace_oldest(lane, envelope).map_err(SendError)
+                }
+                OverflowPolicy::Reject => return Err(SendError(envelope)),
+            },
         };
 
         permit.forget();
-        self.queue.enqueue(envelope);
+        lane.queue.enqueue(envelope);
         self.rx_notify.notify_one();
         Ok(())
     }
 
     pub(crate) fn try_send(&self, envelope: Envelope) -> Result<(), TrySendError<Envelope>> {
-        match self.tx_semaphore.try_acquire() {
+        let lane = self.lane(&envelope);
+
+        if self.check_rate_limit(lane, &envelope).is_err() {
+            return match self.exceed_policy() {
+                ExceedPolicy::Throttle => Err(TrySendError::Full(envelope)),
+                ExceedPolicy::Drop => {
+                    self.on_dropped(envelope);
+                    Ok(())
+                }
+            };
+        }
+
+        match lane.tx_semaphore.try_acquire() {
             Ok(permit) => {
                 permit.forget();
-                self.queue.enqueue(envelope);
+                lane.queue.enqueue(envelope);
                 self.rx_notify.notify_one();
                 Ok(())
             }
-            Err(TryAcquireError::NoPermits) => Err(TrySendError::Full(envelope)),
+            Err(TryAcquireError::NoPermits) => match self.overflow_policy(lane) {
+                OverflowPolicy::Block | OverflowPolicy::Reject => Err(TrySendError::Full(envelope)),
+                OverflowPolicy::DropNewest => {
+                    self.on_dropped(envelope);
+                    Ok(())
+                }
+                OverflowPolicy::DropOldest => self
+                    .replace_oldest(lane, envelope)
+                    .map_err(TrySendError::Closed),
+            },
             Err(TryAcquireError::Closed) => Err(TrySendError::Closed(envelope)),
         }
     }
 
     pub(crate) fn unbounded_send(&self, envelope: Envelope) -> Result<(), SendError<Envelope>> {
-        if !self.tx_semaphore.is_closed() {
-            self.queue.enqueue(envelope);
+        let lane = self.lane(&envelope);
+        if !lane.tx_semaphore.is_closed() {
+            lane.queue.enqueue(envelope);
             self.rx_notify.notify_one();
             Ok(())
         } else {
@@ -212,17 +412,11 @@ impl Mailbox {
 
     pub(crate) async fn recv(&self) -> RecvResult {
         loop {
-            // TODO: it should be possible to use `dequeue_unchecked()` here.
-            // Preliminarily, we should guarantee that it can be called only
-            // by one consumer. However, it's not enough to create a dedicated
-            // `MailboxConsumer` because users can steal `Context` to another
-            // task/thread and create a race with the `drop_all()` method.
-            if let Some(envelope) = self.queue.dequeue() {
-                self.tx_semaphore.add_permits(1);
+            if let Some(envelope) = self.dequeue() {
                 return RecvResult::Data(envelope);
             }
 
-            if self.tx_semaphore.is_closed() {
+            if self.is_closed() {
                 return self.on_close();
             }
 
@@ -231,12 +425,9 @@ impl Mailbox {
     }
 
     pub(crate) fn try_recv(&self) -> Option<RecvResult> {
-        match self.queue.dequeue() {
-            Some(envelope) => {
-                self.tx_semaphore.add_permits(1);
-                Some(RecvResult::Data(envelope))
-            }
-            None if self.tx_semaphore.is_closed() => Some(self.on_close()),
+        match self.dequeue() {
+            Some(envelope) => Some(RecvResult::Data(envelope)),
+            None if self.is_closed() => Some(self.on_close()),
             None => None,
         }
     }
@@ -249,26 +440,163 @@ impl Mailbox {
         // before the `closed_trace_id` is assigned.
         let mut control = self.control.lock();
 
-        if self.tx_semaphore.is_closed() {
+        if self.is_closed() {
             return false;
         }
 
         control.closed_trace_id = Some(trace_id);
 
-        self.tx_semaphore.close();
+        self.high.tx_semaphore.close();
+        self.normal.tx_semaphore.close();
         self.rx_notify.notify_one();
         true
     }
 
     #[cold]
     pub(crate) fn drop_all(&self) {
-        while self.queue.dequeue().is_some() {}
+        while self.high.queue.dequeue().is_some() {}
+        while self.normal.queue.dequeue().is_some() {}
+    }
+
+    /// Takes all queued messages, usually called after `close()`.
+    #[cold]
+    pub(crate) fn take_all(&self) -> Vec<Envelope> {
+        let mut envelopes = Vec::new();
+        while let Some(envelope) = self.dequeue() {
+            envelopes.push(envelope);
+        }
+        envelopes
+    }
+
+    #[inline]
+    fn lane(&self, envelope: &Envelope) -> &Lane {
+        if envelope.message().is_high_priority() {
+            &self.high
+        } else {
+            &self.normal
+        }
+    }
+
+    /// Returns the approximate number of messages in the mailbox.
+    /// Messages sent by `unbounded_send()` aren't taken into account.
+    pub(crate) fn len(&self) -> usize {
+        self.high.len() + self.normal.len()
+    }
+
+    /// Returns the number of dropped messages since the last call.
+    pub(crate) fn take_dropped(&self) -> u64 {
+        // Avoid contended writes in the common case.
+        if self.dropped.load(Ordering::Relaxed) == 0 {
+            return 0;
+        }
+
+        self.dropped.swap(0, Ordering::Relaxed)
+    }
+
+    /// Returns the number of messages exceeding the rate limit since the last call.
+    pub(crate) fn take_rate_limited(&self) -> u64 {
+        // Avoid contended writes in the common case.
+        if self.rate_limited.load(Ordering::Relaxed) == 0 {
+            return 0;
+        }
+
+        self.rate_limited.swap(0, Ordering::Relaxed)
+    }
+
+    /// Returns the time to wait if the message exceeds the rate limit.
+    #[inline]
+    fn check_rate_limit(&self, lane: &Lane, envelope: &Envelope) -> Result<(), Duration> {
+        if ptr::eq(lane, &self.high) {
+            return Ok(());
+        }
+
+        let result = self.rate_limits.acquire(envelope.message().name());
+        if result.is_err() {
+            self.rate_limited.fetch_add(1, Ordering::Relaxed);
+        }
+        result
+    }
+
+    #[cold]
+    fn exceed_policy(&self) -> ExceedPolicy {
+        self.control.lock().on_exceed
+    }
+
+    #[cold]
+    fn overflow_policy(&self, lane: &Lane) -> OverflowPolicy {
+        if ptr::eq(lane, &self.high) {
+            return OverflowPolicy::Block;
+        }
+
+        self.control.lock().on_overflow
+    }
+
+    #[cold]
+    fn replace_oldest(&self, lane: &Lane, envelope: Envelope) -> Result<(), Envelope> {
+        // The permit of the oldest message is reused by the new one.
+        if let Some(oldest) = lane.queue.dequeue() {
+            self.on_dropped(oldest);
+            lane.queue.enqueue(envelope);
+            self.rx_notify.notify_one();
+            return Ok(());
+        }
+
+        // The lane is empty, so either the receiver has just taken the last message
+        // and is going to release its permit, or the capacity is zero.
+        match lane.tx_semaphore.try_acquire() {
+            Ok(permit) => {
+                permit.forget();
+                lane.queue.enqueue(envelope);
+                self.rx_notify.notify_one();
+            }
+            Err(TryAcquireError::NoPermits) => self.on_dropped(envelope),
+            Err(TryAcquireError::Closed) => return Err(envelope),
+        }
+
+        Ok(())
+    }
+
+    #[cold]
+    fn on_dropped(&self, envelope: Envelope) {
+        self.dropped.fetch_add(1, Ordering::Relaxed);
+
+        let message = envelope.message();
+        if let Some(permit) = DUMPER.acquire_m(&*message) {
+            let kind = envelope.message_kind();
+            let mut dump = Dump::message(&*message, kind, Direction::In);
+            // Messages are dropped in the sender's scope, but belong to the receiver.
+            dump.meta = self.meta.clone();
+            permit.record(dump);
+        }
+    }
+
+    #[inline]
+    fn is_closed(&self) -> bool {
+        // Both lanes are closed together under the lock.
+        self.normal.tx_semaphore.is_closed()
+    }
+
+    fn dequeue(&self) -> Option<Envelope> {
+        // TODO: it should be possible to use `dequeue_unchecked()` here.
+        // Preliminarily, we should guarantee that it can be called only
+        // by one consumer. However, it's not enough to create a dedicated
+        // `MailboxConsumer` because users can steal `Context` to another
+        // task/thread and create a race with the `drop_all()` method.
+        if let Some(envelope) = self.high.queue.dequeue() {
+            self.high.tx_semaphore.add_permits(1);
+            return Some(envelope);
+        }
+
+        let envelope = self.normal.queue.dequeue()?;
+        self.normal.tx_semaphore.add_permits(1);
+        Some(envelope)
     }
 
     #[cold]
     fn on_close(&self) -> RecvResult {
         // Some messages may be in the queue after the channel is closed.
-        match self.queue.dequeue() {
+        let envelope = self.high.queue.dequeue();
+        match envelope.or_else(|| self.normal.queue.dequeue()) {
             Some(envelope) => RecvResult::Data(envelope),
             None => {
                 let control = self.control.lock();
@@ -279,6 +607,111 @@ impl Mailbox {
     }
 }
 
+impl Lane {
+    fn new(capacity: usize) -> Self {
+        Self {
+            queue: MpscQueue::new_with_stub(Envelope::stub()),
+            tx_semaphore: Semaphore::new(capacity),
+            capacity: AtomicUsize::new(capacity),
+        }
+    }
+
+    fn len(&self) -> usize {
+        let capacity = self.capacity.load(Ordering::Relaxed);
+        capacity.saturating_sub(self.tx_semaphore.available_permits())
+    }
+
+    /// Must be called under the `control` lock.
+    fn set_capacity(&self, capacity: usize) {
+        let real_capacity = self.capacity.load(Ordering::Relaxed);
+
+        if capacity == real_capacity {
+            return;
+        }
+
+        if capacity < real_capacity {
+            let delta = real_capacity - capacity;
+            let real_delta = self.tx_semaphore.forget_permits(delta);
+
+            // Note that we cannot reduce the number of active permits
+            // (relates to messages that already stored in the queue) in tokio impl.
+            // Sadly, in such cases, we violate provided `capacity`.
+            debug_assert!(real_delta <= delta);
+            self.capacity.fetch_sub(real_delta, Ordering::Relaxed);
+        } else {
+            let real_delta = clamp_capacity(capacity) - real_capacity;
+            self.tx_semaphore.add_permits(real_delta);
+            self.capacity.fetch_add(real_delta, Ordering::Relaxed);
+        }
+    }
+}
+
+impl RateLimits {
+    fn new(config: &RateLimitConfig) -> Self {
+        let limits = Self {
+            is_enabled: AtomicBool::new(false),
+            total: RateLimiter::default(),
+            per_message: RwLock::default(),
+        };
+
+        limits.configure(config);
+        limits
+    }
+
+    fn configure(&self, config: &RateLimitConfig) {
+        let limit = config.max_rate.map_or(RateLimit::Unlimited, |max_rate| {
+            RateLimit::Rps(max_rate.get())
+        });
+        self.total.configure(limit);
+
+        *self.per_message.write() = config
+            .per_message
+            .iter()
+            .map(|(name, max_rate)| {
+                let limiter = RateLimiter::new(RateLimit::Rps(max_rate.get()));
+                (name.clone(), limiter)
+            })
+            .collect();
+
+        let is_enabled = config.max_rate.is_some() || !config.per_message.is_empty();
+        self.is_enabled.store(is_enabled, Ordering::Relaxed);
+    }
+
+    /// Acquires permits for the message.
+    /// Returns the time to wait before the next attempt if the limit is exceeded.
+    #[inline]
+    fn acquire(&self, name: &str) -> Result<(), Duration> {
+        if !self.is_enabled.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+
+        self.acquire_slow(name)
+    }
+
+    #[cold]
+    fn acquire_slow(&self, name: &str) -> Result<(), Duration> {
+        let per_message = self.per_message.read();
+        let limiter = per_message.get(name);
+
+        if let Some(limiter) = limiter {
+            if !limiter.acquire() {
+                return Err(limiter.next_permit_in());
+            }
+        }
+
+        if !self.total.acquire() {
+            // Don't waste the permit, the message isn't sent anyway.
+            if let Some(limiter) = limiter {
+                limiter.release();
+            }
+
+            return Err(self.total.next_permit_in());
+        }
+
+        Ok(())
+    }
+}
+
 pub(crate) enum RecvResult {
     Data(Envelope),
     Closed(TraceId),
@@ -287,3 +720,43 @@ pub(crate) enum RecvResult {
 fn clamp_capacity(capacity: usize) -> usize {
     capacity.min(Semaphore::MAX_PERMITS)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use elfo_utils::time;
+
+    fn rate_limit(toml: &str) -> Result<RateLimitConfig, toml::de::Error> {
+        toml::from_str(toml)
+    }
+
+    #[test]
+    fn rate_limits() {
+        time::with_instant_mock(|mock| {
+            let config = rate_limit("max_rate = 1\nper_message = { A = 2 }").unwrap();
+            let limits = RateLimits::new(&config);
+
+            assert!(limits.acquire("B").is_ok());
+            let delay = limits.acquire("A").unwrap_err();
+            assert!(delay > Duration::ZERO && delay <= Duration::from_secs(1));
+
+            // The per-message permit isn't wasted if `max_rate` is exceeded.
+            let per_message = limits.per_message.read();
+            assert!(per_message["A"].acquire());
+            assert!(per_message["A"].acquire());
+            assert!(!per_message["A"].acquire());
+            drop(per_message);
+
+            mock.advance(delay);
+            assert!(limits.acquire("B").is_ok());
+            assert!(limits.acquire("B").is_err());
+        });
+    }
+
+    #[test]
+    fn zero_rate_limit() {
+        assert!(rate_limit("max_rate = 0").is_err());
+        assert!(rate_limit("per_message = { A = 0 }").is_err());
+    }
+}
@@ -46,6 +46,12 @@ pub trait Message:
         self._vtable().dumping_allowed
     }
 
+    #[doc(hidden)] // unstable because lanes can be reworked
+    #[inline(always)]
+    fn is_high_priority(&self) -> bool {
+        self._vtable().high_priority
+    }
+
     // Private API.
 
     #[doc(hidden)]
@@ -150,3 +156,17 @@ pub trait Request: Message {
     #[doc(hidden)]
     type Wrapper: Message + Into<Self::Response> + From<Self::Response>;
 }
+
+// === StreamRequest ===
+
+/// Represents a request responded by a stream of items followed by the end.
+///
+/// Never implement it by hand, use the `#[message(ret = stream T)]` macro
+/// instead. The responder uses [`ResponseSink`] to send items, the requester
+/// receives them using [`RequestBuilder::stream()`].
+///
+/// [`ResponseSink`]: crate::ResponseSink
+/// [`RequestBuilder::stream()`]: crate::RequestBuilder::stream
+pub trait StreamRequest: Request<Response = ()> {
+    type Item: Message;
+}
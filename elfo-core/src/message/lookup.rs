@@ -48,6 +48,23 @@ impl MessageVTable {
         MESSAGE_VTABLES_MAP.get(protocol, name)
     }
 
+    /// Finds a vtable by the message's type.
+    /// Used rarely, e.g. to describe messages in `MsgFilter`.
+    pub(crate) fn lookup_by_type_id(type_id: MessageTypeId) -> Option<&'static Self> {
+        MESSAGE_VTABLES_LIST
+            .iter()
+            .find(|vtable| MessageTypeId::new(vtable) == type_id)
+            .copied()
+    }
+
+    pub(crate) fn protocol(&self) -> &'static str {
+        self.protocol
+    }
+
+    pub(crate) fn name(&self) -> &'static str {
+        self.name
+    }
+
     #[cfg(miri)]
     pub(crate) fn register_for_miri(&'static self) {
         MESSAGE_VTABLES_MAP.register(self);
@@ -115,6 +115,7 @@ pub struct MessageVTable {
     pub(super) protocol: &'static str,
     pub(super) labels: [Label; 2],    // protocol + name for `metrics`
     pub(super) dumping_allowed: bool, // TODO: introduce `DumpingMode`.
+    pub(super) high_priority: bool,
     #[cfg(feature = "network")]
     pub(super) read_msgpack:
         unsafe fn(buffer: &[u8], out_ptr: NonNull<MessageRepr>) -> Result<(), decode::Error>,
@@ -148,6 +149,7 @@ impl MessageVTable {
         name: &'static str,
         protocol: &'static str,
         dumping_allowed: bool,
+        high_priority: bool,
     ) -> Self {
         Self {
             repr_layout: alloc::Layout::new::<MessageRepr<M>>(),
@@ -158,6 +160,7 @@ impl MessageVTable {
                 Label::from_static_parts("protocol", protocol),
             ],
             dumping_allowed,
+            high_priority,
             debug: vtablefns::debug::<M>,
             clone: vtablefns::clone::<M>,
             erase: vtablefns::erase::<M>,
@@ -2,7 +2,10 @@ use std::{fmt::Display, sync::Arc};
 
 use derive_more::Constructor;
 
-use crate::{actor::ActorMeta, actor_status::ActorStatus, config::AnyConfig, message};
+use crate::{
+    actor::ActorMeta, actor_status::ActorStatus, config::AnyConfig, message,
+    restarting::CircuitState,
+};
 
 /// A helper type for using in generic code (e.g. as an associated type) to
 /// indicate a message that cannot be constructed.
@@ -15,19 +18,19 @@ pub enum Impossible {}
 
 /// Checks that the actor is able to handle messages.
 /// Routed to all actors in a group by default and handled implicitly by actors.
-#[message(ret = ())]
+#[message(ret = (), priority = "high")]
 #[derive(Default)]
 #[non_exhaustive]
 pub struct Ping;
 
-#[message(ret = Result<(), ConfigRejected>)]
+#[message(ret = Result<(), ConfigRejected>, priority = "high")]
 #[derive(Constructor)]
 #[non_exhaustive]
 pub struct ValidateConfig {
     pub config: AnyConfig,
 }
 
-#[message(ret = Result<(), ConfigRejected>)]
+#[message(ret = Result<(), ConfigRejected>, priority = "high")]
 #[derive(Constructor)]
 #[non_exhaustive]
 pub struct UpdateConfig {
@@ -76,7 +79,7 @@ pub struct ConfigUpdated {
     // TODO: add `old_config`.
 }
 
-#[message]
+#[message(priority = "high")]
 #[derive(Default)]
 #[non_exhaustive]
 pub struct Terminate {
@@ -112,6 +115,11 @@ impl SubscribeToActorStatuses {
 pub struct ActorStatusReport {
     pub meta: Arc<ActorMeta>,
     pub status: ActorStatus,
+    /// The state of the circuit breaker, see [`RestartParams::cool_down()`].
+    ///
+    /// [`RestartParams::cool_down()`]: crate::RestartParams::cool_down
+    #[serde(default)]
+    pub circuit: CircuitState,
 }
 
 impl ActorStatusReport {
@@ -120,6 +128,47 @@ impl ActorStatusReport {
         Self {
             meta: meta.into(),
             status,
+            circuit: CircuitState::Closed,
         }
     }
 }
+
+// === Topology ===
+
+/// Sent to groups subscribed by [`Topology::subscribe_to_changes()`] once
+/// a group is mounted by [`Topology::mount_dynamic()`].
+///
+/// [`Topology::subscribe_to_changes()`]: crate::Topology::subscribe_to_changes
+/// [`Topology::mount_dynamic()`]: crate::Topology::mount_dynamic
+#[message]
+#[non_exhaustive]
+pub struct GroupMounted {
+    pub name: String,
+}
+
+/// Sent to groups subscribed by [`Topology::subscribe_to_changes()`] once
+/// a group is unmounted by [`Topology::unmount()`].
+///
+/// [`Topology::subscribe_to_changes()`]: crate::Topology::subscribe_to_changes
+/// [`Topology::unmount()`]: crate::Topology::unmount
+#[message]
+#[non_exhaustive]
+pub struct GroupUnmounted {
+    pub name: String,
+}
+
+// === Streams ===
+
+/// Emitted by a source created by [`RequestBuilder::stream()`] after all
+/// items of the streaming response.
+///
+/// [`RequestBuilder::stream()`]: crate::RequestBuilder::stream
+#[message]
+#[non_exhaustive]
+pub struct StreamEnded {
+    /// The name of the streaming request.
+    pub request: String,
+    /// `false` if the stream is ended due to an error, e.g. the responder has
+    /// failed or the request has timed out. Some items can be missed.
+    pub is_complete: bool,
+}
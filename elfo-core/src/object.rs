@@ -14,11 +14,12 @@ use smallvec::SmallVec;
 #[cfg(feature = "network")]
 use crate::remote::{self, RemoteHandle};
 use crate::{
-    actor::Actor,
+    actor::{Actor, ActorStartCause},
     addr::Addr,
     envelope::Envelope,
     errors::{RequestError, SendError, TrySendError},
     request_table::ResponseToken,
+    shutdown::{config::ShutdownConfig, ShutdownReport},
 };
 
 // Reexported in `_priv`.
@@ -175,6 +176,14 @@ impl Object {
         }
     }
 
+    #[allow(clippy::borrowed_box)]
+    pub(crate) fn as_group(&self) -> Option<&Box<dyn GroupHandle>> {
+        match &self.kind {
+            ObjectKind::Group(handle) => Some(handle),
+            _ => None,
+        }
+    }
+
     #[cfg(feature = "network")]
     #[allow(clippy::borrowed_box)]
     fn as_remote(&self) -> Option<&Box<dyn RemoteHandle>> {
@@ -254,6 +263,9 @@ where
 pub(crate) trait GroupHandle: Send + Sync + 'static {
     fn handle(&self, envelope: Envelope, visitor: &mut dyn GroupVisitor);
     fn finished(&self) -> BoxFuture<'static, ()>;
+    fn restart_all(&self, cause: ActorStartCause);
+    fn shutdown_config(&self) -> ShutdownConfig;
+    fn on_drain_timeout(&self, report: &mut ShutdownReport);
 }
 
 /// The visitor of actors inside a group.
@@ -1,14 +1,22 @@
-use std::{fmt, marker::PhantomData, sync::Arc};
+use std::{
+    fmt,
+    marker::PhantomData,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
 
+use fxhash::FxHashMap;
 use idr_ebr::EbrGuard;
 use parking_lot::Mutex;
 use slotmap::{new_key_type, Key, SlotMap};
 use smallvec::SmallVec;
-use tokio::sync::Notify;
+use tokio::{sync::Notify, time::Instant};
 
 use crate::{
     address_book::AddressBook, envelope::Envelope, errors::RequestError, message::AnyMessage,
-    tracing::TraceId, Addr,
+    response_sink::ChunkSender, tracing::TraceId, Addr,
 };
 
 // === RequestId ===
@@ -43,17 +51,23 @@ pub(crate) struct RequestTable {
     owner: Addr,
     notifier: Notify,
     requests: Mutex<SlotMap<RequestId, RequestData>>,
+    /// Channels to deliver chunks of streaming responses.
+    streams: Mutex<FxHashMap<RequestId, ChunkSender>>,
 }
 
 assert_impl_all!(RequestTable: Sync);
 
-type Responses = SmallVec<[Result<Envelope, RequestError>; 1]>;
+pub(crate) type Responses = SmallVec<[Result<Envelope, RequestError>; 1]>;
 
 #[derive(Default)]
 struct RequestData {
     remainder: usize,
     responses: Responses,
     collect_all: bool,
+    /// The number of successful responses enough to finish the request
+    /// without waiting for the rest ones. Used only with `collect_all`.
+    quorum: Option<usize>,
+    cancellation: Cancellation,
 }
 
 impl RequestData {
@@ -69,8 +83,13 @@ impl RequestData {
         self.remainder -= 1;
 
         if self.collect_all {
+            // Responses received after reaching the quorum are discarded.
+            if self.is_quorum_reached() {
+                return false;
+            }
+
             self.responses.push(response);
-            return self.remainder == 0;
+            return self.is_done();
         }
 
         // `Any` request contains at most one related response.
@@ -97,6 +116,17 @@ impl RequestData {
 
         self.remainder == 0
     }
+
+    fn is_done(&self) -> bool {
+        self.remainder == 0 || self.is_quorum_reached()
+    }
+
+    fn is_quorum_reached(&self) -> bool {
+        self.quorum.is_some_and(|quorum| {
+            let succeeded = self.responses.iter().filter(|r| r.is_ok()).count();
+            succeeded >= quorum
+        })
+    }
 }
 
 impl RequestTable {
@@ -105,6 +135,7 @@ impl RequestTable {
             owner,
             notifier: Notify::new(),
             requests: Mutex::new(SlotMap::default()),
+            streams: Mutex::new(FxHashMap::default()),
         }
     }
 
@@ -113,22 +144,68 @@ impl RequestTable {
         book: AddressBook,
         trace_id: TraceId,
         collect_all: bool,
+        quorum: Option<usize>,
+        deadline: Option<Instant>,
     ) -> ResponseToken {
+        debug_assert!(quorum.is_none() || collect_all);
+
+        let cancellation = Cancellation::default();
         let mut requests = self.requests.lock();
         let request_id = requests.insert(RequestData {
             remainder: 1,
             responses: Responses::new(),
             collect_all,
+            quorum,
+            cancellation: cancellation.clone(),
         });
-        ResponseToken::new(self.owner, request_id, trace_id, book)
+        let data = ResponseTokenData {
+            sender: self.owner,
+            request_id,
+            trace_id,
+            deadline,
+            book,
+            cancellation,
+        };
+        ResponseToken::from_data(data)
     }
 
+    /// Removes the request and notifies responders that the requester doesn't
+    /// wait for responses anymore. Does nothing if the request is done.
     pub(crate) fn cancel_request(&self, request_id: RequestId) {
-        let mut requests = self.requests.lock();
-        requests.remove(request_id);
+        let request = self.requests.lock().remove(request_id);
+
+        if let Some(request) = request {
+            request.cancellation.cancel();
+        }
+    }
+
+    pub(crate) fn register_stream(&self, request_id: RequestId, sender: ChunkSender) {
+        self.streams.lock().insert(request_id, sender);
+    }
+
+    pub(crate) fn unregister_stream(&self, request_id: RequestId) {
+        self.streams.lock().remove(&request_id);
+    }
+
+    pub(crate) fn stream(&self, request_id: RequestId) -> Option<ChunkSender> {
+        self.streams.lock().get(&request_id).cloned()
+    }
+
+    /// Waits for all responses or until the deadline is reached.
+    /// In the latter case, the request is removed from the table and
+    /// missing responses are replaced with `RequestError::Timeout`.
+    pub(crate) async fn wait(&self, request_id: RequestId, deadline: Option<Instant>) -> Responses {
+        let Some(deadline) = deadline else {
+            return self.do_wait(request_id).await;
+        };
+
+        match tokio::time::timeout_at(deadline, self.do_wait(request_id)).await {
+            Ok(responses) => responses,
+            Err(_) => self.expire_request(request_id),
+        }
     }
 
-    pub(crate) async fn wait(&self, request_id: RequestId) -> Responses {
+    async fn do_wait(&self, request_id: RequestId) -> Responses {
         loop {
             let waiting = self.notifier.notified();
 
@@ -136,8 +213,15 @@ impl RequestTable {
                 let mut requests = self.requests.lock();
                 let request = requests.get(request_id).expect("unknown request");
 
-                if request.remainder == 0 {
-                    break requests.remove(request_id).expect("under lock").responses;
+                if request.is_done() {
+                    let request = requests.remove(request_id).expect("under lock");
+
+                    // The quorum is reached, so the rest responses aren't needed.
+                    if request.remainder > 0 {
+                        request.cancellation.cancel();
+                    }
+
+                    break request.responses;
                 }
             }
 
@@ -145,6 +229,31 @@ impl RequestTable {
         }
     }
 
+    fn expire_request(&self, request_id: RequestId) -> Responses {
+        let mut requests = self.requests.lock();
+        let mut request = requests.remove(request_id).expect("unknown request");
+
+        // Responses can be received right before the deadline.
+        if request.is_done() {
+            if request.remainder > 0 {
+                request.cancellation.cancel();
+            }
+
+            return request.responses;
+        }
+
+        if request.collect_all {
+            let timeouts = (0..request.remainder).map(|_| Err(RequestError::Timeout));
+            request.responses.extend(timeouts);
+        } else {
+            // `Any` request contains at most one (failed) response, replace it.
+            request.responses.clear();
+            request.responses.push(Err(RequestError::Timeout));
+        }
+
+        request.responses
+    }
+
     pub(crate) fn resolve(
         &self,
         mut token: ResponseToken,
@@ -180,23 +289,37 @@ struct ResponseTokenData {
     sender: Addr,
     request_id: RequestId,
     trace_id: TraceId,
+    deadline: Option<Instant>,
     book: AddressBook,
+    cancellation: Cancellation,
 }
 
 impl ResponseToken {
     #[doc(hidden)]
     #[inline]
-    pub fn new(sender: Addr, request_id: RequestId, trace_id: TraceId, book: AddressBook) -> Self {
-        debug_assert!(!sender.is_null());
-        debug_assert!(!request_id.is_null());
+    pub fn new(
+        sender: Addr,
+        request_id: RequestId,
+        trace_id: TraceId,
+        deadline: Option<Instant>,
+        book: AddressBook,
+    ) -> Self {
+        Self::from_data(ResponseTokenData {
+            sender,
+            request_id,
+            trace_id,
+            deadline,
+            book,
+            cancellation: Cancellation::default(),
+        })
+    }
+
+    fn from_data(data: ResponseTokenData) -> Self {
+        debug_assert!(!data.sender.is_null());
+        debug_assert!(!data.request_id.is_null());
 
         Self {
-            data: Some(Arc::new(ResponseTokenData {
-                sender,
-                request_id,
-                trace_id,
-                book,
-            })),
+            data: Some(Arc::new(data)),
             received: false,
             marker: PhantomData,
         }
@@ -226,6 +349,14 @@ impl ResponseToken {
         self.data.as_ref().map(|data| data.request_id).unwrap()
     }
 
+    /// # Panics
+    /// If the token is forgotten.
+    #[doc(hidden)]
+    #[inline]
+    pub fn deadline(&self) -> Option<Instant> {
+        self.data.as_ref().map(|data| data.deadline).unwrap()
+    }
+
     /// # Panics
     /// If the token is forgotten.
     #[doc(hidden)]
@@ -299,11 +430,69 @@ impl<R> ResponseToken<R> {
     pub fn is_forgotten(&self) -> bool {
         self.data.is_none()
     }
-}
 
-impl<T> Drop for ResponseToken<T> {
+    /// Returns the requester and the request's ID, `None` if forgotten.
+    pub(crate) fn origin(&self) -> Option<(Addr, RequestId)> {
+        self.data
+            .as_ref()
+            .map(|data| (data.sender, data.request_id))
+    }
+
+    /// Returns `true` if the request has a deadline and it has already passed,
+    /// so the requester doesn't wait for the response anymore.
+    ///
+    /// Forgotten tokens are never expired.
     #[inline]
-    fn drop(&mut self) {
+    pub fn is_expired(&self) -> bool {
+        self.data
+            .as_ref()
+            .and_then(|data| data.deadline)
+            .is_some_and(|deadline| deadline <= Instant::now())
+    }
+
+    /// Returns `true` if the requester doesn't wait for the response anymore,
+    /// e.g. the future returned by `RequestBuilder::resolve()` is dropped.
+    /// Responders can use it to stop doing useless work.
+    ///
+    /// Cancellation of remote requests is propagated if both nodes support it.
+    /// Forgotten tokens are never cancelled.
+    #[inline]
+    pub fn is_cancelled(&self) -> bool {
+        self.data
+            .as_ref()
+            .is_some_and(|data| data.cancellation.is_cancelled())
+    }
+
+    /// Waits until the request is cancelled, see [`ResponseToken::is_cancelled()`].
+    ///
+    /// Never completes for forgotten tokens.
+    ///
+    /// # Example
+    /// ```ignore
+    /// tokio::select! {
+    ///     response = do_heavy_work() => ctx.respond(token, response),
+    ///     _ = token.cancelled() => info!("the request is cancelled"),
+    /// }
+    /// ```
+    pub async fn cancelled(&self) {
+        match &self.data {
+            Some(data) => data.cancellation.cancelled().await,
+            None => std::future::pending().await,
+        }
+    }
+
+    #[doc(hidden)]
+    #[inline]
+    pub fn cancellation(&self) -> Option<&Cancellation> {
+        self.data.as_ref().map(|data| &data.cancellation)
+    }
+
+    /// Responds with `RequestError::Timeout` to the requester.
+    pub(crate) fn expire(mut self) {
+        self.respond_with_error(RequestError::Timeout);
+    }
+
+    fn respond_with_error(&mut self, err: RequestError) {
         // Do nothing for forgotten tokens.
         let data = ward!(self.data.take());
         let book = data.book.clone();
@@ -314,13 +503,21 @@ impl<T> Drop for ResponseToken<T> {
             received: self.received,
             marker: PhantomData,
         };
+
+        object.respond(this, Err(err));
+    }
+}
+
+impl<T> Drop for ResponseToken<T> {
+    #[inline]
+    fn drop(&mut self) {
         let err = if self.received {
             RequestError::Ignored
         } else {
             RequestError::Failed
         };
 
-        object.respond(this, Err(err));
+        self.respond_with_error(err);
     }
 }
 
@@ -330,6 +527,73 @@ impl<T> fmt::Debug for ResponseToken<T> {
     }
 }
 
+// === Cancellation ===
+
+/// Shared by the requester and responders of the same request to notify
+/// responders once the requester doesn't wait for responses anymore.
+#[doc(hidden)]
+#[derive(Clone, Default)]
+pub struct Cancellation(Arc<CancellationInner>);
+
+#[derive(Default)]
+struct CancellationInner {
+    is_cancelled: AtomicBool,
+    notifier: Notify,
+    hooks: Mutex<Vec<Box<dyn FnOnce() + Send>>>,
+}
+
+impl Cancellation {
+    #[doc(hidden)]
+    pub fn cancel(&self) {
+        if self.0.is_cancelled.swap(true, Ordering::AcqRel) {
+            return;
+        }
+
+        self.0.notifier.notify_waiters();
+
+        let hooks = std::mem::take(&mut *self.0.hooks.lock());
+        for hook in hooks {
+            hook();
+        }
+    }
+
+    #[doc(hidden)]
+    #[inline]
+    pub fn is_cancelled(&self) -> bool {
+        self.0.is_cancelled.load(Ordering::Acquire)
+    }
+
+    #[doc(hidden)]
+    pub async fn cancelled(&self) {
+        loop {
+            let waiting = self.0.notifier.notified();
+
+            if self.is_cancelled() {
+                break;
+            }
+
+            waiting.await;
+        }
+    }
+
+    /// Adds the hook called once the request is cancelled.
+    /// If it's already cancelled, the hook is called immediately.
+    ///
+    /// Used by `elfo-network` to propagate cancellation to remote nodes,
+    /// so every connection to a node with recipients adds its own hook.
+    #[doc(hidden)]
+    pub fn on_cancel(&self, hook: impl FnOnce() + Send + 'static) {
+        let mut hooks = self.0.hooks.lock();
+
+        if self.is_cancelled() {
+            drop(hooks);
+            hook();
+        } else {
+            hooks.push(Box::new(hook));
+        }
+    }
+}
+
 #[cfg(test)]
 #[cfg(TODO)]
 mod tests {
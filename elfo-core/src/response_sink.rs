@@ -0,0 +1,109 @@
+use std::{fmt, mem};
+
+use futures::channel::mpsc;
+
+use crate::{
+    errors::RequestError,
+    message,
+    message::{AnyMessage, StreamRequest},
+    request_table::ResponseToken,
+    Context,
+};
+
+/// A chunk of items of a streaming response.
+///
+/// Chunks are sent as requests to the requester, which responds once all
+/// items of the chunk are emitted by its source. Thus, at most one chunk per
+/// stream is in flight, both locally and over the network.
+#[message(ret = ())]
+pub(crate) struct StreamChunk {
+    pub(crate) request_id: u64,
+    pub(crate) items: Vec<AnyMessage>,
+}
+
+pub(crate) type ChunkSender = mpsc::UnboundedSender<(Vec<AnyMessage>, ResponseToken<StreamChunk>)>;
+
+// === ResponseSink ===
+
+/// Sends items of a streaming response, see [`Context::response_sink()`].
+///
+/// Items are buffered and sent in chunks. Sending waits until the previous
+/// chunk is handled by the requester, so the responder cannot overwhelm it.
+///
+/// The stream must be finished by [`ResponseSink::close()`]. If the sink is
+/// dropped instead, the requester gets the stream ended with an error.
+#[must_use]
+pub struct ResponseSink<R> {
+    context: Context,
+    token: ResponseToken<R>,
+    buffer: Vec<AnyMessage>,
+    chunk_size: usize,
+}
+
+impl<R: StreamRequest> ResponseSink<R> {
+    pub(crate) fn new(context: Context, token: ResponseToken<R>) -> Self {
+        Self {
+            context,
+            token,
+            buffer: Vec::new(),
+            chunk_size: 64,
+        }
+    }
+
+    /// Sets the maximum number of items sent in one chunk.
+    ///
+    /// `64` by default.
+    pub fn chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size.max(1);
+        self
+    }
+
+    /// Adds the item to the stream, sending the chunk if it's full.
+    ///
+    /// Returns an error if the requester doesn't wait for items anymore.
+    pub async fn send(&mut self, item: R::Item) -> Result<(), RequestError> {
+        self.buffer.push(AnyMessage::new(item));
+
+        if self.buffer.len() >= self.chunk_size {
+            self.flush().await
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Sends buffered items and waits until they are handled by the requester.
+    pub async fn flush(&mut self) -> Result<(), RequestError> {
+        let items = mem::take(&mut self.buffer);
+
+        // Forgotten tokens don't wait for anything.
+        let Some((requester, request_id)) = self.token.origin() else {
+            return Ok(());
+        };
+
+        if items.is_empty() {
+            return Ok(());
+        }
+
+        let chunk = StreamChunk {
+            request_id: request_id.to_ffi(),
+            items,
+        };
+
+        self.context.request_to(requester, chunk).resolve().await
+    }
+
+    /// Sends buffered items and ends the stream.
+    pub async fn close(mut self) -> Result<(), RequestError> {
+        self.flush().await?;
+        self.context.respond(self.token, ());
+        Ok(())
+    }
+}
+
+impl<R> fmt::Debug for ResponseSink<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResponseSink")
+            .field("buffered", &self.buffer.len())
+            .finish()
+    }
+}
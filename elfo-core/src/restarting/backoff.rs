@@ -7,6 +7,8 @@ pub(crate) struct RestartBackoff {
     start_time: Instant,
     restart_count: u64,
     power: u64,
+    /// `Some(auto_reset)` if retries are exhausted and the circuit is open.
+    circuit_open: Option<Duration>,
 }
 
 impl Default for RestartBackoff {
@@ -15,6 +17,7 @@ impl Default for RestartBackoff {
             start_time: Instant::now(),
             restart_count: 0,
             power: 0,
+            circuit_open: None,
         }
     }
 }
@@ -24,17 +27,37 @@ impl RestartBackoff {
         self.start_time = Instant::now();
     }
 
+    /// Returns `Some(auto_reset)` if the circuit breaker is open, so the next
+    /// start is a trial one, and the actor is considered healthy again after
+    /// `auto_reset`.
+    pub(crate) fn circuit_open(&self) -> Option<Duration> {
+        self.circuit_open
+    }
+
+    /// Closes the circuit once the trial actor is considered healthy.
+    pub(crate) fn close_circuit(&mut self) {
+        self.restart_count = 0;
+        self.power = 0;
+        self.circuit_open = None;
+    }
+
     pub(crate) fn next(&mut self, params: &RestartParams) -> Option<Duration> {
         // If an actor is alive enough time, reset the backoff.
         if self.start_time.elapsed() >= params.auto_reset {
             self.restart_count = 1;
             self.power = 0;
+            self.circuit_open = None;
             return Some(Duration::ZERO);
         }
         self.restart_count += 1;
 
         if self.restart_count > params.max_retries.get() {
-            return None;
+            let cool_down = params.cool_down?;
+            // Only one attempt is allowed in the half-open state, so the next
+            // failure exceeds the limit again and reopens the circuit.
+            self.restart_count = params.max_retries.get();
+            self.circuit_open = Some(params.auto_reset);
+            return Some(cool_down);
         }
 
         let delay = (params.min_backoff.as_secs_f64() * params.factor.powf(self.power as f64))
@@ -132,4 +155,36 @@ mod tests {
         assert_eq!(backoff.next(&params), Some(params.max_backoff));
         assert_eq!(backoff.next(&params), None);
     }
+
+    #[test]
+    fn circuit_breaker() {
+        time::with_instant_mock(|mock| {
+            let mut backoff = RestartBackoff::default();
+            let params = RestartParams::new(Duration::from_secs(5), Duration::from_secs(30))
+                .max_retries(NonZeroU64::new(2).unwrap())
+                .cool_down(Duration::from_secs(60));
+
+            assert_eq!(backoff.next(&params), Some(params.min_backoff));
+            assert_eq!(backoff.next(&params), Some(2 * params.min_backoff));
+            assert_eq!(backoff.circuit_open(), None);
+
+            // Retries are exhausted, the circuit is open.
+            assert_eq!(backoff.next(&params), Some(Duration::from_secs(60)));
+            assert_eq!(backoff.circuit_open(), Some(params.auto_reset));
+            mock.advance(Duration::from_secs(60));
+            backoff.start();
+
+            // The trial attempt has failed, the circuit is open again.
+            mock.advance(params.min_backoff / 2);
+            assert_eq!(backoff.next(&params), Some(Duration::from_secs(60)));
+            assert_eq!(backoff.circuit_open(), Some(params.auto_reset));
+            mock.advance(Duration::from_secs(60));
+            backoff.start();
+
+            // The trial attempt has succeeded, the circuit is closed.
+            mock.advance(params.auto_reset);
+            assert_eq!(backoff.next(&params), Some(Duration::ZERO));
+            assert_eq!(backoff.circuit_open(), None);
+        });
+    }
 }
@@ -27,10 +27,12 @@ use crate::restarting::restart_policy::{RestartParams, RestartPolicy};
 /// [`Context::set_restart_policy()`]: crate::Context::set_restart_policy
 /// [The Actoromicon]: https://actoromicon.rs/ch04-02-supervision.html#restart
 #[derive(Debug, Clone, Default, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct RestartPolicyConfig(pub Option<WhenConfig>);
 
 /// Restart policies.
 #[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(tag = "when")]
 pub enum WhenConfig {
     /// Restart both on failures and terminations.
@@ -43,18 +45,22 @@ pub enum WhenConfig {
 
 /// Restart policy parameters.
 #[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct RestartParamsConfig {
     /// Minimal restart time limit.
     #[serde(with = "humantime_serde")]
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
     min_backoff: Duration,
     /// Maximum restart time limit.
     #[serde(with = "humantime_serde")]
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
     max_backoff: Duration,
     /// The duration of an actor's lifecycle sufficient to deem the actor
     /// healthy.
     ///
     /// The default value is `min_backoff`.
     #[serde(with = "humantime_serde", default)]
+    #[cfg_attr(feature = "schema", schemars(with = "Option<String>"))]
     auto_reset: Option<Duration>,
     /// The limit on retry attempts, after which the actor stops attempts to
     /// restart.
@@ -65,6 +71,13 @@ pub struct RestartParamsConfig {
     ///
     /// Default value is 2.0.
     factor: Option<f64>,
+    /// The duration after which the actor is started once more after
+    /// exhausting `max_retries`.
+    ///
+    /// The circuit breaker is disabled by default.
+    #[serde(with = "humantime_serde", default)]
+    #[cfg_attr(feature = "schema", schemars(with = "Option<String>"))]
+    cool_down: Option<Duration>,
 }
 
 impl RestartPolicyConfig {
@@ -83,5 +96,6 @@ impl RestartParamsConfig {
             .factor(self.factor)
             .auto_reset(self.auto_reset)
             .max_retries(self.max_retries)
+            .cool_down(self.cool_down)
     }
 }
@@ -4,4 +4,4 @@ mod backoff;
 mod restart_policy;
 
 pub(crate) use self::backoff::RestartBackoff;
-pub use restart_policy::{RestartParams, RestartPolicy};
+pub use restart_policy::{CircuitState, RestartParams, RestartPolicy};
@@ -1,5 +1,6 @@
 use std::{num::NonZeroU64, time::Duration};
 
+use serde::{Deserialize, Serialize};
 use tracing::warn;
 
 use crate::ActorStatus;
@@ -67,15 +68,17 @@ pub struct RestartParams {
     pub(crate) auto_reset: Duration,
     pub(crate) max_retries: NonZeroU64,
     pub(crate) factor: f64,
+    pub(crate) cool_down: Option<Duration>,
 }
 
 impl RestartParams {
     /// Creates a new instance with the specified minimum and maximum backoff
-    /// durations. The default values for `auto_reset`, `max_retries`, and
-    /// `factor` are set as follows:
+    /// durations. The default values for `auto_reset`, `max_retries`,
+    /// `factor` and `cool_down` are set as follows:
     /// - `auto_reset = min_backoff`
     /// - `max_retries = NonZeroU64::MAX`
     /// - `factor = 2.0`
+    /// - `cool_down = None`
     pub fn new(min_backoff: Duration, max_backoff: Duration) -> Self {
         RestartParams {
             min_backoff,
@@ -83,6 +86,7 @@ impl RestartParams {
             auto_reset: min_backoff,
             max_retries: NonZeroU64::MAX,
             factor: 2.0,
+            cool_down: None,
         }
     }
 
@@ -140,4 +144,45 @@ impl RestartParams {
             ..self
         }
     }
+
+    /// Enables the circuit breaker: once the retries reach `max_retries`,
+    /// the circuit is opened instead of stopping the actor forever. While the
+    /// circuit is open, the actor stays dead and messages routed to it by
+    /// [`Outcome::Unicast`] are rejected with a [`SendError`] immediately.
+    /// After `cool_down` elapses, the circuit becomes half-open and the actor
+    /// is started once more. If the actor lives long enough to be considered
+    /// healthy (see [RestartParams::auto_reset]), the circuit is closed.
+    /// Otherwise, the circuit is opened again for another `cool_down`.
+    ///
+    /// The state of the circuit is reported in [`ActorStatusReport`].
+    ///
+    /// `None` does not change the `cool_down` setting.
+    ///
+    /// If the function isn't used, the circuit breaker is disabled.
+    ///
+    /// [`Outcome::Unicast`]: crate::routers::Outcome::Unicast
+    /// [`SendError`]: crate::errors::SendError
+    /// [`ActorStatusReport`]: crate::messages::ActorStatusReport
+    pub fn cool_down(self, cool_down: impl Into<Option<Duration>>) -> Self {
+        Self {
+            cool_down: cool_down.into().or(self.cool_down),
+            ..self
+        }
+    }
+}
+
+/// The state of the circuit breaker of an actor.
+/// See [`RestartParams::cool_down()`] for details.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum CircuitState {
+    /// The actor is restarted according to the backoff strategy.
+    #[default]
+    Closed,
+    /// Retries are exhausted, the actor isn't restarted until the cool-down
+    /// elapses.
+    Open,
+    /// The actor has been started after the cool-down and isn't considered
+    /// healthy yet.
+    HalfOpen,
 }
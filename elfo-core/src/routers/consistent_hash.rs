@@ -0,0 +1,334 @@
+use std::{
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    sync::Arc,
+};
+
+use arc_swap::ArcSwap;
+use fxhash::FxHasher64;
+
+use super::{Outcome, Router};
+use crate::{envelope::Envelope, message::Message};
+
+type Extractor = Box<dyn Fn(&Envelope) -> Option<u64> + Send + Sync>;
+type RebalanceHook = Box<dyn Fn(&Rebalance<'_>) + Send + Sync>;
+
+/// A router that distributes messages among shards using consistent hashing.
+///
+/// Keys of actors are shard numbers in `0..shards`, where the number of shards
+/// is provided by the config. Each shard owns `virtual_nodes` points on the
+/// hash ring, so changing the number of shards moves only a small part of
+/// keys to other shards.
+///
+/// Messages are routed by keys, which are extracted by functions registered
+/// by [`ConsistentHashRouter::key()`] for each message type. All other messages
+/// are routed as [`Outcome::Default`].
+///
+/// # Example
+/// ```
+/// # use elfo_core as elfo;
+/// # #[elfo::message]
+/// # struct AddOrder { instrument: u32 }
+/// # #[elfo::message]
+/// # struct CancelOrder { instrument: u32 }
+/// # #[derive(serde::Deserialize)]
+/// # struct Config { shards: usize }
+/// use elfo::routers::ConsistentHashRouter;
+///
+/// let router = ConsistentHashRouter::new(|config: &Config| config.shards)
+///     .virtual_nodes(64)
+///     .key(|msg: &AddOrder| msg.instrument)
+///     .key(|msg: &CancelOrder| msg.instrument)
+///     .on_rebalance(|rebalance| {
+///         tracing::info!(
+///             from = rebalance.old_shards(),
+///             to = rebalance.new_shards(),
+///             moved = rebalance.moved_fraction(),
+///             "rebalanced",
+///         );
+///     });
+/// ```
+pub struct ConsistentHashRouter<C, S> {
+    config: PhantomData<C>,
+    shards: S,
+    virtual_nodes: usize,
+    extractors: Vec<Extractor>,
+    on_rebalance: Option<RebalanceHook>,
+    ring: ArcSwap<Ring>,
+}
+
+impl<C, S> ConsistentHashRouter<C, S>
+where
+    C: Send + Sync + 'static,
+    S: Fn(&C) -> usize + Send + Sync + 'static,
+{
+    /// Creates a new router, `shards` returns the number of shards.
+    pub fn new(shards: S) -> Self {
+        Self {
+            config: PhantomData,
+            shards,
+            virtual_nodes: 100,
+            extractors: Vec::new(),
+            on_rebalance: None,
+            ring: ArcSwap::default(),
+        }
+    }
+
+    /// Sets the number of points on the hash ring per shard.
+    /// More points provide more even distribution, but slower routing.
+    ///
+    /// `100` by default.
+    ///
+    /// # Panics
+    ///
+    /// If `virtual_nodes` is zero.
+    pub fn virtual_nodes(mut self, virtual_nodes: usize) -> Self {
+        assert!(
+            virtual_nodes > 0,
+            "the number of virtual nodes must be positive"
+        );
+        self.virtual_nodes = virtual_nodes;
+        self
+    }
+
+    /// Registers a key extractor for the message type `M`.
+    pub fn key<M, K>(mut self, extract: impl Fn(&M) -> K + Send + Sync + 'static) -> Self
+    where
+        M: Message,
+        K: Hash,
+    {
+        self.extractors.push(Box::new(move |envelope| {
+            let message = envelope.message().downcast_ref::<M>()?;
+            Some(hash(&extract(message)))
+        }));
+        self
+    }
+
+    /// Registers a function called once the number of shards is changed.
+    pub fn on_rebalance(mut self, f: impl Fn(&Rebalance<'_>) + Send + Sync + 'static) -> Self {
+        self.on_rebalance = Some(Box::new(f));
+        self
+    }
+}
+
+impl<C, S> Router<C> for ConsistentHashRouter<C, S>
+where
+    C: Send + Sync + 'static,
+    S: Fn(&C) -> usize + Send + Sync + 'static,
+{
+    type Key = usize;
+
+    fn update(&self, config: &C) {
+        let shards = (self.shards)(config);
+        let old = self.ring.load_full();
+
+        if old.shards == shards {
+            return;
+        }
+
+        let new = Arc::new(Ring::new(shards, self.virtual_nodes));
+
+        if let Some(on_rebalance) = &self.on_rebalance {
+            // The initial configuration isn't considered as a rebalance.
+            if old.shards > 0 {
+                on_rebalance(&Rebalance {
+                    old: &old,
+                    new: &new,
+                });
+            }
+        }
+
+        self.ring.store(new);
+    }
+
+    #[inline]
+    fn route(&self, envelope: &Envelope) -> Outcome<Self::Key> {
+        let Some(key) = self.extractors.iter().find_map(|extract| extract(envelope)) else {
+            return Outcome::Default;
+        };
+
+        match self.ring.load().lookup(key) {
+            Some(shard) => Outcome::Unicast(shard),
+            None => Outcome::Discard,
+        }
+    }
+}
+
+// === Rebalance ===
+
+/// Describes changes in the distribution of keys after changing the number of
+/// shards. Passed to the function registered by
+/// [`ConsistentHashRouter::on_rebalance()`].
+pub struct Rebalance<'a> {
+    old: &'a Ring,
+    new: &'a Ring,
+}
+
+impl Rebalance<'_> {
+    /// Returns the previous number of shards.
+    pub fn old_shards(&self) -> usize {
+        self.old.shards
+    }
+
+    /// Returns the current number of shards.
+    pub fn new_shards(&self) -> usize {
+        self.new.shards
+    }
+
+    /// Returns `Some((old_shard, new_shard))` if the key has been moved to
+    /// another shard, `None` otherwise.
+    ///
+    /// The key must be the same as returned by the extractor registered by
+    /// [`ConsistentHashRouter::key()`].
+    pub fn moved<K: Hash>(&self, key: &K) -> Option<(usize, usize)> {
+        let key = hash(key);
+        let old = self.old.lookup(key)?;
+        let new = self.new.lookup(key)?;
+        (old != new).then_some((old, new))
+    }
+
+    /// Returns the approximate fraction of keys moved to other shards,
+    /// in the range `[0, 1]`.
+    pub fn moved_fraction(&self) -> f64 {
+        // Both rings are split into segments by points of both rings.
+        // Each segment is owned by one shard in each ring.
+        let mut bounds = self
+            .old
+            .points
+            .iter()
+            .chain(&self.new.points)
+            .map(|(point, _)| *point)
+            .collect::<Vec<_>>();
+        bounds.sort_unstable();
+        bounds.dedup();
+
+        let Some(&last) = bounds.last() else {
+            return 0.;
+        };
+
+        let mut moved = 0u128;
+        let mut prev = last;
+
+        for &bound in &bounds {
+            // Keys in `(prev, bound]` are owned by the shard of `bound`.
+            if self.old.lookup(bound) != self.new.lookup(bound) {
+                moved += u128::from(bound.wrapping_sub(prev));
+            }
+            prev = bound;
+        }
+
+        // A ring with the only point has a segment covering the whole ring.
+        if bounds.len() == 1 && self.old.lookup(last) != self.new.lookup(last) {
+            return 1.;
+        }
+
+        moved as f64 / (u128::from(u64::MAX) + 1) as f64
+    }
+}
+
+// === Ring ===
+
+#[derive(Default)]
+struct Ring {
+    shards: usize,
+    /// Sorted by points.
+    points: Vec<(u64, usize)>,
+}
+
+impl Ring {
+    fn new(shards: usize, virtual_nodes: usize) -> Self {
+        let mut points = (0..shards)
+            .flat_map(|shard| (0..virtual_nodes).map(move |vnode| (hash(&(shard, vnode)), shard)))
+            .collect::<Vec<_>>();
+
+        points.sort_unstable();
+
+        Self { shards, points }
+    }
+
+    /// Returns the shard owning the first point at or after `key`.
+    fn lookup(&self, key: u64) -> Option<usize> {
+        let idx = self.points.partition_point(|(point, _)| *point < key);
+        let (_, shard) = self.points.get(idx).or_else(|| self.points.first())?;
+        Some(*shard)
+    }
+}
+
+/// A stable hash, which doesn't depend on the process.
+fn hash(key: &impl Hash) -> u64 {
+    let mut hasher = FxHasher64::default();
+    key.hash(&mut hasher);
+
+    // `FxHasher` isn't well distributed, so apply the `splitmix64` finalizer.
+    let mut h = hasher.finish();
+    h = (h ^ (h >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    h = (h ^ (h >> 27)).wrapping_mul(0x94d049bb133111eb);
+    h ^ (h >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn distribution(ring: &Ring, keys: u64) -> Vec<u64> {
+        let mut counts = vec![0; ring.shards];
+        for key in 0..keys {
+            counts[ring.lookup(hash(&key)).unwrap()] += 1;
+        }
+        counts
+    }
+
+    #[test]
+    fn empty() {
+        let ring = Ring::new(0, 10);
+        assert_eq!(ring.lookup(42), None);
+    }
+
+    #[test]
+    fn balanced() {
+        let ring = Ring::new(8, 100);
+        for count in distribution(&ring, 80_000) {
+            assert!((7_000..13_000).contains(&count), "{count}");
+        }
+    }
+
+    #[test]
+    fn rebalance() {
+        let old = Ring::new(4, 100);
+        let new = Ring::new(5, 100);
+        let rebalance = Rebalance {
+            old: &old,
+            new: &new,
+        };
+
+        let keys = 10_000u64;
+        let mut moved = 0;
+        for key in 0..keys {
+            if let Some((from, to)) = rebalance.moved(&key) {
+                // Keys are moved only to the added shard.
+                assert_ne!(from, 4);
+                assert_eq!(to, 4);
+                moved += 1;
+            }
+        }
+
+        let fraction = rebalance.moved_fraction();
+        assert!((0.1..0.3).contains(&fraction), "{fraction}");
+
+        let actual = moved as f64 / keys as f64;
+        assert!((actual - fraction).abs() < 0.05, "{actual} vs {fraction}");
+    }
+
+    #[test]
+    fn rebalance_same() {
+        let old = Ring::new(3, 10);
+        let new = Ring::new(3, 10);
+        let rebalance = Rebalance {
+            old: &old,
+            new: &new,
+        };
+
+        assert_eq!(rebalance.moved_fraction(), 0.);
+        assert_eq!(rebalance.moved(&42), None);
+    }
+}
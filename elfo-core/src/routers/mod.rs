@@ -5,9 +5,15 @@ use std::{
 
 use crate::{envelope::Envelope, msg};
 
-pub use self::map::MapRouter;
+pub use self::{
+    consistent_hash::{ConsistentHashRouter, Rebalance},
+    map::MapRouter,
+    pool::{Dispatch, PoolRouter},
+};
 
+mod consistent_hash;
 mod map;
+mod pool;
 
 pub trait Router<C>: Send + Sync + 'static {
     type Key: Clone + Hash + Eq + Display + Send + Sync; // TODO: why is `Sync` required?
@@ -36,6 +42,10 @@ pub enum Outcome<T> {
     /// If there is no active or restarting actors for these keys,
     /// the message will be descarded, no actors are started.
     GentleMulticast(Vec<T>),
+    /// Routes a message to an actor with the shortest mailbox among actors
+    /// with specified keys. If there is no active or restarting actors for
+    /// these keys, they will be started.
+    LeastLoaded(Vec<T>),
     /// Routes a message to all active actors.
     Broadcast,
     /// Discards a message.
@@ -53,7 +63,7 @@ assert_eq_size!(Outcome<u64>, [u8; 32]);
 assert_eq_size!(Outcome<u128>, [u8; 32]);
 
 impl<T> Outcome<T> {
-    /// Transforms `Unicast` and `Multicast` variants.
+    /// Transforms `Unicast`, `Multicast` and `LeastLoaded` variants.
     #[inline]
     pub fn map<U>(self, mut f: impl FnMut(T) -> U) -> Outcome<U> {
         match self {
@@ -63,6 +73,7 @@ impl<T> Outcome<T> {
             Outcome::GentleMulticast(list) => {
                 Outcome::GentleMulticast(list.into_iter().map(f).collect())
             }
+            Outcome::LeastLoaded(list) => Outcome::LeastLoaded(list.into_iter().map(f).collect()),
             Outcome::Broadcast => Outcome::Broadcast,
             Outcome::Discard => Outcome::Discard,
             Outcome::Default => Outcome::Default,
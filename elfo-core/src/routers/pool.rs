@@ -0,0 +1,138 @@
+use std::{
+    collections::hash_map::RandomState,
+    hash::BuildHasher,
+    marker::PhantomData,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use super::{Outcome, Router};
+use crate::{envelope::Envelope, messages, msg};
+
+/// A router that spreads messages across a pool of identical actors.
+///
+/// Keys of actors are numbers in `0..size`, where the size is provided by the
+/// config. All pool members are started once the group is mounted, and
+/// restarted according to the group's [`RestartPolicy`] like any other actors.
+/// If the size is reduced, extra members don't receive new messages, but
+/// aren't stopped automatically and still receive config updates.
+///
+/// System messages are routed as [`Outcome::Default`], all other messages are
+/// routed to one of pool members according to [`Dispatch`].
+///
+/// # Example
+/// ```
+/// # use elfo_core as elfo;
+/// # #[derive(serde::Deserialize)]
+/// # struct Config { workers: usize }
+/// use elfo::routers::{Dispatch, PoolRouter};
+///
+/// let router = PoolRouter::new(|config: &Config| config.workers)
+///     .dispatch(Dispatch::LeastLoaded);
+/// ```
+///
+/// [`RestartPolicy`]: crate::RestartPolicy
+pub struct PoolRouter<C, S> {
+    config: PhantomData<C>,
+    size_fn: S,
+    dispatch: Dispatch,
+    size: AtomicUsize,
+    /// The largest size ever configured, all members have keys below it.
+    max_size: AtomicUsize,
+    counter: AtomicUsize,
+    random: RandomState,
+}
+
+/// Specifies how [`PoolRouter`] chooses a pool member.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Dispatch {
+    /// Members are chosen one by one.
+    #[default]
+    RoundRobin,
+    /// Members are chosen randomly.
+    Random,
+    /// A member with the shortest mailbox is chosen.
+    /// See [`Outcome::LeastLoaded`] for details.
+    LeastLoaded,
+}
+
+impl<C, S> PoolRouter<C, S>
+where
+    C: Send + Sync + 'static,
+    S: Fn(&C) -> usize + Send + Sync + 'static,
+{
+    /// Creates a new router, `size` returns the number of pool members.
+    pub fn new(size: S) -> Self {
+        Self {
+            config: PhantomData,
+            size_fn: size,
+            dispatch: Dispatch::default(),
+            size: AtomicUsize::new(0),
+            max_size: AtomicUsize::new(0),
+            counter: AtomicUsize::new(0),
+            random: RandomState::new(),
+        }
+    }
+
+    /// Sets the dispatch strategy.
+    ///
+    /// [`Dispatch::RoundRobin`] by default.
+    pub fn dispatch(mut self, dispatch: Dispatch) -> Self {
+        self.dispatch = dispatch;
+        self
+    }
+}
+
+impl<C, S> Router<C> for PoolRouter<C, S>
+where
+    C: Send + Sync + 'static,
+    S: Fn(&C) -> usize + Send + Sync + 'static,
+{
+    type Key = usize;
+
+    fn update(&self, config: &C) {
+        let size = (self.size_fn)(config);
+        self.size.store(size, Ordering::Relaxed);
+        self.max_size.fetch_max(size, Ordering::Relaxed);
+    }
+
+    #[inline]
+    fn route(&self, envelope: &Envelope) -> Outcome<Self::Key> {
+        let size = self.size.load(Ordering::Relaxed);
+
+        msg!(match envelope {
+            messages::UpdateConfig => {
+                // Extra members can be alive after reducing the size.
+                if size < self.max_size.load(Ordering::Relaxed) {
+                    return Outcome::Broadcast;
+                }
+
+                // Start all members once the group is mounted or the size grows.
+                return Outcome::Multicast((0..size).collect());
+            }
+            messages::ValidateConfig | messages::Terminate | messages::Ping => {
+                return Outcome::Default;
+            }
+        });
+
+        if size == 0 {
+            return Outcome::Discard;
+        }
+
+        match self.dispatch {
+            Dispatch::RoundRobin => Outcome::Unicast(self.next() % size),
+            Dispatch::Random => {
+                let no = self.random.hash_one(self.next()) as usize;
+                Outcome::Unicast(no % size)
+            }
+            Dispatch::LeastLoaded => Outcome::LeastLoaded((0..size).collect()),
+        }
+    }
+}
+
+impl<C, S> PoolRouter<C, S> {
+    #[inline]
+    fn next(&self) -> usize {
+        self.counter.fetch_add(1, Ordering::Relaxed)
+    }
+}
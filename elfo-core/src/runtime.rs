@@ -33,6 +33,11 @@ impl RuntimeManager {
         tokio::runtime::Handle::current()
     }
 
+    /// Returns the index of the dedicated runtime used for the actor.
+    pub(crate) fn dedicated_index(&self, meta: &ActorMeta) -> Option<usize> {
+        self.dedicated.iter().position(|(f, _)| f(meta))
+    }
+
     #[cfg(feature = "unstable-stuck-detection")]
     pub(crate) fn stuck_detector(&self) -> StuckDetector {
         self.stuck_detector.clone()
@@ -0,0 +1,126 @@
+use std::sync::Arc;
+
+use crate::{actor::ActorMeta, envelope::Envelope, message::Message};
+
+// === ShutdownConfig ===
+
+pub mod config {
+    //! [Config]
+    //!
+    //! [Config]: ShutdownConfig
+
+    use std::time::Duration;
+
+    use serde::Deserialize;
+
+    /// Shutdown configuration.
+    ///
+    /// On shutdown, groups are stopped phase by phase in ascending order.
+    /// Every group in a phase gets the `Terminate` message, so mailboxes of
+    /// groups with [`TerminationPolicy::closing()`] reject new messages, but
+    /// still deliver queued ones. Actors that haven't finished before the
+    /// drain deadline are reported in [`ShutdownReport`], their queued messages
+    /// are discarded and mailboxes are closed forcibly.
+    ///
+    /// # Example
+    /// ```toml
+    /// [some_group]
+    /// system.shutdown.phase = 10
+    /// system.shutdown.drain_timeout = "5s"
+    /// ```
+    ///
+    /// [`TerminationPolicy::closing()`]: crate::TerminationPolicy::closing
+    /// [`ShutdownReport`]: crate::ShutdownReport
+    #[derive(Debug, Clone, PartialEq, Deserialize)]
+    #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+    #[serde(default)]
+    pub struct ShutdownConfig {
+        /// The phase in which the group is stopped.
+        ///
+        /// Overrides [`ActorGroup::stop_order()`].
+        ///
+        /// [`ActorGroup::stop_order()`]: crate::ActorGroup::stop_order
+        pub phase: Option<i8>,
+        /// How long actors can handle queued messages after the group
+        /// started terminating.
+        ///
+        /// `25s` by default.
+        #[serde(with = "humantime_serde")]
+        #[cfg_attr(feature = "schema", schemars(with = "String"))]
+        pub drain_timeout: Duration,
+    }
+
+    impl Default for ShutdownConfig {
+        fn default() -> Self {
+            Self {
+                phase: None,
+                drain_timeout: Duration::from_secs(25),
+            }
+        }
+    }
+}
+
+// === ShutdownReport ===
+
+/// Describes problems occurred during the shutdown of the node.
+///
+/// Returned by [`init::try_start_with_report()`].
+///
+/// [`init::try_start_with_report()`]: crate::init::try_start_with_report
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct ShutdownReport {
+    /// Actors that haven't finished before the drain deadline.
+    pub missed_deadline: Vec<Arc<ActorMeta>>,
+    /// Messages discarded from mailboxes of actors that missed the deadline.
+    pub undelivered: Vec<UndeliveredMessages>,
+}
+
+/// Messages of the same type left undelivered to the actor.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct UndeliveredMessages {
+    /// The actor the messages were sent to.
+    pub recipient: Arc<ActorMeta>,
+    /// The protocol of the message.
+    pub protocol: &'static str,
+    /// The name of the message.
+    pub name: &'static str,
+    /// How many messages were discarded.
+    pub count: usize,
+}
+
+impl ShutdownReport {
+    /// Returns `true` if all actors finished in time.
+    pub fn is_clean(&self) -> bool {
+        self.missed_deadline.is_empty()
+    }
+
+    /// Returns the total number of undelivered messages.
+    pub fn undelivered_count(&self) -> usize {
+        self.undelivered.iter().map(|u| u.count).sum()
+    }
+
+    pub(crate) fn add_missed(&mut self, meta: Arc<ActorMeta>, undelivered: Vec<Envelope>) {
+        for envelope in undelivered {
+            let message = envelope.message();
+            let (protocol, name) = (message.protocol(), message.name());
+
+            let existing = self.undelivered.iter_mut().find(|u| {
+                Arc::ptr_eq(&u.recipient, &meta) && u.protocol == protocol && u.name == name
+            });
+
+            match existing {
+                Some(existing) => existing.count += 1,
+                None => self.undelivered.push(UndeliveredMessages {
+                    recipient: meta.clone(),
+                    protocol,
+                    name,
+                    count: 1,
+                }),
+            }
+        }
+
+        self.missed_deadline.push(meta);
+    }
+}
@@ -0,0 +1,445 @@
+//! Backends keeping states stashed by [`Context::stash_state()`] across
+//! restarts of actors.
+//!
+//! [`Context::stash_state()`]: crate::Context::stash_state
+
+use std::{
+    fmt, fs,
+    io::{self, Write},
+    panic,
+    path::PathBuf,
+    sync::Arc,
+};
+
+use fxhash::FxHashMap;
+use parking_lot::Mutex;
+use tokio::{sync::Notify, task};
+use tracing::error;
+
+use crate::{actor::ActorMeta, message::AnyMessage, scope};
+
+/// A storage for stashed states of actors.
+///
+/// States are identified by [`ActorMeta`], i.e. by a pair of the group's name
+/// and the actor's key. Methods are called on the blocking thread pool, so
+/// implementations are allowed to use blocking I/O, unless
+/// [`StashBackend::is_blocking()`] returns `false`.
+pub trait StashBackend: Send + Sync + 'static {
+    /// Saves the state, replacing the previous one.
+    fn save(&self, meta: &ActorMeta, state: &AnyMessage) -> io::Result<()>;
+
+    /// Loads the last saved state, if any.
+    fn load(&self, meta: &ActorMeta) -> io::Result<Option<AnyMessage>>;
+
+    /// Removes the saved state, if any.
+    fn remove(&self, meta: &ActorMeta) -> io::Result<()>;
+
+    /// Whether methods can block. Non-blocking backends are called directly
+    /// by actors, which avoids delaying their start.
+    ///
+    /// `true` by default.
+    fn is_blocking(&self) -> bool {
+        true
+    }
+}
+
+impl fmt::Debug for dyn StashBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("StashBackend")
+    }
+}
+
+// === Stashers ===
+
+/// Creates stashers of actors in the group.
+///
+/// Stashers with unfinished work are kept, so the next incarnation of the
+/// actor reuses the stasher of the previous one and waits for that work
+/// before loading the state.
+pub(crate) struct Stashers {
+    backend: Arc<dyn StashBackend>,
+    busy: Arc<Mutex<FxHashMap<String, Stasher>>>,
+}
+
+impl Stashers {
+    pub(crate) fn new(backend: Arc<dyn StashBackend>) -> Self {
+        Self {
+            backend,
+            busy: Default::default(),
+        }
+    }
+
+    pub(crate) fn get(&self, meta: Arc<ActorMeta>) -> Stasher {
+        let busy = self.busy.lock();
+        if let Some(stasher) = busy.get(&meta.key) {
+            return stasher.clone();
+        }
+
+        Stasher {
+            backend: self.backend.clone(),
+            meta,
+            busy: self.busy.clone(),
+            queue: Default::default(),
+        }
+    }
+}
+
+// === Stasher ===
+
+/// Calls the backend of the actor on the blocking thread pool.
+///
+/// Operations are performed in the background one by one. If the backend is
+/// slower than the actor, intermediate operations are skipped and only the
+/// last one is performed, because it defines the final state anyway.
+#[derive(Clone)]
+pub(crate) struct Stasher {
+    backend: Arc<dyn StashBackend>,
+    meta: Arc<ActorMeta>,
+    busy: Arc<Mutex<FxHashMap<String, Stasher>>>,
+    queue: Arc<Queue>,
+}
+
+#[derive(Default)]
+struct Queue {
+    state: Mutex<QueueState>,
+    idle: Notify,
+}
+
+#[derive(Default)]
+struct QueueState {
+    pending: Option<Operation>,
+    is_running: bool,
+}
+
+enum Operation {
+    Save(AnyMessage),
+    Remove,
+}
+
+impl Stasher {
+    pub(crate) fn save(&self, state: AnyMessage) {
+        self.push(Operation::Save(state));
+    }
+
+    pub(crate) fn remove(&self) {
+        self.push(Operation::Remove);
+    }
+
+    /// Loads the state once all previous operations are performed.
+    pub(crate) async fn load(&self) -> Option<AnyMessage> {
+        self.flush().await;
+
+        let result = if self.backend.is_blocking() {
+            let this = self.clone();
+            blocking(move || this.backend.load(&this.meta)).await
+        } else {
+            self.backend.load(&self.meta)
+        };
+
+        result.unwrap_or_else(|err| {
+            error!(error = %err, "cannot recover the stashed state");
+            None
+        })
+    }
+
+    /// Waits until all previous operations are performed.
+    pub(crate) async fn flush(&self) {
+        loop {
+            let idle = self.queue.idle.notified();
+            tokio::pin!(idle);
+            idle.as_mut().enable();
+
+            if !self.queue.state.lock().is_running {
+                return;
+            }
+
+            idle.await;
+        }
+    }
+
+    fn push(&self, operation: Operation) {
+        if !self.backend.is_blocking() {
+            return self.perform(operation);
+        }
+
+        let mut state = self.queue.state.lock();
+        state.pending = Some(operation);
+
+        if !state.is_running {
+            state.is_running = true;
+            self.busy.lock().insert(self.meta.key.clone(), self.clone());
+
+            let this = self.clone();
+            let scope = scope::expose();
+            task::spawn_blocking(|| scope.sync_within(move || this.run()));
+        }
+    }
+
+    fn run(&self) {
+        loop {
+            let operation = {
+                let mut state = self.queue.state.lock();
+                match state.pending.take() {
+                    Some(operation) => operation,
+                    None => {
+                        state.is_running = false;
+                        self.busy.lock().remove(&self.meta.key);
+                        drop(state);
+                        self.queue.idle.notify_waiters();
+                        return;
+                    }
+                }
+            };
+
+            self.perform(operation);
+        }
+    }
+
+    fn perform(&self, operation: Operation) {
+        // Panics must not leave the queue running forever.
+        let result = panic::catch_unwind(panic::AssertUnwindSafe(|| match &operation {
+            Operation::Save(state) => self.backend.save(&self.meta, state),
+            Operation::Remove => self.backend.remove(&self.meta),
+        }));
+
+        let message = match operation {
+            Operation::Save(_) => "cannot stash the state",
+            Operation::Remove => "cannot discard the stashed state",
+        };
+
+        match result {
+            Ok(Ok(())) => {}
+            Ok(Err(err)) => error!(error = %err, message),
+            Err(_) => error!(error = "the backend panicked", message),
+        }
+    }
+}
+
+async fn blocking<R: Send + 'static>(f: impl FnOnce() -> R + Send + 'static) -> R {
+    let scope = scope::expose();
+    match task::spawn_blocking(|| scope.sync_within(f)).await {
+        Ok(result) => result,
+        Err(err) => panic::resume_unwind(err.into_panic()),
+    }
+}
+
+// === MemoryStash ===
+
+/// Keeps states in memory, so they survive restarts of actors,
+/// but not restarts of the whole process.
+///
+/// This backend is used by default.
+#[derive(Default)]
+pub struct MemoryStash {
+    states: Mutex<FxHashMap<(String, String), AnyMessage>>,
+}
+
+impl StashBackend for MemoryStash {
+    fn save(&self, meta: &ActorMeta, state: &AnyMessage) -> io::Result<()> {
+        let id = (meta.group.clone(), meta.key.clone());
+        self.states.lock().insert(id, state.clone());
+        Ok(())
+    }
+
+    fn load(&self, meta: &ActorMeta) -> io::Result<Option<AnyMessage>> {
+        let id = (meta.group.clone(), meta.key.clone());
+        Ok(self.states.lock().get(&id).cloned())
+    }
+
+    fn remove(&self, meta: &ActorMeta) -> io::Result<()> {
+        let id = (meta.group.clone(), meta.key.clone());
+        self.states.lock().remove(&id);
+        Ok(())
+    }
+
+    fn is_blocking(&self) -> bool {
+        false
+    }
+}
+
+// === FileStash ===
+
+/// Keeps states in JSON files inside the provided directory,
+/// so they survive even crashes of the whole process.
+///
+/// Each state is stored in a separate file, which is replaced atomically.
+/// Only messages, which can be deserialized on this node, can be recovered.
+pub struct FileStash {
+    dir: PathBuf,
+}
+
+impl FileStash {
+    /// Creates a new backend storing files in the provided directory.
+    /// The directory is created on the first save if it doesn't exist.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn path(&self, meta: &ActorMeta) -> PathBuf {
+        let mut name = String::new();
+        escape(&meta.group, &mut name);
+        name.push('.');
+        escape(&meta.key, &mut name);
+        name.push_str(".json");
+        self.dir.join(name)
+    }
+}
+
+impl StashBackend for FileStash {
+    fn save(&self, meta: &ActorMeta, state: &AnyMessage) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+
+        let path = self.path(meta);
+        let tmp_path = path.with_extension("json.tmp");
+
+        let mut file = fs::File::create(&tmp_path)?;
+        serde_json::to_writer(&mut file, state)?;
+        file.flush()?;
+        file.sync_all()?;
+
+        fs::rename(&tmp_path, &path)
+    }
+
+    fn load(&self, meta: &ActorMeta) -> io::Result<Option<AnyMessage>> {
+        let content = match fs::read(self.path(meta)) {
+            Ok(content) => content,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err),
+        };
+
+        Ok(Some(serde_json::from_slice(&content)?))
+    }
+
+    fn remove(&self, meta: &ActorMeta) -> io::Result<()> {
+        match fs::remove_file(self.path(meta)) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Escapes all characters except alphanumeric ones, `-` and `_`,
+/// to get a valid and unique file name.
+fn escape(s: &str, out: &mut String) {
+    for c in s.chars() {
+        if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+            out.push(c);
+        } else {
+            for byte in c.encode_utf8(&mut [0; 4]).bytes() {
+                out.push_str(&format!("%{byte:02X}"));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::{thread, time::Duration};
+
+    use crate::{message, scope::Scope, Addr};
+
+    #[message]
+    #[derive(PartialEq)]
+    struct State(u32);
+
+    fn meta(key: &str) -> ActorMeta {
+        ActorMeta {
+            group: "group".into(),
+            key: key.into(),
+        }
+    }
+
+    fn check(backend: &dyn StashBackend) {
+        let meta = meta("a/b");
+        assert!(backend.load(&meta).unwrap().is_none());
+
+        backend.save(&meta, &AnyMessage::new(State(1))).unwrap();
+        backend.save(&meta, &AnyMessage::new(State(2))).unwrap();
+        let state = backend.load(&meta).unwrap().unwrap();
+        assert_eq!(state.downcast_ref::<State>(), Some(&State(2)));
+        assert!(backend.load(&self::meta("a")).unwrap().is_none());
+
+        backend.remove(&meta).unwrap();
+        backend.remove(&meta).unwrap();
+        assert!(backend.load(&meta).unwrap().is_none());
+    }
+
+    #[test]
+    fn memory() {
+        check(&MemoryStash::default());
+    }
+
+    #[test]
+    fn file() {
+        let dir = std::env::temp_dir().join(format!("elfo-stash-{}", std::process::id()));
+        check(&FileStash::new(&dir));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    /// Counts calls and slows them down to make saves overlap.
+    #[derive(Default)]
+    struct SlowStash {
+        inner: MemoryStash,
+        saves: Mutex<u32>,
+    }
+
+    impl StashBackend for SlowStash {
+        fn save(&self, meta: &ActorMeta, state: &AnyMessage) -> io::Result<()> {
+            *self.saves.lock() += 1;
+            thread::sleep(Duration::from_millis(50));
+            self.inner.save(meta, state)
+        }
+
+        fn load(&self, meta: &ActorMeta) -> io::Result<Option<AnyMessage>> {
+            self.inner.load(meta)
+        }
+
+        fn remove(&self, meta: &ActorMeta) -> io::Result<()> {
+            self.inner.remove(meta)
+        }
+    }
+
+    #[tokio::test]
+    async fn stasher() {
+        let backend = Arc::new(SlowStash::default());
+        let meta = Arc::new(meta("a"));
+        let stashers = Stashers::new(backend.clone());
+        let stasher = stashers.get(meta.clone());
+
+        let scope = Scope::test(Addr::NULL, meta.clone());
+        scope
+            .within(async {
+                assert!(stasher.load().await.is_none());
+
+                // Intermediate states are skipped while the backend is busy.
+                for i in 1..=10 {
+                    stasher.save(AnyMessage::new(State(i)));
+                }
+                stasher.flush().await;
+                assert!(*backend.saves.lock() <= 2);
+
+                let state = stasher.load().await.unwrap();
+                assert_eq!(state.downcast_ref::<State>(), Some(&State(10)));
+
+                // The next incarnation waits for pending operations.
+                stasher.save(AnyMessage::new(State(11)));
+                let next = stashers.get(meta.clone());
+                let state = next.load().await.unwrap();
+                assert_eq!(state.downcast_ref::<State>(), Some(&State(11)));
+
+                stasher.save(AnyMessage::new(State(12)));
+                stasher.remove();
+                assert!(stashers.get(meta.clone()).load().await.is_none());
+            })
+            .await;
+    }
+
+    #[test]
+    fn escaping() {
+        let mut out = String::new();
+        escape("a-b_c.d/é", &mut out);
+        assert_eq!(out, "a-b_c%2Ed%2F%C3%A9");
+    }
+}
@@ -1,4 +1,13 @@
-use std::{future::Future, mem, ops::Deref, sync::Arc, time::Duration};
+use std::{
+    future::Future,
+    mem,
+    ops::Deref,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 
 use dashmap::DashMap;
 use futures::future::BoxFuture;
@@ -11,22 +20,24 @@ use elfo_utils::CachePadded;
 
 use self::{error_chain::ErrorChain, measure_poll::MeasurePoll};
 use crate::{
-    actor::{Actor, ActorMeta, ActorStartInfo},
+    actor::{Actor, ActorMeta, ActorStartCause, ActorStartInfo},
     actor_status::ActorStatus,
     addr::{Addr, NodeLaunchId, NodeNo},
     config::{AnyConfig, Config, SystemConfig},
     context::Context,
     envelope::Envelope,
     exec::{Exec, ExecResult},
-    group::TerminationPolicy,
+    group::{SupervisionStrategy, TerminationPolicy},
     message::Request,
     messages, msg,
     object::{GroupVisitor, Object, OwnedObject},
     panic,
-    restarting::{RestartBackoff, RestartPolicy},
+    restarting::{CircuitState, RestartBackoff, RestartPolicy},
     routers::{Outcome, Router},
     runtime::RuntimeManager,
     scope::{self, Scope, ScopeGroupShared},
+    shutdown::{config::ShutdownConfig, ShutdownReport},
+    stash::{StashBackend, Stashers},
     subscription::SubscriptionManager,
     tracing::TraceId,
     ResponseToken,
@@ -39,6 +50,11 @@ pub(crate) struct Supervisor<R: Router<C>, C, X> {
     meta: Arc<ActorMeta>,
     restart_policy: RestartPolicy,
     termination_policy: TerminationPolicy,
+    supervision_strategy: SupervisionStrategy,
+    stashers: Stashers,
+    /// Dependent groups, see `Local::escalate_to()`.
+    escalations: Vec<Addr>,
+    next_start_order: AtomicU64,
     span: Span,
     context: Context,
     objects: DashMap<R::Key, OwnedObject, FxBuildHasher>,
@@ -57,6 +73,15 @@ struct Control<C> {
     stop_spawning: bool,
 }
 
+/// The state kept across restarts of actors with the same key.
+#[derive(Default)]
+struct Lineage {
+    backoff: RestartBackoff,
+    /// See `SupervisionStrategy::RestForOne`.
+    start_order: Option<u64>,
+    restart_count: u64,
+}
+
 /// Returns `None` if cannot be spawned.
 macro_rules! get_or_spawn {
     ($this:ident, $key:expr, $start_info:expr) => {{
@@ -90,6 +115,9 @@ where
         router: R,
         restart_policy: RestartPolicy,
         termination_policy: TerminationPolicy,
+        supervision_strategy: SupervisionStrategy,
+        stash: Arc<dyn StashBackend>,
+        escalations: Vec<Addr>,
         rt_manager: RuntimeManager,
     ) -> Self {
         let control = Control {
@@ -109,6 +137,10 @@ where
             }),
             restart_policy,
             termination_policy,
+            supervision_strategy,
+            stashers: Stashers::new(stash),
+            escalations,
+            next_start_order: AtomicU64::new(0),
             objects: DashMap::default(),
             router,
             exec,
@@ -132,6 +164,20 @@ where
         .sync_within(|| self.span.in_scope(f));
     }
 
+    /// Emits counters of all actors' mailboxes in their scopes.
+    /// Called periodically by pingers.
+    fn emit_mailbox_counters(&self) {
+        for object in self.objects.iter() {
+            let Some(actor) = object.as_actor() else {
+                continue;
+            };
+
+            let (meta, group) = (actor.meta().clone(), self.scope_shared.clone());
+            let scope = Scope::new(scope::trace_id(), object.addr(), meta, group);
+            scope.sync_within(|| actor.emit_mailbox_counters());
+        }
+    }
+
     pub(crate) fn handle(self: &Arc<Self>, mut envelope: Envelope, visitor: &mut dyn GroupVisitor) {
         let outcome = msg!(match &envelope {
             messages::ValidateConfig { config } => match config.decode::<C>() {
@@ -213,6 +259,7 @@ where
                 self.router.route(&envelope).or(Outcome::Broadcast)
             }
             messages::Ping => {
+                self.emit_mailbox_counters();
                 self.router.route(&envelope).or(Outcome::Broadcast)
             }
             _ => {
@@ -243,6 +290,21 @@ where
                 let iter = list.into_iter().filter_map(|key| self.objects.get(&key));
                 self.visit_multiple(envelope, visitor, iter);
             }
+            Outcome::LeastLoaded(list) => {
+                for key in list.iter() {
+                    if !self.objects.contains_key(key) {
+                        get_or_spawn!(self, key.clone(), start_info.clone());
+                    }
+                }
+                let least_loaded = list
+                    .into_iter()
+                    .filter_map(|key| self.objects.get(&key))
+                    .min_by_key(|object| object.as_actor().map_or(usize::MAX, |a| a.mailbox_len()));
+                match least_loaded {
+                    Some(object) => visitor.visit_last(&object, envelope),
+                    None => visitor.empty(envelope),
+                }
+            }
             Outcome::Broadcast => self.visit_multiple(envelope, visitor, self.objects.iter()),
             Outcome::Discard => visitor.empty(envelope),
             Outcome::Default => unreachable!("must be altered earlier"),
@@ -271,11 +333,16 @@ where
         }
     }
 
+    fn with_actor<T>(&self, key: &R::Key, f: impl FnOnce(&Actor) -> T) -> T {
+        let object = self.objects.get(key).expect("where is the current actor?");
+        f(object.as_actor().expect("a supervisor stores only actors"))
+    }
+
     fn spawn(
         self: &Arc<Self>,
         key: R::Key,
         start_info: ActorStartInfo,
-        mut backoff: RestartBackoff,
+        mut lineage: Lineage,
     ) -> Option<OwnedObject> {
         let control = self.control.read();
         if control.stop_spawning {
@@ -318,8 +385,22 @@ where
             &system_config.mailbox,
             self.termination_policy.clone(),
             self.status_subscription.clone(),
+            self.stashers.get(meta.clone()),
         );
 
+        let start_order = *lineage
+            .start_order
+            .get_or_insert_with(|| self.next_start_order.fetch_add(1, Ordering::Relaxed));
+        let actor = actor.with_start_order(start_order);
+
+        // The actor is started after the cool-down, so the circuit is half-open.
+        let circuit_open = lineage.backoff.circuit_open();
+        let actor = if circuit_open.is_some() {
+            actor.with_circuit(CircuitState::HalfOpen)
+        } else {
+            actor
+        };
+
         drop(control);
 
         let sv = self.clone();
@@ -338,15 +419,35 @@ where
                 .on_start();
 
             // It must be called after `entry.insert()`.
+            let stasher = sv.with_actor(&key, Actor::stasher);
+            let recovered_state = stasher.load().await;
+            let start_info = start_info.with_recovered_state(recovered_state);
             let ctx = ctx.with_addr(addr).with_start_info(start_info);
-            let fut = async { sv.exec.exec(ctx).await.unify() };
-            let new_status = match panic::catch(fut).await {
-                Ok(Ok(())) => ActorStatus::TERMINATED,
-                Ok(Err(err)) => ActorStatus::FAILED.with_details(ErrorChain(&*err)),
-                Err(panic) => ActorStatus::FAILED.with_details(panic),
+            let fut = async {
+                let fut = async { sv.exec.exec(ctx).await.unify() };
+                match panic::catch(fut).await {
+                    Ok(Ok(())) => ActorStatus::TERMINATED,
+                    Ok(Err(err)) => ActorStatus::FAILED.with_details(ErrorChain(&*err)),
+                    Err(panic) => ActorStatus::FAILED.with_details(panic),
+                }
+            };
+            let new_status = if let Some(auto_reset) = circuit_open {
+                // Close the circuit once the actor is considered healthy.
+                tokio::pin!(fut);
+                tokio::select! {
+                    status = &mut fut => status,
+                    _ = tokio::time::sleep(auto_reset) => {
+                        lineage.backoff.close_circuit();
+                        sv.with_actor(&key, |actor| actor.set_circuit(CircuitState::Closed));
+                        fut.await
+                    }
+                }
+            } else {
+                fut.await
             };
 
-            let restart_after = {
+            let is_failed = new_status.kind().is_failed();
+            let (restart_after, forced_restart) = {
                 let object = sv.objects.get(&key).expect("where is the current actor?");
 
                 let actor = object.as_actor().expect("a supervisor stores only actors");
@@ -362,22 +463,52 @@ where
                     .unwrap_or(sv.restart_policy.clone());
                 let restart_policy = actor.restart_policy().unwrap_or(default_restart_policy);
 
-                let restarting_allowed = restart_policy.restarting_allowed(&new_status)
-                    && !sv.control.read().stop_spawning;
+                let stop_spawning = sv.control.read().stop_spawning;
+                let restarting_allowed =
+                    restart_policy.restarting_allowed(&new_status) && !stop_spawning;
 
                 actor.set_status(new_status);
 
-                restarting_allowed
-                    .then(|| {
-                        restart_policy
-                            .restart_params()
-                            .and_then(|p| backoff.next(&p))
-                    })
-                    .flatten()
+                // Actors stopped by the supervision strategy are restarted immediately,
+                // unless the group is terminating.
+                let forced_restart = actor.take_forced_restart().filter(|_| !stop_spawning);
+                let restart_after = if forced_restart.is_some() {
+                    Some(Duration::ZERO)
+                } else {
+                    restarting_allowed
+                        .then(|| {
+                            restart_policy
+                                .restart_params()
+                                .and_then(|p| lineage.backoff.next(&p))
+                        })
+                        .flatten()
+                };
+
+                (restart_after, forced_restart)
             };
 
+            let is_circuit_open =
+                forced_restart.is_none() && lineage.backoff.circuit_open().is_some();
+
+            if is_failed && forced_restart.is_none() {
+                if restart_after.is_some() && !is_circuit_open {
+                    sv.restart_siblings(&key);
+                } else {
+                    sv.escalate();
+                }
+            }
+
             let _ = if let Some(after) = restart_after {
-                if after == Duration::ZERO {
+                if is_circuit_open {
+                    // The actor stays in `objects` during the cool-down, so messages
+                    // routed to it are rejected by the closed mailbox.
+                    sv.with_actor(&key, |actor| actor.set_circuit(CircuitState::Open));
+                    debug!(?after, "actor will be restarted after the cool-down");
+
+                    increment_gauge!("elfo_open_circuits", 1.);
+                    tokio::time::sleep(after).await;
+                    decrement_gauge!("elfo_open_circuits", 1.);
+                } else if after == Duration::ZERO {
                     debug!("actor will be restarted immediately");
                 } else {
                     debug!(?after, "actor will be restarted");
@@ -390,14 +521,23 @@ where
                 // Restarted actors should have a new trace id.
                 scope::set_trace_id(TraceId::generate());
 
-                backoff.start();
-                if let Some(object) = sv.spawn(key.clone(), ActorStartInfo::on_restart(), backoff) {
+                lineage.backoff.start();
+                lineage.restart_count += 1;
+                let cause = forced_restart.unwrap_or(ActorStartCause::Restarted);
+                let start_info = ActorStartInfo::on_restart(cause, lineage.restart_count);
+                if let Some(object) = sv.spawn(key.clone(), start_info, lineage) {
                     sv.objects.insert(key.clone(), object)
                 } else {
                     sv.objects.remove(&key).map(|(_, v)| v)
                 }
             } else {
                 debug!("actor won't be restarted");
+
+                // Keep the stashed state if the group is terminating.
+                if !sv.control.read().stop_spawning {
+                    stasher.remove();
+                }
+
                 sv.objects.remove(&key).map(|(_, v)| v)
             }
             .expect("where is the current actor?");
@@ -423,13 +563,96 @@ where
         Some(object)
     }
 
+    /// Restarts other actors in the group according to the supervision strategy.
+    fn restart_siblings(&self, key: &R::Key) {
+        let min_start_order = match self.supervision_strategy {
+            SupervisionStrategy::OneForOne => return,
+            SupervisionStrategy::OneForAll => 0,
+            SupervisionStrategy::RestForOne => {
+                self.with_actor(key, |actor| actor.start_order()) + 1
+            }
+        };
+
+        let cause = ActorStartCause::SiblingFailed {
+            key: key.to_string(),
+        };
+
+        let mut count = 0;
+        for object in self.objects.iter() {
+            let actor = object.as_actor().expect("a supervisor stores only actors");
+            if object.key() != key
+                && actor.start_order() >= min_start_order
+                && actor.force_restart(cause.clone())
+            {
+                count += 1;
+            }
+        }
+
+        if count > 0 {
+            info!(strategy = ?self.supervision_strategy, count, "restarting other actors");
+        }
+    }
+
+    /// Restarts actors in dependent groups, see `Local::escalate_to()`.
+    fn escalate(&self) {
+        // Failures are expected while the group is terminating.
+        if self.control.read().stop_spawning {
+            return;
+        }
+
+        let cause = ActorStartCause::Escalated {
+            group: self.meta.group.clone(),
+        };
+
+        for addr in &self.escalations {
+            // The dependent group can be not mounted yet.
+            let object = ward!(self.context.book().get_owned(*addr), continue);
+            let group = object.as_group().expect("only groups can be dependent");
+
+            warn!(group = %addr, "escalating the failure");
+            group.restart_all(cause.clone());
+        }
+    }
+
+    pub(crate) fn restart_all(&self, cause: ActorStartCause) {
+        // Stopped actors wouldn't be spawned again.
+        if self.control.read().stop_spawning {
+            return;
+        }
+
+        let count = self
+            .objects
+            .iter()
+            .filter(|object| {
+                let actor = object.as_actor().expect("a supervisor stores only actors");
+                actor.force_restart(cause.clone())
+            })
+            .count();
+
+        self.in_scope(|| info!(?cause, count, "restarting all actors"));
+    }
+
+    pub(crate) fn shutdown_config(&self) -> ShutdownConfig {
+        self.control.read().system_config.shutdown.clone()
+    }
+
+    pub(crate) fn on_drain_timeout(&self, report: &mut ShutdownReport) {
+        for object in self.objects.iter() {
+            let actor = object.as_actor().expect("a supervisor stores only actors");
+
+            if !actor.status_kind().is_finished() {
+                report.add_missed(actor.meta().clone(), actor.discard_queued());
+            }
+        }
+    }
+
     fn spawn_on_group_mounted(self: &Arc<Self>, outcome: Outcome<R::Key>) {
         let start_info = ActorStartInfo::on_group_mounted();
         match outcome {
             Outcome::Unicast(key) => {
                 get_or_spawn!(self, key, start_info);
             }
-            Outcome::Multicast(keys) => {
+            Outcome::Multicast(keys) | Outcome::LeastLoaded(keys) => {
                 for key in keys {
                     get_or_spawn!(self, key, start_info.clone());
                 }
@@ -463,6 +686,8 @@ where
                     .expect("a supervisor stores only actors");
 
                 actor.set_mailbox_capacity_config(system.mailbox.capacity);
+                actor.set_mailbox_overflow_policy(system.mailbox.on_overflow);
+                actor.set_mailbox_rate_limit(&system.mailbox.rate_limit);
             }
         }
 
@@ -19,6 +19,7 @@ use serde::{
 /// system.teleemtry.per_actor_key = true
 /// ```
 #[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(default)]
 pub struct TelemetryConfig {
     /// Whether to enable per-actor-group telemetry.
@@ -37,6 +38,7 @@ pub struct TelemetryConfig {
     /// per_actor_key = true # emit metrics keywise
     /// per_actor_key = [".*:(.*?)", "${1}"] # group keys
     /// ```
+    #[cfg_attr(feature = "schema", schemars(with = "BoolOrPairOfStrings"))]
     pub per_actor_key: PerActorKey,
 }
 
@@ -84,13 +86,6 @@ impl<'de> Deserialize<'de> for PerActorKey {
     where
         D: Deserializer<'de>,
     {
-        #[derive(Deserialize)]
-        #[serde(untagged)]
-        enum BoolOrPairOfStrings {
-            Bool(bool),
-            Pair(String, String),
-        }
-
         Ok(match BoolOrPairOfStrings::deserialize(deserializer)? {
             BoolOrPairOfStrings::Bool(flag) => PerActorKey::Bool(flag),
             BoolOrPairOfStrings::Pair(pattern, template) => {
@@ -101,6 +96,14 @@ impl<'de> Deserialize<'de> for PerActorKey {
     }
 }
 
+#[derive(Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(untagged)]
+enum BoolOrPairOfStrings {
+    Bool(bool),
+    Pair(String, String),
+}
+
 impl Default for TelemetryConfig {
     fn default() -> Self {
         Self {
@@ -1,6 +1,6 @@
 use std::{cell::RefCell, sync::Arc};
 
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use sealed::sealed;
 use tokio::runtime::Handle;
 
@@ -9,14 +9,26 @@ use crate::stuck_detection::StuckDetector;
 use crate::{
     addr::{Addr, GroupNo, NodeLaunchId, NodeNo},
     address_book::{AddressBook, VacantEntry},
+    config::{AnyConfig, ConfigSpec},
     context::Context,
     demux::Demux,
     envelope::Envelope,
     group::Blueprint,
+    init,
+    message::Message,
+    messages::{GroupMounted, GroupUnmounted},
     object::Object,
     runtime::RuntimeManager,
+    shutdown::ShutdownReport,
 };
 
+pub use self::{export::Format, filter::MsgFilter};
+
+mod export;
+mod filter;
+#[cfg(feature = "schema")]
+mod schema;
+
 pub(crate) const SYSTEM_INIT_GROUP_NO: u8 = 1;
 
 /// The topology defines local and remote groups, and routes between them.
@@ -34,6 +46,7 @@ struct Inner {
     #[cfg(feature = "network")]
     remotes: Vec<RemoteActorGroup>,
     connections: Vec<Connection>,
+    subscribers: Vec<Addr>,
     rt_manager: RuntimeManager,
 }
 
@@ -45,6 +58,7 @@ impl Default for Inner {
             #[cfg(feature = "network")]
             remotes: Vec::new(),
             connections: Vec::new(),
+            subscribers: Vec::new(),
             rt_manager: RuntimeManager::default(),
         }
     }
@@ -58,7 +72,20 @@ pub struct LocalActorGroup {
     pub name: String,
     pub is_entrypoint: bool,
     pub is_mounted: bool,
+    /// Whether the group is mounted by [`Topology::mount_dynamic()`].
+    pub is_dynamic: bool,
     pub(crate) stop_order: i8,
+    pub(crate) config: ConfigSpec,
+}
+
+impl LocalActorGroup {
+    /// Checks that the config can be decoded by the group, including
+    /// the `system` section. Unlike `ValidateConfig`, no actors are involved,
+    /// so it doesn't check invariants checked by actors themselves.
+    #[stability::unstable]
+    pub fn check_config(&self, config: &AnyConfig) -> Result<(), String> {
+        self.config.check(config)
+    }
 }
 
 /// Represents a connection between two groups.
@@ -68,6 +95,8 @@ pub struct LocalActorGroup {
 pub struct Connection {
     pub from: Addr,
     pub to: ConnectionTo,
+    /// The name of the route's filter if provided.
+    pub label: Option<String>,
 }
 
 // TODO: #[stability::unstable]
@@ -163,7 +192,9 @@ impl Topology {
             name: name.clone(),
             is_mounted: false,
             is_entrypoint: false,
+            is_dynamic: false,
             stop_order: 0,
+            config: ConfigSpec::default(),
         });
 
         Local {
@@ -171,6 +202,7 @@ impl Topology {
             topology: self,
             entry,
             demux: RefCell::new(Demux::default()),
+            escalations: RefCell::new(Vec::new()),
         }
     }
 
@@ -185,6 +217,157 @@ impl Topology {
         let inner = self.inner.read();
         inner.connections.clone().into_iter()
     }
+
+    /// Declares and mounts a new local group while the system is running.
+    ///
+    /// Subscribed groups (e.g. configurers and pingers) receive
+    /// the [`GroupMounted`] message, so the group gets its config and
+    /// starts like any group mounted before the start.
+    ///
+    /// Group numbers aren't reused, so the total number of groups mounted
+    /// during the node's lifetime is limited.
+    ///
+    /// Use [`Topology::mount_dynamic_with()`] to define routes from the group.
+    ///
+    /// # Panics
+    /// * If the name is already taken for another local group.
+    /// * If there are too many local groups.
+    /// * If called outside the actor system.
+    ///
+    /// [`GroupMounted`]: crate::messages::GroupMounted
+    #[track_caller]
+    pub fn mount_dynamic(&self, name: impl Into<String>, blueprint: Blueprint) -> Addr {
+        self.mount_dynamic_with(name, blueprint, |_| {})
+    }
+
+    /// The same as [`Topology::mount_dynamic()`], but calls the provided
+    /// function before mounting, which can define routes and escalations from
+    /// the new group. Already mounted groups are available as destinations
+    /// via [`Topology::locals()`].
+    ///
+    /// Routes of already running groups are fixed once they are mounted,
+    /// so there is no way to route messages *to* the new group. Send them
+    /// directly to the returned address instead.
+    ///
+    /// # Example
+    /// ```
+    /// # use elfo_core as elfo;
+    /// # #[elfo::message] struct SomeEvent;
+    /// # fn plugin() -> elfo::Blueprint { unimplemented!() }
+    /// use elfo::{msg, Envelope, Topology};
+    ///
+    /// fn mount_plugin(topology: &Topology) {
+    ///     let sink = topology.locals().find(|g| g.name == "sink").unwrap();
+    ///
+    ///     topology.mount_dynamic_with("plugin", plugin(), |plugin| {
+    ///         plugin.route_to(&sink, |envelope: &Envelope| {
+    ///             msg!(match envelope {
+    ///                 SomeEvent => true,
+    ///                 _ => false,
+    ///             })
+    ///         });
+    ///     });
+    /// }
+    /// ```
+    ///
+    /// # Panics
+    /// The same as [`Topology::mount_dynamic()`].
+    #[track_caller]
+    pub fn mount_dynamic_with(
+        &self,
+        name: impl Into<String>,
+        blueprint: Blueprint,
+        f: impl FnOnce(&Local<'_>),
+    ) -> Addr {
+        let local = self.local(name);
+        let addr = local.addr();
+        let name = local.name.clone();
+
+        f(&local);
+        local.with_group_mut(|group| group.is_dynamic = true);
+        local.mount(blueprint);
+
+        self.notify(GroupMounted { name });
+        addr
+    }
+
+    /// Gracefully terminates a group mounted by [`Topology::mount_dynamic()`]
+    /// and removes it from the topology, including its connections.
+    ///
+    /// Actors are stopped in the same way as on shutdown, respecting
+    /// the group's `system.shutdown.drain_timeout`. Subscribed groups receive
+    /// the [`GroupUnmounted`] message afterwards.
+    ///
+    /// # Panics
+    /// * If there is no such group.
+    /// * If the group isn't mounted by [`Topology::mount_dynamic()`].
+    /// * If called outside the actor system.
+    ///
+    /// [`GroupUnmounted`]: crate::messages::GroupUnmounted
+    pub async fn unmount(&self, name: &str) -> ShutdownReport {
+        let group = self
+            .inner
+            .read()
+            .locals
+            .iter()
+            .find(|group| group.name == name)
+            .cloned()
+            .unwrap_or_else(|| panic!("there is no local group `{name}`"));
+
+        assert!(
+            group.is_dynamic,
+            "local group `{name}` isn't mounted dynamically"
+        );
+
+        let addr = group.addr;
+        let drain_timeout = self
+            .book
+            .get_owned(addr)
+            .and_then(|object| object.as_group().map(|group| group.shutdown_config()))
+            .unwrap_or_default()
+            .drain_timeout;
+
+        let ctx = Context::new(self.book.clone(), Demux::default());
+        let report = Mutex::new(ShutdownReport::default());
+        init::terminate_group(&ctx, addr, name, drain_timeout, &report).await;
+
+        self.book.remove(addr);
+
+        let mut inner = self.inner.write();
+        inner.locals.retain(|group| group.addr != addr);
+        inner.subscribers.retain(|subscriber| *subscriber != addr);
+        inner.connections.retain(|connection| {
+            connection.from != addr
+                && !matches!(connection.to, ConnectionTo::Local(to) if to == addr)
+        });
+        drop(inner);
+
+        self.notify(GroupUnmounted { name: group.name });
+
+        report.into_inner()
+    }
+
+    /// Subscribes the group to [`GroupMounted`] and [`GroupUnmounted`]
+    /// messages, which are sent once the topology is changed at runtime.
+    ///
+    /// [`GroupMounted`]: crate::messages::GroupMounted
+    /// [`GroupUnmounted`]: crate::messages::GroupUnmounted
+    #[stability::unstable]
+    pub fn subscribe_to_changes(&self, group: Addr) {
+        let mut inner = self.inner.write();
+        if !inner.subscribers.contains(&group) {
+            inner.subscribers.push(group);
+        }
+    }
+
+    fn notify<M: Message + Clone>(&self, message: M) {
+        let subscribers = self.inner.read().subscribers.clone();
+        let ctx = Context::new(self.book.clone(), Demux::default());
+
+        for subscriber in subscribers {
+            let _ = ctx.unbounded_send_to(subscriber, message.clone());
+        }
+    }
 }
 
 /// Represents a local group's settings.
@@ -194,6 +377,7 @@ pub struct Local<'t> {
     name: String,
     entry: VacantEntry<'t>,
     demux: RefCell<Demux>,
+    escalations: RefCell<Vec<Addr>>,
 }
 
 impl Local<'_> {
@@ -215,27 +399,52 @@ impl Local<'_> {
 
     /// Defines a route to the given destination (local or remote group).
     ///
+    /// The filter is either a closure or a declarative [`MsgFilter`].
+    /// Unlike closures, `MsgFilter` is printable, so the route is labelled
+    /// by the filter in [`Topology::export()`].
+    ///
     /// # Examples
     /// Local to local:
     /// ```
     /// # use elfo_core as elfo;
     /// # #[elfo::message] struct SomeEvent;
-    /// use elfo::{Topology, msg};
+    /// use elfo::{messages::Ping, msg, topology::MsgFilter, Envelope, Topology};
     ///
     /// let topology = Topology::empty();
     /// let foo = topology.local("foo");
     /// let bar = topology.local("bar");
+    /// let baz = topology.local("baz");
     ///
-    /// foo.route_to(&bar, |envelope| {
+    /// foo.route_to(&bar, |envelope: &Envelope| {
     ///     msg!(match envelope {
     ///         SomeEvent => true,
     ///         _ => false,
     ///     })
     /// });
+    ///
+    /// foo.route_to(&baz, MsgFilter::protocol("elfo-core").except::<Ping>());
     /// ```
     ///
     /// Local to remote (requires the `network` feature): TODO
     pub fn route_to<F>(&self, dest: &impl Destination<F>, filter: F) {
+        self.do_route_to(dest, filter, None);
+    }
+
+    /// The same as [`Local::route_to()`], but also names the route's filter.
+    ///
+    /// The name is used to label the route in [`Topology::export()`].
+    pub fn route_to_named<F>(
+        &self,
+        dest: &impl Destination<F>,
+        name: impl Into<String>,
+        filter: F,
+    ) {
+        self.do_route_to(dest, filter, Some(name.into()));
+    }
+
+    fn do_route_to<F>(&self, dest: &impl Destination<F>, filter: F, label: Option<String>) {
+        let label = label.or_else(|| dest.label(&filter));
+
         dest.extend_demux(
             self.entry.addr().group_no().expect("invalid addr"),
             &mut self.demux.borrow_mut(),
@@ -246,6 +455,7 @@ impl Local<'_> {
         inner.connections.push(Connection {
             from: self.entry.addr(),
             to: dest.connection_endpoint(),
+            label,
         });
     }
 
@@ -257,10 +467,36 @@ impl Local<'_> {
             .append(move |_, addrs| addrs.push(addr));
     }
 
+    /// Declares that the given local group depends on this group.
+    ///
+    /// If an actor in this group fails and isn't going to be restarted
+    /// anymore (the restart policy doesn't allow it or retries are exhausted),
+    /// all actors in the dependent group are restarted with
+    /// [`ActorStartCause::Escalated`].
+    ///
+    /// # Example
+    /// ```
+    /// # use elfo_core as elfo;
+    /// use elfo::Topology;
+    ///
+    /// let topology = Topology::empty();
+    /// let connections = topology.local("connections");
+    /// let sessions = topology.local("sessions");
+    ///
+    /// // Sessions are restarted if connections cannot be restored.
+    /// connections.escalate_to(&sessions);
+    /// ```
+    ///
+    /// [`ActorStartCause::Escalated`]: crate::ActorStartCause::Escalated
+    pub fn escalate_to(&self, dest: &Local<'_>) {
+        self.escalations.borrow_mut().push(dest.entry.addr());
+    }
+
     /// Mounts a blueprint to this group.
     pub fn mount(self, blueprint: Blueprint) {
         self.with_group_mut(|group| {
             group.stop_order = blueprint.stop_order;
+            group.config = blueprint.config;
             group.is_mounted = true;
         });
 
@@ -274,6 +510,7 @@ impl Local<'_> {
             self.topology.launch_id,
             self.name,
             rt_manager,
+            self.escalations.into_inner(),
         );
         self.entry.insert(object);
     }
@@ -296,6 +533,46 @@ pub trait Destination<F> {
 
     #[doc(hidden)]
     fn connection_endpoint(&self) -> ConnectionTo;
+
+    #[doc(hidden)]
+    fn label(&self, _filter: &F) -> Option<String> {
+        None
+    }
+}
+
+#[sealed]
+impl<F> Destination<F> for LocalActorGroup
+where
+    F: Fn(&Envelope) -> bool + Send + Sync + 'static,
+{
+    fn extend_demux(&self, _: GroupNo, demux: &mut Demux, filter: F) {
+        let addr = self.addr;
+        demux.append(move |envelope, addrs| {
+            if filter(envelope) {
+                addrs.push(addr);
+            }
+        });
+    }
+
+    fn connection_endpoint(&self) -> ConnectionTo {
+        ConnectionTo::Local(self.addr)
+    }
+}
+
+#[sealed]
+impl Destination<MsgFilter> for LocalActorGroup {
+    fn extend_demux(&self, source_group_no: GroupNo, demux: &mut Demux, filter: MsgFilter) {
+        let filter = move |envelope: &Envelope| filter.matches(&*envelope.message());
+        Destination::extend_demux(self, source_group_no, demux, filter);
+    }
+
+    fn connection_endpoint(&self) -> ConnectionTo {
+        ConnectionTo::Local(self.addr)
+    }
+
+    fn label(&self, filter: &MsgFilter) -> Option<String> {
+        Some(filter.to_string())
+    }
 }
 
 #[sealed]
@@ -317,6 +594,22 @@ where
     }
 }
 
+#[sealed]
+impl Destination<MsgFilter> for Local<'_> {
+    fn extend_demux(&self, source_group_no: GroupNo, demux: &mut Demux, filter: MsgFilter) {
+        let filter = move |envelope: &Envelope| filter.matches(&*envelope.message());
+        Destination::extend_demux(self, source_group_no, demux, filter);
+    }
+
+    fn connection_endpoint(&self) -> ConnectionTo {
+        ConnectionTo::Local(self.entry.addr())
+    }
+
+    fn label(&self, filter: &MsgFilter) -> Option<String> {
+        Some(filter.to_string())
+    }
+}
+
 cfg_network!({
     use arc_swap::ArcSwap;
     use fxhash::FxHashMap;
@@ -482,6 +775,29 @@ cfg_network!({
         }
     }
 
+    /// Broadcasts matched messages to all nodes.
+    #[sealed]
+    impl Destination<MsgFilter> for Remote<'_> {
+        fn extend_demux(&self, local_group_no: GroupNo, demux: &mut Demux, filter: MsgFilter) {
+            let filter = move |envelope: &Envelope, _: &NodeDiscovery| {
+                if filter.matches(&*envelope.message()) {
+                    Outcome::Broadcast
+                } else {
+                    Outcome::Discard
+                }
+            };
+            Destination::extend_demux(self, local_group_no, demux, filter);
+        }
+
+        fn connection_endpoint(&self) -> ConnectionTo {
+            ConnectionTo::Remote(self.name.clone())
+        }
+
+        fn label(&self, filter: &MsgFilter) -> Option<String> {
+            Some(filter.to_string())
+        }
+    }
+
     #[derive(Debug)]
     #[non_exhaustive]
     pub enum Outcome {
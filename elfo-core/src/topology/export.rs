@@ -0,0 +1,287 @@
+use std::fmt::{self, Write};
+
+use serde::{Deserialize, Serialize};
+
+use super::{ConnectionTo, Topology};
+use crate::{actor::ActorMeta, addr::NodeNo};
+
+/// A format used by [`Topology::export()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum Format {
+    /// The [DOT](https://graphviz.org/doc/info/lang.html) language,
+    /// which can be rendered by Graphviz.
+    Dot,
+    /// JSON containing `groups` and `routes` arrays.
+    Json,
+}
+
+#[derive(Serialize)]
+struct Graph {
+    node_no: NodeNo,
+    groups: Vec<Group>,
+    routes: Vec<Route>,
+}
+
+#[derive(Serialize)]
+struct Group {
+    name: String,
+    is_remote: bool,
+    is_entrypoint: bool,
+    is_mounted: bool,
+    is_dynamic: bool,
+    /// The index of the dedicated runtime, see `Topology::add_dedicated_rt()`.
+    runtime: Option<usize>,
+}
+
+#[derive(Serialize)]
+struct Route {
+    from: String,
+    to: String,
+    to_remote: bool,
+    label: Option<String>,
+}
+
+impl Topology {
+    /// Exports groups and routes between them in the specified format.
+    ///
+    /// The output includes entrypoints, groups mounted at runtime, dedicated
+    /// runtimes and labels of routes with named filters. Dedicated runtimes
+    /// are matched as if groups contained only one actor with the `_` key.
+    ///
+    /// # Example
+    /// ```
+    /// # use elfo_core as elfo;
+    /// use elfo::{topology::Format, Envelope, Topology};
+    ///
+    /// let topology = Topology::empty();
+    /// let producers = topology.local("producers");
+    /// let consumers = topology.local("consumers");
+    /// producers.route_to_named(&consumers, "all", |_: &Envelope| true);
+    ///
+    /// let dot = topology.export(Format::Dot);
+    /// assert!(dot.contains(r#""producers" -> "consumers" [label="all"];"#));
+    /// ```
+    pub fn export(&self, format: Format) -> String {
+        let graph = self.graph();
+
+        match format {
+            Format::Dot => {
+                let mut out = String::new();
+                write_dot(&graph, &mut out).expect("cannot write to a string");
+                out
+            }
+            Format::Json => serde_json::to_string_pretty(&graph).expect("cannot serialize"),
+        }
+    }
+
+    fn graph(&self) -> Graph {
+        let inner = self.inner.read();
+
+        #[cfg_attr(not(feature = "network"), allow(unused_mut))]
+        let mut groups = inner
+            .locals
+            .iter()
+            .map(|local| {
+                let meta = ActorMeta {
+                    group: local.name.clone(),
+                    key: "_".into(),
+                };
+
+                Group {
+                    name: local.name.clone(),
+                    is_remote: false,
+                    is_entrypoint: local.is_entrypoint,
+                    is_mounted: local.is_mounted,
+                    is_dynamic: local.is_dynamic,
+                    runtime: inner.rt_manager.dedicated_index(&meta),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        #[cfg(feature = "network")]
+        groups.extend(inner.remotes.iter().map(|remote| Group {
+            name: remote.name.clone(),
+            is_remote: true,
+            is_entrypoint: false,
+            is_mounted: true,
+            is_dynamic: false,
+            runtime: None,
+        }));
+
+        let local_name = |addr| {
+            inner
+                .locals
+                .iter()
+                .find(|local| local.addr == addr)
+                .map(|local| local.name.clone())
+        };
+
+        let routes = inner
+            .connections
+            .iter()
+            .filter_map(|connection| {
+                let (to, to_remote) = match &connection.to {
+                    ConnectionTo::Local(addr) => (local_name(*addr)?, false),
+                    #[cfg(feature = "network")]
+                    ConnectionTo::Remote(name) => (name.clone(), true),
+                };
+
+                Some(Route {
+                    from: local_name(connection.from)?,
+                    to,
+                    to_remote,
+                    label: connection.label.clone(),
+                })
+            })
+            .collect();
+
+        Graph {
+            node_no: self.node_no,
+            groups,
+            routes,
+        }
+    }
+}
+
+fn write_dot(graph: &Graph, out: &mut impl Write) -> fmt::Result {
+    let id = |name: &str, is_remote: bool| {
+        if is_remote {
+            quote(&format!("remote:{name}"))
+        } else {
+            quote(name)
+        }
+    };
+
+    writeln!(out, "digraph topology {{")?;
+    let label = quote(&format!("node {}", graph.node_no));
+    writeln!(out, "    label={label};")?;
+    writeln!(out, "    node [shape=box];")?;
+
+    for group in &graph.groups {
+        let mut label = group.name.clone();
+        if let Some(runtime) = group.runtime {
+            write!(label, "\nruntime #{runtime}")?;
+        }
+
+        let mut attrs = vec![format!("label={}", quote(&label))];
+
+        if group.is_remote {
+            attrs.push("shape=ellipse".into());
+        }
+        if group.is_entrypoint {
+            attrs.push("peripheries=2".into());
+        }
+        if !group.is_mounted {
+            attrs.push("style=dotted".into());
+        } else if group.is_dynamic {
+            attrs.push("style=dashed".into());
+        }
+
+        let id = id(&group.name, group.is_remote);
+        writeln!(out, "    {id} [{}];", attrs.join(", "))?;
+    }
+
+    for route in &graph.routes {
+        let from = id(&route.from, false);
+        let to = id(&route.to, route.to_remote);
+
+        match &route.label {
+            Some(label) => writeln!(out, "    {from} -> {to} [label={}];", quote(label))?,
+            None => writeln!(out, "    {from} -> {to};")?,
+        }
+    }
+
+    writeln!(out, "}}")
+}
+
+fn quote(s: &str) -> String {
+    let escaped = s
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n");
+    format!("\"{escaped}\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{topology::MsgFilter, ActorGroup, Blueprint, Envelope};
+
+    fn blueprint() -> Blueprint {
+        ActorGroup::new().exec(|_| async {})
+    }
+
+    fn topology() -> Topology {
+        let topology = Topology::empty();
+
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        topology.add_dedicated_rt(|meta| meta.group == "workers", rt.handle().clone());
+
+        let configurers = topology.local("system.configurers").entrypoint();
+        let producers = topology.local("producers");
+        let workers = topology.local("workers");
+        let unused = topology.local("unused \"group\"");
+
+        producers.route_to_named(&workers, "tasks", |_: &Envelope| true);
+        workers.route_to(&unused, |_: &Envelope| false);
+        workers.route_to(&producers, MsgFilter::protocol("x").except_protocol("y"));
+
+        configurers.mount(blueprint());
+        producers.mount(blueprint());
+        workers.mount(blueprint());
+        drop(unused);
+
+        topology
+    }
+
+    #[test]
+    fn dot() {
+        let topology = topology();
+        let dot = topology.export(Format::Dot);
+        let node_no = topology.node_no();
+
+        assert_eq!(
+            dot,
+            format!(
+                r#"digraph topology {{
+    label="node {node_no}";
+    node [shape=box];
+    "system.configurers" [label="system.configurers", peripheries=2];
+    "producers" [label="producers"];
+    "workers" [label="workers\nruntime #0"];
+    "unused \"group\"" [label="unused \"group\"", style=dotted];
+    "producers" -> "workers" [label="tasks"];
+    "workers" -> "unused \"group\"";
+    "workers" -> "producers" [label="x/* except y/*"];
+}}
+"#
+            )
+        );
+    }
+
+    #[test]
+    fn json() {
+        let topology = topology();
+        let json = topology.export(Format::Json);
+        let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(json["groups"].as_array().unwrap().len(), 4);
+        assert_eq!(json["groups"][0]["name"], "system.configurers");
+        assert_eq!(json["groups"][0]["is_entrypoint"], true);
+        assert_eq!(json["groups"][2]["runtime"], 0);
+        assert_eq!(json["groups"][3]["is_mounted"], false);
+
+        assert_eq!(
+            json["routes"],
+            serde_json::json!([
+                { "from": "producers", "to": "workers", "to_remote": false, "label": "tasks" },
+                { "from": "workers", "to": "unused \"group\"", "to_remote": false, "label": null },
+                { "from": "workers", "to": "producers", "to_remote": false, "label": "x/* except y/*" },
+            ])
+        );
+    }
+}
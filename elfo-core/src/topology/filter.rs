@@ -0,0 +1,279 @@
+use std::fmt;
+
+use crate::message::{Message, MessageTypeId, MessageVTable};
+
+/// A declarative filter of messages, which can be used instead of closures
+/// in [`Local::route_to()`] for local and remote groups. Matched messages
+/// are broadcast to all nodes of remote groups.
+///
+/// Unlike closures, filters are printable, so routes are labelled by them in
+/// [`Topology::export()`]. Messages are matched by their protocols and types.
+///
+/// A filter is a union of terms, each of which matches messages selected by
+/// [`MsgFilter::all()`], [`MsgFilter::protocol()`] and so on, except excluded
+/// ones. Exclusions apply to all terms added before them, so
+/// `a.except::<X>().or(b)` doesn't exclude `X` from `b`.
+///
+/// # Example
+/// ```
+/// # use elfo_core as elfo;
+/// # #[elfo::message] struct SomeEvent;
+/// use elfo::{messages::Ping, topology::MsgFilter, Topology};
+///
+/// let topology = Topology::empty();
+/// let foo = topology.local("foo");
+/// let bar = topology.local("bar");
+///
+/// let filter = MsgFilter::protocol("elfo-core").except::<Ping>();
+/// assert_eq!(filter.to_string(), "elfo-core/* except elfo-core/Ping");
+///
+/// foo.route_to(&bar, filter.or(MsgFilter::message::<SomeEvent>()));
+/// ```
+///
+/// [`Local::route_to()`]: crate::topology::Local::route_to
+/// [`Topology::export()`]: crate::Topology::export
+#[derive(Clone)]
+pub struct MsgFilter {
+    terms: Vec<Term>,
+}
+
+#[derive(Clone)]
+struct Term {
+    include: Selector,
+    exclude: Vec<Selector>,
+}
+
+impl Term {
+    fn matches(&self, message: &impl Message) -> bool {
+        self.include.matches(message) && !self.exclude.iter().any(|s| s.matches(message))
+    }
+}
+
+impl fmt::Display for Term {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.include)?;
+
+        for (i, selector) in self.exclude.iter().enumerate() {
+            f.write_str(if i == 0 { " except " } else { ", " })?;
+            write!(f, "{selector}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+enum Selector {
+    All,
+    Protocol(String),
+    Message(&'static MessageVTable),
+}
+
+impl Selector {
+    fn matches(&self, message: &impl Message) -> bool {
+        match self {
+            Self::All => true,
+            Self::Protocol(protocol) => message.protocol() == protocol,
+            Self::Message(vtable) => {
+                MessageTypeId::new(message._vtable()) == MessageTypeId::new(vtable)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Selector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::All => f.write_str("*"),
+            Self::Protocol(protocol) => write!(f, "{protocol}/*"),
+            Self::Message(vtable) => write!(f, "{}/{}", vtable.protocol(), vtable.name()),
+        }
+    }
+}
+
+impl MsgFilter {
+    /// Matches all messages.
+    pub fn all() -> Self {
+        Self::new(Selector::All)
+    }
+
+    /// Matches all messages of the protocol.
+    pub fn protocol(protocol: impl Into<String>) -> Self {
+        Self::new(Selector::Protocol(protocol.into()))
+    }
+
+    /// Matches the message.
+    ///
+    /// # Panics
+    /// If the message isn't registered, e.g. `AnyMessage` is provided.
+    #[track_caller]
+    pub fn message<M: Message>() -> Self {
+        Self::new(Selector::Message(lookup::<M>()))
+    }
+
+    /// Matches the message by its protocol and name.
+    ///
+    /// # Panics
+    /// If there is no such message.
+    #[track_caller]
+    pub fn by_name(protocol: &str, name: &str) -> Self {
+        let vtable = MessageVTable::lookup(protocol, name)
+            .unwrap_or_else(|| panic!("unknown message `{protocol}/{name}`"));
+        Self::new(Selector::Message(vtable))
+    }
+
+    /// Also matches messages matched by the provided filter.
+    pub fn or(mut self, other: MsgFilter) -> Self {
+        self.terms.extend(other.terms);
+        self
+    }
+
+    /// Excludes the message from all terms added so far.
+    ///
+    /// # Panics
+    /// If the message isn't registered, e.g. `AnyMessage` is provided.
+    #[track_caller]
+    pub fn except<M: Message>(self) -> Self {
+        self.exclude(Selector::Message(lookup::<M>()))
+    }
+
+    /// Excludes all messages of the protocol from all terms added so far.
+    pub fn except_protocol(self, protocol: impl Into<String>) -> Self {
+        self.exclude(Selector::Protocol(protocol.into()))
+    }
+
+    /// Returns `true` if the message matches the filter.
+    pub fn matches(&self, message: &impl Message) -> bool {
+        self.terms.iter().any(|term| term.matches(message))
+    }
+
+    fn new(include: Selector) -> Self {
+        Self {
+            terms: vec![Term {
+                include,
+                exclude: Vec::new(),
+            }],
+        }
+    }
+
+    fn exclude(mut self, selector: Selector) -> Self {
+        for term in &mut self.terms {
+            term.exclude.push(selector.clone());
+        }
+        self
+    }
+}
+
+#[track_caller]
+fn lookup<M: Message>() -> &'static MessageVTable {
+    MessageVTable::lookup_by_type_id(M::_type_id()).expect("the message isn't registered")
+}
+
+impl fmt::Display for MsgFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let is_union = self.terms.len() > 1;
+
+        for (i, term) in self.terms.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" | ")?;
+            }
+
+            if is_union && !term.exclude.is_empty() {
+                write!(f, "({term})")?;
+            } else {
+                write!(f, "{term}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Debug for MsgFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "MsgFilter({self})")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{message, messages::Ping, AnyMessage};
+
+    #[message(protocol = "test")]
+    struct A;
+
+    #[message(protocol = "test")]
+    struct B;
+
+    #[test]
+    fn matches() {
+        let filter = MsgFilter::protocol("elfo-core")
+            .or(MsgFilter::message::<A>())
+            .except::<Ping>();
+
+        assert!(filter.matches(&A));
+        assert!(!filter.matches(&B));
+        assert!(!filter.matches(&Ping));
+        assert!(filter.matches(&crate::messages::Terminate::default()));
+
+        // Type-erased messages are matched too.
+        assert!(filter.matches(&AnyMessage::new(A)));
+        assert!(!filter.matches(&AnyMessage::new(Ping)));
+
+        let filter = MsgFilter::all().except_protocol("elfo-core");
+        assert!(filter.matches(&A));
+        assert!(!filter.matches(&Ping));
+
+        let filter = MsgFilter::by_name("elfo-core", "Ping");
+        assert!(filter.matches(&Ping));
+        assert!(!filter.matches(&A));
+    }
+
+    #[test]
+    fn union() {
+        // Exclusions of one term don't affect others.
+        let filter = MsgFilter::protocol("test")
+            .except::<A>()
+            .or(MsgFilter::message::<A>());
+        assert!(filter.matches(&A));
+        assert!(filter.matches(&B));
+
+        let filter = MsgFilter::protocol("test")
+            .except::<A>()
+            .or(MsgFilter::protocol("elfo-core").except::<Ping>());
+        assert!(!filter.matches(&A));
+        assert!(filter.matches(&B));
+        assert!(!filter.matches(&Ping));
+        assert!(filter.matches(&crate::messages::Terminate::default()));
+    }
+
+    #[test]
+    fn display() {
+        let filter = MsgFilter::protocol("elfo-core")
+            .or(MsgFilter::message::<A>())
+            .except::<Ping>()
+            .except_protocol("x");
+
+        assert_eq!(
+            filter.to_string(),
+            "(elfo-core/* except elfo-core/Ping, x/*) | (test/A except elfo-core/Ping, x/*)"
+        );
+        assert_eq!(MsgFilter::all().to_string(), "*");
+
+        let filter = MsgFilter::all()
+            .or(MsgFilter::protocol("x").except::<Ping>())
+            .or(MsgFilter::message::<B>());
+        assert_eq!(
+            filter.to_string(),
+            "* | (x/* except elfo-core/Ping) | test/B"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "unknown message `elfo-core/Unknown`")]
+    fn unknown() {
+        MsgFilter::by_name("elfo-core", "Unknown");
+    }
+}
@@ -0,0 +1,187 @@
+use schemars::{generate::SchemaSettings, Schema};
+use serde_json::{json, Map, Value};
+
+use super::Topology;
+use crate::config::SystemConfig;
+
+impl Topology {
+    /// Generates the JSON Schema (draft 2020-12) of configs of all mounted
+    /// groups, e.g. to validate configs in CI or to get completions in IDEs.
+    ///
+    /// Each group is described by its section, e.g. `system.configurers`.
+    /// The `system` section of each group is always included. The schema of
+    /// the group's own config is included only if it's enabled by
+    /// [`ActorGroup::config_schema()`], otherwise any value is allowed.
+    ///
+    /// # Example
+    /// ```
+    /// # use elfo_core as elfo;
+    /// use elfo::{ActorGroup, Topology};
+    ///
+    /// #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+    /// struct Config {
+    ///     rate: u32,
+    /// }
+    ///
+    /// let topology = Topology::empty();
+    /// let producers = topology.local("producers");
+    ///
+    /// producers.mount(
+    ///     ActorGroup::new()
+    ///         .config::<Config>()
+    ///         .config_schema()
+    ///         .exec(|_| async {}),
+    /// );
+    ///
+    /// let schema = serde_json::to_string_pretty(&topology.config_schema());
+    /// ```
+    ///
+    /// [`ActorGroup::config_schema()`]: crate::ActorGroup::config_schema
+    pub fn config_schema(&self) -> Schema {
+        let mut generator = SchemaSettings::draft2020_12()
+            .for_deserialize()
+            .into_generator();
+        let system = generator.subschema_for::<SystemConfig>();
+
+        let mut root = Map::new();
+
+        if let Some(meta_schema) = &generator.settings().meta_schema {
+            root.insert("$schema".into(), meta_schema.as_ref().into());
+        }
+
+        root.insert("type".into(), "object".into());
+
+        // Values of the `common` section are defaults for all groups.
+        insert(&mut root, "common", object(json!({ "type": "object" })));
+
+        for group in self.locals() {
+            let mut schema = object(json!({
+                "type": "object",
+                "properties": { "system": system },
+            }));
+
+            if let Some(user) = group.config.schema(&mut generator) {
+                schema.insert("allOf".into(), json!([user]));
+            }
+
+            insert(&mut root, &group.name, schema);
+        }
+
+        let definitions = generator.take_definitions(true);
+        root.insert("$defs".into(), Value::Object(definitions));
+
+        Schema::from(root)
+    }
+}
+
+fn object(value: Value) -> Map<String, Value> {
+    match value {
+        Value::Object(map) => map,
+        _ => unreachable!("must be an object"),
+    }
+}
+
+/// Inserts the schema by the dotted path, e.g. `system.configurers`.
+fn insert(parent: &mut Map<String, Value>, path: &str, schema: Map<String, Value>) {
+    let properties = parent
+        .entry("properties")
+        .or_insert_with(|| json!({}))
+        .as_object_mut()
+        .expect("properties must be an object");
+
+    let (name, rest) = match path.split_once('.') {
+        Some((name, rest)) => (name, Some(rest)),
+        None => (path, None),
+    };
+
+    let child = properties
+        .entry(name)
+        .or_insert_with(|| json!({ "type": "object" }))
+        .as_object_mut()
+        .expect("schema must be an object");
+
+    let Some(rest) = rest else {
+        // The section can be already created by nested groups, e.g. `a.b`
+        // is mounted before `a`, so keep their properties.
+        for (key, value) in schema {
+            match (child.get_mut(&key), value) {
+                (Some(Value::Object(dst)), Value::Object(src)) if key == "properties" => {
+                    dst.extend(src);
+                }
+                (_, value) => {
+                    child.insert(key, value);
+                }
+            }
+        }
+        return;
+    };
+
+    insert(child, rest, schema);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{config::Secret, ActorGroup, Blueprint};
+
+    #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+    #[allow(dead_code)]
+    struct TestConfig {
+        rate: u32,
+        token: Secret<String>,
+    }
+
+    fn blueprint(with_schema: bool) -> Blueprint {
+        let group = ActorGroup::new().config::<TestConfig>();
+        let group = if with_schema {
+            group.config_schema()
+        } else {
+            group
+        };
+        group.exec(|_| async {})
+    }
+
+    #[test]
+    fn groups() {
+        let topology = Topology::empty();
+
+        topology.local("a.b").mount(blueprint(false));
+        topology.local("a").mount(blueprint(true));
+        topology.local("system.c").mount(blueprint(false));
+        drop(topology.local("unmounted"));
+
+        let schema = topology.config_schema();
+        let get = |pointer: &str| schema.pointer(pointer).cloned().unwrap_or_default();
+
+        assert_eq!(get("/type"), "object");
+        assert_eq!(get("/properties/common/type"), "object");
+        assert_eq!(get("/properties/unmounted"), Value::Null);
+
+        // With the schema of the group's config.
+        let system = json!({ "$ref": "#/$defs/SystemConfig" });
+        assert_eq!(get("/properties/a/properties/system"), system);
+        assert_eq!(get("/properties/a/allOf/0/$ref"), "#/$defs/TestConfig");
+        assert_eq!(get("/$defs/TestConfig/properties/rate/type"), "integer");
+
+        // Secrets can be provided by references.
+        let token = get("/$defs/TestConfig/properties/token/anyOf");
+        assert_eq!(token[0]["type"], "string");
+        assert_eq!(token[1]["required"], json!(["file"]));
+        assert_eq!(token[2]["required"], json!(["env"]));
+
+        // Without the schema, but nested.
+        assert_eq!(get("/properties/a/properties/b/properties/system"), system);
+        assert_eq!(get("/properties/a/properties/b/allOf"), Value::Null);
+        assert_eq!(
+            get("/properties/system/properties/c/properties/system"),
+            system
+        );
+
+        // System configs.
+        let mailbox = get("/$defs/SystemConfig/properties/mailbox/$ref");
+        assert_eq!(mailbox, "#/$defs/MailboxConfig");
+        let level = get("/$defs/LoggingConfig/properties/max_level/$ref");
+        assert_eq!(level, "#/$defs/PrettyLevelFilter");
+    }
+}
@@ -0,0 +1,170 @@
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+};
+
+use crate::{addr::Addr, message::Message};
+
+// === Protocol ===
+
+/// Represents a set of messages handled by some actors.
+///
+/// Never implement it by hand, use the `#[protocol]` macro instead:
+/// ```
+/// # use elfo_core as elfo;
+/// use elfo::{message, protocol};
+///
+/// #[message]
+/// struct Increment;
+///
+/// #[message(ret = u32)]
+/// struct GetValue;
+///
+/// #[protocol]
+/// enum Counter {
+///     Increment(Increment),
+///     GetValue(GetValue),
+/// }
+/// ```
+pub trait Protocol: 'static {}
+
+/// Implemented by the `#[protocol]` macro for every message of the protocol.
+#[diagnostic::on_unimplemented(message = "`{Self}` doesn't handle `{M}`")]
+pub trait Handles<M: Message>: Protocol {}
+
+// === Recipient ===
+
+/// Something messages of type `M` can be sent to:
+/// * [`Addr`] accepts any messages.
+/// * [`TypedAddr`] accepts only messages of its protocol.
+#[diagnostic::on_unimplemented(
+    message = "`{M}` cannot be sent to `{Self}`",
+    note = "only messages listed in the recipient's `#[protocol]` can be sent to `TypedAddr`"
+)]
+pub trait Recipient<M> {
+    fn into_addr(self) -> Addr;
+}
+
+impl<M: Message> Recipient<M> for Addr {
+    #[inline]
+    fn into_addr(self) -> Addr {
+        self
+    }
+}
+
+impl<P: Handles<M>, M: Message> Recipient<M> for TypedAddr<P> {
+    #[inline]
+    fn into_addr(self) -> Addr {
+        self.addr
+    }
+}
+
+// === TypedAddr ===
+
+/// An address of an actor or a group handling the protocol `P`.
+///
+/// Methods like [`Context::send_to()`] and [`Context::request_to()`] accept
+/// only messages of the protocol if they are called with `TypedAddr`,
+/// so protocol mistakes are found at compile time instead of being silently
+/// ignored at runtime.
+///
+/// It has the same representation as [`Addr`] and can be converted to it.
+///
+/// # Example
+/// ```
+/// # use elfo_core as elfo;
+/// # use elfo::{message, protocol};
+/// # #[message]
+/// # struct Increment;
+/// # #[message(ret = u32)]
+/// # struct GetValue;
+/// # #[protocol]
+/// # enum Counter {
+/// #     Increment(Increment),
+/// #     GetValue(GetValue),
+/// # }
+/// # async fn exec(ctx: elfo::Context, addr: elfo::Addr) {
+/// use elfo::TypedAddr;
+///
+/// let counter = TypedAddr::<Counter>::new(addr);
+/// let _ = ctx.send_to(counter, Increment).await;
+/// let value = ctx.request_to(counter, GetValue).resolve().await;
+/// # }
+/// ```
+///
+/// [`Context::send_to()`]: crate::Context::send_to
+/// [`Context::request_to()`]: crate::Context::request_to
+#[repr(transparent)]
+pub struct TypedAddr<P> {
+    addr: Addr,
+    marker: PhantomData<fn() -> P>,
+}
+
+assert_eq_size!(TypedAddr<()>, Addr);
+
+impl<P: Protocol> TypedAddr<P> {
+    /// Creates a typed address. It's a caller's responsibility to ensure
+    /// that the actor or group behind the address handles the protocol.
+    #[inline]
+    pub fn new(addr: Addr) -> Self {
+        Self {
+            addr,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<P> TypedAddr<P> {
+    /// Returns the untyped address.
+    #[inline]
+    pub fn addr(self) -> Addr {
+        self.addr
+    }
+}
+
+impl<P> From<TypedAddr<P>> for Addr {
+    #[inline]
+    fn from(addr: TypedAddr<P>) -> Self {
+        addr.addr
+    }
+}
+
+// Implemented manually to avoid extra bounds on `P`.
+
+impl<P> Clone for TypedAddr<P> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<P> Copy for TypedAddr<P> {}
+
+impl<P> PartialEq for TypedAddr<P> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.addr == other.addr
+    }
+}
+
+impl<P> Eq for TypedAddr<P> {}
+
+impl<P> Hash for TypedAddr<P> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.addr.hash(state);
+    }
+}
+
+impl<P> fmt::Debug for TypedAddr<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.addr, f)
+    }
+}
+
+impl<P> fmt::Display for TypedAddr<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.addr, f)
+    }
+}
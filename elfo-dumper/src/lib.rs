@@ -29,6 +29,10 @@ mod serializer;
 
 pub mod config;
 
+// Used by `elfo-journal` to write journals in the same format.
+#[doc(hidden)]
+pub use self::serializer::write_compact;
+
 /// Installs a global dump recorder and returns a group to handle dumps.
 pub fn new() -> Blueprint {
     let storage = Arc::new(Mutex::new(DumpStorage::new()));
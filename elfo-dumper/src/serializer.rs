@@ -171,6 +171,25 @@ impl Serializer {
     }
 }
 
+/// Appends the dump to the output in the same format as the dumper does,
+/// but without any limits and the trailing newline.
+pub fn write_compact(
+    output: &mut Vec<u8>,
+    dump: &Dump,
+    class: &str,
+) -> Result<(), serde_json::Error> {
+    let mut name_buffer = String::new();
+    let compact_dump = CompactDump {
+        dump,
+        class,
+        node_no: scope::node_no(),
+        message_name: dump.message_name.to_str(&mut name_buffer),
+        message: None,
+    };
+
+    serde_json::to_writer(output, &compact_dump)
+}
+
 // === CompactDump ===
 
 struct CompactDump<'a> {
@@ -0,0 +1,69 @@
+//! Configuration for journals.
+//!
+//! Note: all types here are exported only for documentation purposes
+//! and are not subject to stable guarantees. However, the config
+//! structure (usually encoded in TOML) follows stable guarantees.
+//!
+//! The main structure here is [`Config`].
+use std::path::PathBuf;
+
+use bytesize::ByteSize;
+use serde::Deserialize;
+
+/// The journal's config, usually a part of the actor's config.
+///
+/// # Example
+/// ```toml
+/// [orders.journal]
+/// path = "/path/journals"
+/// rules = [
+///     { protocol = "orders" },
+///     { protocol = "orders", message = "OrderQueried", persist = false },
+/// ]
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct Config {
+    /// A directory containing journals.
+    /// Each actor uses its own subdirectory named after its group and key.
+    pub path: PathBuf,
+    /// Once a segment file exceeds this size, a new one is started.
+    /// `64MiB` by default.
+    #[serde(default = "default_segment_size")]
+    pub segment_size: ByteSize,
+    /// Whether to call `fsync` after every record.
+    /// `false` by default.
+    #[serde(default)]
+    pub sync: bool,
+    /// Rule set to select messages to persist.
+    /// If several rules match a message, the last one is applied.
+    /// Messages matching no rules aren't persisted.
+    #[serde(default)]
+    pub rules: Vec<Rule>,
+}
+
+/// Defines a rule to select messages.
+///
+/// It's exported only for documentation purposes and cannot be created or
+/// received outside the journal.
+#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+pub struct Rule {
+    // Matchers.
+    /// Applies only for the specified protocol.
+    pub protocol: Option<String>,
+    /// Applies only for the specified message.
+    pub message: Option<String>,
+
+    // Params.
+    /// Whether to persist matched messages.
+    /// `true` by default.
+    #[serde(default = "default_persist")]
+    pub persist: bool,
+}
+
+fn default_segment_size() -> ByteSize {
+    ByteSize::mib(64)
+}
+
+fn default_persist() -> bool {
+    true
+}
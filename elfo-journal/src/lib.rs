@@ -0,0 +1,701 @@
+//! Journals selected incoming messages of an actor to replay them after
+//! restarts. [Configuration].
+//!
+//! Messages are selected by their protocols and names, similar to rules of
+//! the dumper, and appended to segment files in the actor's own directory.
+//! Each line is a valid JSON in the same format as dumps, where `s` is the
+//! sequence number of the record, increasing strictly inside the journal.
+//!
+//! Journals grow until a snapshot of the actor's state is saved, which
+//! removes all segments covered by the snapshot.
+//!
+//! Unlike other batteries, it's not a group, but a structure used directly
+//! inside an actor, which should replay the journal before handling new
+//! messages:
+//! ```
+//! # use elfo_core as elfo;
+//! use elfo::{msg, Context};
+//! use elfo_journal::{config::Config, Journal};
+//!
+//! #[elfo::message]
+//! struct Deposit(u64);
+//!
+//! #[elfo::message]
+//! struct Balance(u64);
+//!
+//! async fn exec(mut ctx: Context<Config>) -> std::io::Result<()> {
+//!     let mut journal = Journal::open(ctx.config()).await?;
+//!     let mut balance = journal.load_snapshot::<Balance>().await?.unwrap_or(Balance(0));
+//!
+//!     let mut replay = journal.replay();
+//!     while let Some(envelope) = replay.next().await {
+//!         msg!(match envelope? {
+//!             Deposit(amount) => balance.0 += amount,
+//!         });
+//!     }
+//!
+//!     while let Some(envelope) = ctx.recv().await {
+//!         journal.record(&envelope).await?;
+//!
+//!         msg!(match envelope {
+//!             Deposit(amount) => balance.0 += amount,
+//!         });
+//!
+//!         if journal.records_since_snapshot() >= 1000 {
+//!             journal.save_snapshot(&balance).await?;
+//!         }
+//!     }
+//!
+//!     Ok(())
+//! }
+//! ```
+//!
+//! [Configuration]: crate::config::Config
+
+use std::{
+    borrow::Cow,
+    future::Future,
+    io, mem,
+    path::{Path, PathBuf},
+    vec,
+};
+
+use serde::{de::IgnoredAny, Deserialize, Serialize};
+use serde_json::Value;
+use tokio::{
+    fs::{self, File, OpenOptions},
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines},
+};
+
+use elfo_core::{
+    dumping::{Direction, Dump, SequenceNo},
+    scope,
+    tracing::TraceId,
+    Addr, AnyMessage, Envelope, Message,
+    _priv::MessageKind,
+};
+
+use self::{config::Config, rule_set::RuleSet};
+
+mod rule_set;
+
+pub mod config;
+
+/// The class of written dumps.
+const CLASS: &str = "journal";
+const SEGMENT_EXTENSION: &str = "dump";
+const SNAPSHOT_FILE: &str = "snapshot.json";
+
+/// The journal of the current actor.
+pub struct Journal {
+    dir: PathBuf,
+    segment_size: u64,
+    sync: bool,
+    rule_set: RuleSet,
+    /// Paths to segments, ordered by sequence numbers.
+    segments: Vec<PathBuf>,
+    /// The last segment and its size, if it's opened for writing.
+    writer: Option<(File, u64)>,
+    last_sequence_no: u64,
+    snapshot_sequence_no: u64,
+    buffer: Vec<u8>,
+}
+
+impl Journal {
+    /// Opens the journal of the current actor, creating it if it doesn't exist.
+    ///
+    /// If the last record is incomplete, e.g. after a crash of the process,
+    /// it's removed along with segments left without records.
+    ///
+    /// # Panics
+    /// If called outside the actor system.
+    pub async fn open(config: &Config) -> io::Result<Self> {
+        let meta = scope::meta();
+        let mut name = String::new();
+        escape(&meta.group, &mut name);
+        if !meta.key.is_empty() {
+            name.push('.');
+            escape(&meta.key, &mut name);
+        }
+
+        let dir = config.path.join(name);
+        fs::create_dir_all(&dir).await?;
+
+        let mut segments = Vec::new();
+        let mut entries = fs::read_dir(&dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == SEGMENT_EXTENSION) {
+                segments.push(path);
+            }
+        }
+
+        // Names are zero-padded sequence numbers, so they're ordered properly.
+        segments.sort();
+
+        let snapshot_sequence_no = match fs::read(dir.join(SNAPSHOT_FILE)).await {
+            Ok(content) => serde_json::from_slice::<Snapshot<IgnoredAny>>(&content)?.s,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => 0,
+            Err(err) => return Err(err),
+        };
+
+        // A crash can leave the newest segments empty or torn,
+        // so the last record is searched starting from the newest segment.
+        let mut last_sequence_no = snapshot_sequence_no;
+        while let Some(path) = segments.last() {
+            if let Some(sequence_no) = recover_segment(path).await? {
+                last_sequence_no = last_sequence_no.max(sequence_no);
+                break;
+            }
+
+            fs::remove_file(path).await?;
+            segments.pop();
+        }
+
+        Ok(Self {
+            dir,
+            segment_size: config.segment_size.as_u64(),
+            sync: config.sync,
+            rule_set: RuleSet::new(&config.rules),
+            segments,
+            writer: None,
+            last_sequence_no,
+            snapshot_sequence_no,
+            buffer: Vec::new(),
+        })
+    }
+
+    /// Updates rules and limits. The path cannot be changed.
+    pub fn configure(&mut self, config: &Config) {
+        self.segment_size = config.segment_size.as_u64();
+        self.sync = config.sync;
+        self.rule_set.configure(&config.rules);
+    }
+
+    /// Returns the sequence number of the last record, if any.
+    pub fn last_sequence_no(&self) -> Option<SequenceNo> {
+        SequenceNo::try_from(self.last_sequence_no).ok()
+    }
+
+    /// Returns the number of records, which aren't covered by the snapshot.
+    pub fn records_since_snapshot(&self) -> u64 {
+        self.last_sequence_no - self.snapshot_sequence_no
+    }
+
+    /// Appends the message to the journal if it's selected by rules.
+    /// Returns the sequence number of the record if it's written.
+    ///
+    /// Requests are recorded as regular messages, so they are replayed
+    /// without an ability to respond.
+    ///
+    /// If the returned future is dropped before completion, the record can be
+    /// written partially, so the journal should be reopened.
+    // Envelopes aren't `Sync`, so the envelope is serialized before returning
+    // the future to keep it `Send`.
+    pub fn record(
+        &mut self,
+        envelope: &Envelope,
+    ) -> impl Future<Output = io::Result<Option<SequenceNo>>> + Send + '_ {
+        let serialized = self.serialize(envelope);
+
+        async move {
+            let Some(sequence_no) = serialized? else {
+                return Ok(None);
+            };
+
+            self.append(sequence_no).await?;
+            Ok(Some(sequence_no))
+        }
+    }
+
+    /// Writes the record into the buffer if it's selected by rules.
+    fn serialize(&mut self, envelope: &Envelope) -> io::Result<Option<SequenceNo>> {
+        let message = envelope.message();
+
+        if !self.rule_set.persist(message.protocol(), message.name()) {
+            return Ok(None);
+        }
+
+        let sequence_no = SequenceNo::try_from(self.last_sequence_no + 1).expect("impossible");
+
+        let mut dump = Dump::builder()
+            .direction(Direction::In)
+            .finish_any(&message);
+        dump.sequence_no = sequence_no;
+        dump.trace_id = envelope.trace_id();
+
+        self.buffer.clear();
+        elfo_dumper::write_compact(&mut self.buffer, &dump, CLASS)?;
+        self.buffer.push(b'\n');
+
+        Ok(Some(sequence_no))
+    }
+
+    /// Appends the buffer to the last segment, starting a new one if needed.
+    async fn append(&mut self, sequence_no: SequenceNo) -> io::Result<()> {
+        let raw_sequence_no = u64::from(sequence_no);
+
+        let is_full = |(_, size): &(File, u64)| *size >= self.segment_size;
+        if self.writer.as_ref().map_or(true, is_full) {
+            let name = format!("{raw_sequence_no:020}.{SEGMENT_EXTENSION}");
+            let path = self.dir.join(name);
+
+            // Existing segments contain records, so they're never truncated.
+            let file = OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&path)
+                .await?;
+
+            self.segments.push(path);
+            self.writer = Some((file, 0));
+        }
+
+        let (file, size) = self.writer.as_mut().expect("just created");
+
+        // Writes are buffered by `File`, so errors can be reported by `flush()`.
+        let result = match file.write_all(&self.buffer).await {
+            Ok(()) => file.flush().await,
+            Err(err) => Err(err),
+        };
+
+        if let Err(err) = result {
+            // Try to remove the incomplete record and start a new segment.
+            if *size == 0 {
+                let path = self.segments.pop().expect("just created");
+                let _ = fs::remove_file(path).await;
+            } else {
+                let _ = file.set_len(*size).await;
+            }
+
+            self.writer = None;
+            return Err(err);
+        }
+
+        if self.sync {
+            file.sync_data().await?;
+        }
+
+        *size += self.buffer.len() as u64;
+        self.last_sequence_no = raw_sequence_no;
+        Ok(())
+    }
+
+    /// Returns a stream of records, which aren't covered by the snapshot.
+    ///
+    /// Messages are provided as regular ones, without any sender.
+    /// Messages, which cannot be deserialized on this node, result in errors.
+    pub fn replay(&self) -> Replay {
+        Replay {
+            segments: self.segments.clone().into_iter(),
+            lines: None,
+            after: self.snapshot_sequence_no,
+        }
+    }
+
+    /// Loads the last saved snapshot, if any.
+    pub async fn load_snapshot<S: Message>(&self) -> io::Result<Option<S>> {
+        let content = match fs::read(self.dir.join(SNAPSHOT_FILE)).await {
+            Ok(content) => content,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err),
+        };
+
+        let snapshot = serde_json::from_slice::<Snapshot<AnyMessage>>(&content)?;
+        let state = snapshot.m.downcast::<S>().map_err(|state| {
+            let message = format!("unexpected snapshot: {}", state.name());
+            io::Error::new(io::ErrorKind::InvalidData, message)
+        })?;
+
+        Ok(Some(state))
+    }
+
+    /// Saves the snapshot of the state, which must reflect all recorded
+    /// messages, and removes all segments of the journal.
+    pub async fn save_snapshot<S: Message>(&mut self, state: &S) -> io::Result<()> {
+        let snapshot = Snapshot {
+            s: self.last_sequence_no,
+            m: AnyMessage::new(state.clone()),
+        };
+
+        let path = self.dir.join(SNAPSHOT_FILE);
+        let tmp_path = path.with_extension("json.tmp");
+
+        let mut file = File::create(&tmp_path).await?;
+        file.write_all(&serde_json::to_vec(&snapshot)?).await?;
+        file.flush().await?;
+        file.sync_all().await?;
+        fs::rename(&tmp_path, &path).await?;
+
+        self.snapshot_sequence_no = self.last_sequence_no;
+        self.writer = None;
+
+        for path in mem::take(&mut self.segments) {
+            remove_file(&path).await?;
+        }
+
+        Ok(())
+    }
+}
+
+// === Replay ===
+
+/// A stream of journaled messages, see [`Journal::replay()`].
+pub struct Replay {
+    segments: vec::IntoIter<PathBuf>,
+    lines: Option<Lines<BufReader<File>>>,
+    after: u64,
+}
+
+impl Replay {
+    /// Returns the next journaled message or `None` if all of them are
+    /// replayed.
+    pub async fn next(&mut self) -> Option<io::Result<Envelope>> {
+        loop {
+            if let Some(lines) = &mut self.lines {
+                match lines.next_line().await {
+                    Ok(Some(line)) if line.is_empty() => continue,
+                    Ok(Some(line)) => match parse_record(&line) {
+                        Ok((sequence_no, _)) if sequence_no <= self.after => continue,
+                        Ok((_, envelope)) => return Some(Ok(envelope)),
+                        Err(err) => return Some(Err(err)),
+                    },
+                    Ok(None) => self.lines = None,
+                    Err(err) => return Some(Err(err)),
+                }
+            }
+
+            let path = self.segments.next()?;
+
+            match File::open(path).await {
+                Ok(file) => self.lines = Some(BufReader::new(file).lines()),
+                // The segment has been removed by compaction.
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+}
+
+// === Files ===
+
+#[derive(Serialize, Deserialize)]
+struct Snapshot<M> {
+    s: u64,
+    m: M,
+}
+
+/// A subset of fields of a dump required to restore the message.
+#[derive(Deserialize)]
+struct Record<'a> {
+    s: u64,
+    t: TraceId,
+    #[serde(borrow)]
+    mp: Cow<'a, str>,
+    #[serde(borrow)]
+    mn: Cow<'a, str>,
+    m: Value,
+}
+
+#[derive(Deserialize)]
+struct RecordHeader {
+    s: u64,
+}
+
+fn parse_record(line: &str) -> io::Result<(u64, Envelope)> {
+    let record = serde_json::from_str::<Record<'_>>(line)?;
+    let message = Value::Array(vec![record.mp.into(), record.mn.into(), record.m]);
+    let message = AnyMessage::deserialize(&message)?;
+    let kind = MessageKind::regular(Addr::NULL);
+    Ok((record.s, Envelope::with_trace_id(message, kind, record.t)))
+}
+
+/// Truncates an incomplete record at the end of the segment.
+/// Returns the sequence number of the last record, if any.
+async fn recover_segment(path: &Path) -> io::Result<Option<u64>> {
+    let content = fs::read(path).await?;
+    let valid_len = content
+        .iter()
+        .rposition(|b| *b == b'\n')
+        .map_or(0, |pos| pos + 1);
+
+    if valid_len < content.len() {
+        OpenOptions::new()
+            .write(true)
+            .open(path)
+            .await?
+            .set_len(valid_len as u64)
+            .await?;
+    }
+
+    let Some(content) = content[..valid_len].strip_suffix(b"\n") else {
+        return Ok(None);
+    };
+
+    let start = content
+        .iter()
+        .rposition(|b| *b == b'\n')
+        .map_or(0, |pos| pos + 1);
+    let header = serde_json::from_slice::<RecordHeader>(&content[start..])?;
+    Ok(Some(header.s))
+}
+
+async fn remove_file(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path).await {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+        _ => Ok(()),
+    }
+}
+
+/// Escapes all characters except alphanumeric ones, `-` and `_`,
+/// to get a valid and unique file name.
+fn escape(s: &str, out: &mut String) {
+    for c in s.chars() {
+        if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+            out.push(c);
+        } else {
+            for byte in c.encode_utf8(&mut [0; 4]).bytes() {
+                out.push_str(&format!("%{byte:02X}"));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use elfo_core::{message, scope::Scope, ActorMeta};
+
+    use super::*;
+    use crate::config::Rule;
+
+    #[message]
+    struct Deposit(u64);
+
+    #[message]
+    struct Query;
+
+    #[message]
+    #[derive(PartialEq)]
+    struct Balance(u64);
+
+    fn config(name: &str) -> Config {
+        let path = std::env::temp_dir().join(format!("elfo-journal-{}-{name}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&path);
+
+        Config {
+            path,
+            segment_size: bytesize::ByteSize::b(100),
+            sync: false,
+            rules: vec![Rule {
+                protocol: None,
+                message: Some("Deposit".into()),
+                persist: true,
+            }],
+        }
+    }
+
+    async fn within<F: Future>(f: F) -> F::Output {
+        let meta = Arc::new(ActorMeta {
+            group: "group".into(),
+            key: "a/b".into(),
+        });
+
+        Scope::test(Addr::NULL, meta).within(f).await
+    }
+
+    async fn record(journal: &mut Journal, message: impl Message) -> Option<u64> {
+        let envelope = Envelope::new(message, MessageKind::regular(Addr::NULL));
+        journal.record(&envelope).await.unwrap().map(u64::from)
+    }
+
+    async fn replay(journal: &Journal) -> Vec<u64> {
+        let mut replay = journal.replay();
+        let mut amounts = Vec::new();
+
+        while let Some(envelope) = replay.next().await {
+            amounts.push(envelope.unwrap().unpack::<Deposit>().unwrap().0 .0);
+        }
+
+        amounts
+    }
+
+    #[tokio::test]
+    async fn record_and_replay() {
+        let config = config("replay");
+
+        within(async {
+            let mut journal = Journal::open(&config).await.unwrap();
+            assert_eq!(journal.last_sequence_no(), None);
+            assert!(replay(&journal).await.is_empty());
+
+            assert_eq!(record(&mut journal, Deposit(1)).await, Some(1));
+            assert_eq!(record(&mut journal, Query).await, None);
+            for amount in 2..=5 {
+                assert_eq!(record(&mut journal, Deposit(amount)).await, Some(amount));
+            }
+
+            // Segments are rotated.
+            assert!(journal.segments.len() > 1);
+            assert_eq!(replay(&journal).await, [1, 2, 3, 4, 5]);
+
+            // Sequence numbers continue after reopening.
+            let mut journal = Journal::open(&config).await.unwrap();
+            assert_eq!(journal.last_sequence_no().map(u64::from), Some(5));
+            assert_eq!(replay(&journal).await, [1, 2, 3, 4, 5]);
+            assert_eq!(record(&mut journal, Deposit(6)).await, Some(6));
+            assert_eq!(replay(&journal).await, [1, 2, 3, 4, 5, 6]);
+        })
+        .await;
+
+        std::fs::remove_dir_all(config.path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn snapshot() {
+        let config = config("snapshot");
+
+        within(async {
+            let mut journal = Journal::open(&config).await.unwrap();
+            assert_eq!(journal.load_snapshot::<Balance>().await.unwrap(), None);
+
+            for amount in 1..=3 {
+                record(&mut journal, Deposit(amount)).await;
+            }
+
+            journal.save_snapshot(&Balance(6)).await.unwrap();
+            assert_eq!(journal.records_since_snapshot(), 0);
+            assert!(replay(&journal).await.is_empty());
+            record(&mut journal, Deposit(4)).await;
+            assert_eq!(journal.records_since_snapshot(), 1);
+
+            let journal = Journal::open(&config).await.unwrap();
+            let snapshot = journal.load_snapshot::<Balance>().await.unwrap();
+            assert_eq!(snapshot, Some(Balance(6)));
+            assert_eq!(journal.last_sequence_no().map(u64::from), Some(4));
+            assert_eq!(replay(&journal).await, [4]);
+            assert!(journal.load_snapshot::<Deposit>().await.is_err());
+        })
+        .await;
+
+        std::fs::remove_dir_all(config.path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn incomplete_record() {
+        let mut config = config("incomplete");
+        config.segment_size = bytesize::ByteSize::mib(1);
+
+        within(async {
+            let mut journal = Journal::open(&config).await.unwrap();
+            record(&mut journal, Deposit(1)).await;
+            record(&mut journal, Deposit(2)).await;
+
+            let path = journal.segments.last().unwrap().clone();
+            append(&path, br#"{"ts":1,"g":"grou"#);
+
+            let mut journal = Journal::open(&config).await.unwrap();
+            assert_eq!(replay(&journal).await, [1, 2]);
+            assert_eq!(record(&mut journal, Deposit(3)).await, Some(3));
+            assert_eq!(replay(&journal).await, [1, 2, 3]);
+        })
+        .await;
+
+        std::fs::remove_dir_all(config.path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn crash_after_rollover() {
+        let config = config("rollover");
+
+        within(async {
+            let mut journal = Journal::open(&config).await.unwrap();
+            for amount in 1..=3 {
+                record(&mut journal, Deposit(amount)).await;
+            }
+
+            // The process crashes while writing the first record of a new
+            // segment, and then once more right after creating the next one.
+            let segment = |no: u64| journal.dir.join(format!("{no:020}.{SEGMENT_EXTENSION}"));
+            append(&segment(4), br#"{"ts":1,"g":"grou"#);
+            append(&segment(5), b"");
+
+            let mut journal = Journal::open(&config).await.unwrap();
+            assert_eq!(journal.last_sequence_no().map(u64::from), Some(3));
+            assert_eq!(replay(&journal).await, [1, 2, 3]);
+            assert_eq!(record(&mut journal, Deposit(4)).await, Some(4));
+
+            let journal = Journal::open(&config).await.unwrap();
+            assert_eq!(replay(&journal).await, [1, 2, 3, 4]);
+        })
+        .await;
+
+        std::fs::remove_dir_all(config.path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn existing_segment() {
+        let config = config("existing");
+
+        within(async {
+            let mut journal = Journal::open(&config).await.unwrap();
+
+            // Segments are never overwritten.
+            let path = journal.dir.join(format!("{:020}.{SEGMENT_EXTENSION}", 1));
+            append(&path, b"garbage");
+
+            let envelope = Envelope::new(Deposit(1), MessageKind::regular(Addr::NULL));
+            let err = journal.record(&envelope).await.unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+            assert_eq!(std::fs::read(&path).unwrap(), b"garbage");
+        })
+        .await;
+
+        std::fs::remove_dir_all(config.path).unwrap();
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn write_failure() {
+        let config = config("failure");
+
+        within(async {
+            let mut journal = Journal::open(&config).await.unwrap();
+
+            // The first segment is written on a full device.
+            let path = journal.dir.join(format!("{:020}.{SEGMENT_EXTENSION}", 1));
+            std::os::unix::fs::symlink("/dev/full", &path).unwrap();
+            let file = OpenOptions::new().write(true).open(&path).await.unwrap();
+            journal.segments.push(path.clone());
+            journal.writer = Some((file, 0));
+
+            let envelope = Envelope::new(Deposit(1), MessageKind::regular(Addr::NULL));
+            assert!(journal.record(&envelope).await.is_err());
+            assert!(std::fs::symlink_metadata(&path).is_err());
+            assert!(journal.segments.is_empty());
+
+            // Recording is resumed.
+            assert_eq!(record(&mut journal, Deposit(2)).await, Some(1));
+            assert_eq!(replay(&journal).await, [2]);
+
+            let journal = Journal::open(&config).await.unwrap();
+            assert_eq!(replay(&journal).await, [2]);
+        })
+        .await;
+
+        std::fs::remove_dir_all(config.path).unwrap();
+    }
+
+    fn append(path: &Path, content: &[u8]) {
+        use std::io::Write;
+
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .unwrap();
+
+        file.write_all(content).unwrap();
+    }
+}
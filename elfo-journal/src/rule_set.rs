@@ -0,0 +1,75 @@
+use fxhash::FxHashMap;
+
+use crate::config::Rule;
+
+pub(crate) struct RuleSet {
+    rules: Vec<Rule>,
+    cache: FxHashMap<(&'static str, &'static str), bool>,
+}
+
+impl RuleSet {
+    pub(crate) fn new(rules: &[Rule]) -> Self {
+        Self {
+            rules: rules.to_vec(),
+            cache: FxHashMap::default(),
+        }
+    }
+
+    pub(crate) fn configure(&mut self, rules: &[Rule]) {
+        if self.rules != rules {
+            self.cache.clear();
+            self.rules = rules.to_vec();
+        }
+    }
+
+    pub(crate) fn persist(&mut self, protocol: &'static str, message: &'static str) -> bool {
+        *self
+            .cache
+            .entry((protocol, message))
+            .or_insert_with(|| collect_persist(&self.rules, protocol, message))
+    }
+}
+
+#[cold]
+fn collect_persist(rules: &[Rule], protocol: &str, message: &str) -> bool {
+    rules
+        .iter()
+        .rev()
+        .find(|r| {
+            r.protocol.as_ref().map_or(true, |p| p == protocol)
+                && r.message.as_ref().map_or(true, |m| m == message)
+        })
+        .is_some_and(|r| r.persist)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(protocol: Option<&str>, message: Option<&str>, persist: bool) -> Rule {
+        Rule {
+            protocol: protocol.map(Into::into),
+            message: message.map(Into::into),
+            persist,
+        }
+    }
+
+    #[test]
+    fn it_works() {
+        let mut rule_set = RuleSet::new(&[]);
+        assert!(!rule_set.persist("a", "A"));
+
+        rule_set.configure(&[
+            rule(Some("a"), None, true),
+            rule(Some("a"), Some("B"), false),
+            rule(None, Some("C"), true),
+        ]);
+        assert!(rule_set.persist("a", "A"));
+        assert!(!rule_set.persist("a", "B"));
+        assert!(rule_set.persist("b", "C"));
+        assert!(!rule_set.persist("b", "A"));
+
+        rule_set.configure(&[rule(None, None, true)]);
+        assert!(rule_set.persist("a", "B"));
+    }
+}
@@ -0,0 +1,381 @@
+use std::collections::HashSet;
+
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use syn::{
+    parse::{Error as ParseError, Parse, ParseStream},
+    parse_macro_input, parse_quote,
+    spanned::Spanned,
+    ExprMatch, FnArg, Ident, ImplItem, ImplItemFn, ItemImpl, Path, PathArguments, Token, Type,
+};
+
+use crate::errors::emit_error;
+
+#[derive(Debug)]
+struct ActorArgs {
+    crate_: Option<Path>,
+}
+
+impl Parse for ActorArgs {
+    fn parse(input: ParseStream<'_>) -> Result<Self, ParseError> {
+        let mut args = ActorArgs { crate_: None };
+
+        // `#[actor]`
+        // `#[actor(elfo = some)]`
+        while !input.is_empty() {
+            let ident: Ident = input.parse()?;
+
+            match ident.to_string().as_str() {
+                "elfo" => {
+                    let _: Token![=] = input.parse()?;
+                    args.crate_ = Some(input.parse()?);
+                }
+                _ => return Err(input.error("unknown attribute")),
+            }
+
+            if !input.is_empty() {
+                let _: Token![,] = input.parse()?;
+            }
+        }
+
+        Ok(args)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum HandlerKind {
+    // `#[handler]`
+    Message,
+    // `#[handler(validate_config)]`
+    ValidateConfig,
+    // `#[handler(update_config)]`
+    UpdateConfig,
+}
+
+#[derive(Debug)]
+enum Input {
+    // `message: SomeMessage`
+    Regular(Path),
+    // `(request, token): (SomeRequest, ResponseToken<SomeRequest>)`
+    Request(Path),
+    // `envelope: Envelope`
+    Envelope,
+    // `config: &Config`
+    Config(Type),
+}
+
+#[derive(Debug)]
+struct Handler {
+    method: Ident,
+    kind: HandlerKind,
+    is_async: bool,
+    // `Some(is_mut)` if the handler takes the context.
+    context: Option<bool>,
+    input: Input,
+}
+
+/// Removes `#[handler]` attributes from the method and parses the handler's kind.
+fn take_handler_kind(method: &mut ImplItemFn) -> Option<HandlerKind> {
+    let index = method
+        .attrs
+        .iter()
+        .position(|attr| attr.path().is_ident("handler"))?;
+
+    let attr = method.attrs.remove(index);
+
+    if attr.meta.require_path_only().is_ok() {
+        return Some(HandlerKind::Message);
+    }
+
+    match attr.parse_args::<Ident>() {
+        Ok(ident) if ident == "validate_config" => Some(HandlerKind::ValidateConfig),
+        Ok(ident) if ident == "update_config" => Some(HandlerKind::UpdateConfig),
+        _ => {
+            emit_error!(
+                attr.span(),
+                "expected `#[handler]`, `#[handler(validate_config)]` or `#[handler(update_config)]`"
+            );
+            None
+        }
+    }
+}
+
+fn extract_message_path(ty: &Type) -> Option<&Path> {
+    match ty {
+        Type::Path(ty) if ty.qself.is_none() => {
+            let path = &ty.path;
+            let is_generic = path
+                .segments
+                .iter()
+                .any(|segment| !matches!(segment.arguments, PathArguments::None));
+
+            if is_generic {
+                emit_error!(ty.span(), "generic messages are not supported");
+            }
+
+            Some(path)
+        }
+        _ => None,
+    }
+}
+
+fn extract_input(kind: HandlerKind, ty: &Type) -> Option<Input> {
+    if kind != HandlerKind::Message {
+        return match ty {
+            Type::Reference(ty) if ty.mutability.is_none() => {
+                Some(Input::Config((*ty.elem).clone()))
+            }
+            _ => {
+                emit_error!(
+                    ty.span(),
+                    "expected a reference to the config, e.g. `&Config`"
+                );
+                None
+            }
+        };
+    }
+
+    match ty {
+        Type::Tuple(tuple) if tuple.elems.len() == 2 => extract_message_path(&tuple.elems[0])
+            .cloned()
+            .map(Input::Request),
+        Type::Path(path) if path.path.segments.last().unwrap().ident == "Envelope" => {
+            Some(Input::Envelope)
+        }
+        _ => extract_message_path(ty).cloned().map(Input::Regular),
+    }
+    .or_else(|| {
+        emit_error!(
+            ty.span(),
+            "expected a message, a `(request, token)` pair or `Envelope`"
+        );
+        None
+    })
+}
+
+fn parse_handler(method: &ImplItemFn, kind: HandlerKind) -> Option<(Handler, Option<Type>)> {
+    let sig = &method.sig;
+
+    match sig.receiver() {
+        Some(receiver) if receiver.reference.is_some() => {}
+        _ => {
+            emit_error!(sig.span(), "handlers must take `&self` or `&mut self`");
+            return None;
+        }
+    }
+
+    if !sig.generics.params.is_empty() {
+        emit_error!(sig.generics.span(), "handlers cannot be generic");
+    }
+
+    let args = sig
+        .inputs
+        .iter()
+        .filter_map(|arg| match arg {
+            FnArg::Typed(arg) => Some(&*arg.ty),
+            FnArg::Receiver(_) => None,
+        })
+        .collect::<Vec<_>>();
+
+    let (context, input) = match (kind, &args[..]) {
+        (HandlerKind::Message, [context, input]) => (Some(*context), input),
+        (_, [input]) => (None, input),
+        (HandlerKind::Message, _) => {
+            emit_error!(
+                sig.inputs.span(),
+                "expected `(&mut self, ctx: &Context, message)` or `(&mut self, message)`"
+            );
+            return None;
+        }
+        _ => {
+            emit_error!(sig.inputs.span(), "expected `(&mut self, config: &Config)`");
+            return None;
+        }
+    };
+
+    let context = match context {
+        Some(Type::Reference(ty)) => Some((ty.mutability.is_some(), (*ty.elem).clone())),
+        Some(ty) => {
+            emit_error!(ty.span(), "expected a reference to the context");
+            return None;
+        }
+        None => None,
+    };
+
+    let handler = Handler {
+        method: sig.ident.clone(),
+        kind,
+        is_async: sig.asyncness.is_some(),
+        context: context.as_ref().map(|(is_mut, _)| *is_mut),
+        input: extract_input(kind, input)?,
+    };
+
+    Some((handler, context.map(|(_, ty)| ty)))
+}
+
+/// Returns a key used to detect multiple handlers for the same message.
+fn handled_message(handler: &Handler) -> String {
+    let path = match (&handler.kind, &handler.input) {
+        (HandlerKind::ValidateConfig, _) => return "ValidateConfig".into(),
+        (HandlerKind::UpdateConfig, _) => return "ConfigUpdated".into(),
+        (_, Input::Envelope) => return "Envelope".into(),
+        (_, Input::Regular(path) | Input::Request(path)) => path,
+        (_, Input::Config(_)) => unreachable!(),
+    };
+
+    let last = path.segments.last().unwrap().ident.to_string();
+    if last == "ValidateConfig" || last == "ConfigUpdated" {
+        last
+    } else {
+        path.to_token_stream().to_string()
+    }
+}
+
+fn gen_arm(handler: &Handler, crate_: &Path) -> TokenStream {
+    let method = &handler.method;
+    let await_ = handler.is_async.then(|| quote! { .await });
+    let context = handler.context.map(|is_mut| {
+        if is_mut {
+            quote! { &mut ctx, }
+        } else {
+            quote! { &ctx, }
+        }
+    });
+
+    match (&handler.kind, &handler.input) {
+        (HandlerKind::ValidateConfig, _) => quote! {
+            (#crate_::messages::ValidateConfig { config, .. }, token) => {
+                let result = self.#method(ctx.unpack_config(&config))#await_;
+                ctx.respond(token, result.map_err(#crate_::messages::ConfigRejected::from));
+            }
+        },
+        (HandlerKind::UpdateConfig, _) => quote! {
+            #crate_::messages::ConfigUpdated => {
+                self.#method(ctx.config())#await_;
+            }
+        },
+        (_, Input::Regular(path)) => quote! {
+            message @ #path => {
+                self.#method(#context message)#await_;
+            }
+        },
+        (_, Input::Request(path)) => quote! {
+            (request @ #path, token) => {
+                self.#method(#context (request, token))#await_;
+            }
+        },
+        (_, Input::Envelope) => quote! {
+            envelope => {
+                self.#method(#context envelope)#await_;
+            }
+        },
+        (_, Input::Config(_)) => unreachable!(),
+    }
+}
+
+/// Implementation of the `#[actor]` macro.
+pub fn actor_impl(
+    args: proc_macro::TokenStream,
+    input: proc_macro::TokenStream,
+    default_path_to_elfo: Path,
+) -> proc_macro::TokenStream {
+    let args = parse_macro_input!(args as ActorArgs);
+    let crate_ = args.crate_.unwrap_or(default_path_to_elfo);
+
+    let mut input = parse_macro_input!(input as ItemImpl);
+
+    if let Some((_, trait_, _)) = &input.trait_ {
+        emit_error!(trait_.span(), "expected an inherent `impl` block");
+    }
+
+    let mut handlers = Vec::new();
+    let mut context_ty = None;
+    let mut config_ty = None;
+    let mut handled = HashSet::new();
+
+    for item in &mut input.items {
+        let ImplItem::Fn(method) = item else {
+            continue;
+        };
+
+        let Some(kind) = take_handler_kind(method) else {
+            continue;
+        };
+
+        let Some((handler, context)) = parse_handler(method, kind) else {
+            continue;
+        };
+
+        if !handled.insert(handled_message(&handler)) {
+            emit_error!(handler.method.span(), "the message is already handled");
+        }
+
+        if let Input::Config(ty) = &handler.input {
+            config_ty.get_or_insert_with(|| ty.clone());
+        }
+
+        if let Some(context) = context {
+            context_ty.get_or_insert(context);
+        }
+
+        handlers.push(handler);
+    }
+
+    // The context's type is taken from handlers, the compiler checks the rest.
+    let context_ty = context_ty.unwrap_or_else(|| match config_ty {
+        Some(config) => parse_quote! { #crate_::Context<#config> },
+        None => parse_quote! { #crate_::Context },
+    });
+
+    let mut arms = handlers
+        .iter()
+        .filter(|handler| !matches!(handler.input, Input::Envelope))
+        .map(|handler| gen_arm(handler, &crate_))
+        .collect::<Vec<_>>();
+
+    // The wildcard arm must be the last one.
+    arms.push(
+        match handlers
+            .iter()
+            .find(|handler| matches!(handler.input, Input::Envelope))
+        {
+            Some(handler) => gen_arm(handler, &crate_),
+            None => quote! {
+                envelope => #crate_::_priv::on_unhandled_message(envelope),
+            },
+        },
+    );
+
+    let dispatch: ExprMatch = parse_quote! {
+        match envelope {
+            #(#arms)*
+        }
+    };
+    let dispatch = crate::msg::expand(dispatch, &crate_);
+
+    let (impl_generics, _, where_clause) = input.generics.split_for_impl();
+    let self_ty = &input.self_ty;
+
+    let expanded = quote! {
+        #input
+
+        #[automatically_derived]
+        impl #impl_generics #self_ty #where_clause {
+            /// Receives messages and dispatches them to handlers until
+            /// the mailbox is closed.
+            #[allow(unused_mut)]
+            pub async fn run(mut self, mut ctx: #context_ty) {
+                while let Some(envelope) = ctx.recv().await {
+                    #dispatch
+                }
+            }
+        }
+    };
+
+    // Errors must be checked after expansion, otherwise some errors can be lost.
+    if let Some(errors) = crate::errors::into_tokens() {
+        quote! { #expanded #errors }.into()
+    } else {
+        expanded.into()
+    }
+}
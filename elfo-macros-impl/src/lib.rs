@@ -1,11 +1,15 @@
-//! An internal crate for the `message` and `msg` macros.
+//! An internal crate for the `message`, `msg`, `protocol` and `actor` macros.
 //! Prefer the `elfo-macros` crate if you don't need to wrap macros.
 
 extern crate proc_macro;
 
+mod actor;
 mod errors;
 mod message;
 mod msg;
+mod protocol;
 
+pub use actor::actor_impl;
 pub use message::message_impl;
 pub use msg::msg_impl;
+pub use protocol::protocol_impl;
@@ -15,9 +15,11 @@ struct MessageArgs {
     name: Option<LitStr>,
     protocol: Option<LitStr>,
     ret: Option<Type>,
+    stream_item: Option<Type>,
     part: bool,
     transparent: bool,
     dumping_allowed: Option<bool>,
+    high_priority: Option<bool>,
     crate_: Option<Path>,
     not: Vec<String>,
 }
@@ -26,11 +28,13 @@ impl Parse for MessageArgs {
     fn parse(input: ParseStream<'_>) -> Result<Self, ParseError> {
         let mut args = MessageArgs {
             ret: None,
+            stream_item: None,
             name: None,
             protocol: None,
             part: false,
             transparent: false,
             dumping_allowed: None,
+            high_priority: None,
             crate_: None,
             not: Vec::new(),
         };
@@ -39,11 +43,13 @@ impl Parse for MessageArgs {
         // `#[message(name = "N")]`
         // `#[message(protocol = "P")]`
         // `#[message(ret = A)]`
+        // `#[message(ret = stream A)]`
         // `#[message(part)]`
         // `#[message(part, transparent)]`
         // `#[message(elfo = some)]`
         // `#[message(not(Debug))]`
         // `#[message(dumping = "disabled")]`
+        // `#[message(priority = "high")]`
         while !input.is_empty() {
             let ident: Ident = input.parse()?;
 
@@ -58,7 +64,14 @@ impl Parse for MessageArgs {
                 }
                 "ret" => {
                     let _: Token![=] = input.parse()?;
-                    args.ret = Some(input.parse()?);
+
+                    if is_stream_keyword(input) {
+                        let _: Ident = input.parse()?;
+                        args.stream_item = Some(input.parse()?);
+                        args.ret = Some(syn::parse_quote! { () });
+                    } else {
+                        args.ret = Some(input.parse()?);
+                    }
                 }
                 "part" => args.part = true,
                 "transparent" => args.transparent = true,
@@ -73,6 +86,16 @@ impl Parse for MessageArgs {
                         return Err(input.error("only `dumping = \"disabled\"` is supported"));
                     }
                 }
+                "priority" => {
+                    let _: Token![=] = input.parse()?;
+                    let s: LitStr = input.parse()?;
+
+                    args.high_priority = Some(match s.value().as_str() {
+                        "high" => true,
+                        "normal" => false,
+                        _ => return Err(input.error("expected `\"high\"` or `\"normal\"`")),
+                    });
+                }
                 // TODO: call it `crate` like in linkme?
                 "elfo" => {
                     let _: Token![=] = input.parse()?;
@@ -99,6 +122,17 @@ impl Parse for MessageArgs {
     }
 }
 
+/// Checks if the input starts with `stream T`, but not with a type `stream`,
+/// `stream::T` or `stream<T>`.
+fn is_stream_keyword(input: ParseStream<'_>) -> bool {
+    let fork = input.fork();
+    matches!(fork.parse::<Ident>(), Ok(ident) if ident == "stream")
+        && !fork.is_empty()
+        && !fork.peek(Token![,])
+        && !fork.peek(Token![::])
+        && !fork.peek(Token![<])
+}
+
 impl MessageArgs {
     fn validate(&self) {
         if self.part {
@@ -112,6 +146,7 @@ impl MessageArgs {
             incompatible(&self.name, "name");
             incompatible(&self.protocol, "protocol");
             incompatible(&self.dumping_allowed, "dumping_allowed");
+            incompatible(&self.high_priority, "priority");
         }
     }
 }
@@ -197,6 +232,7 @@ pub fn message_impl(
 
     // TODO: pass to `ElfoResponseWrapper`.
     let dumping_allowed = args.dumping_allowed.unwrap_or(true);
+    let high_priority = args.high_priority.unwrap_or(false);
 
     let protocol = if let Some(protocol) = &args.protocol {
         quote! { #protocol }
@@ -224,7 +260,8 @@ pub fn message_impl(
             static VTABLE: &#internal::MessageVTable = &#internal::MessageVTable::new::<#name>(
                 #name_str,
                 #protocol,
-                #dumping_allowed
+                #dumping_allowed,
+                #high_priority
             );
         }
     });
@@ -269,6 +306,15 @@ pub fn message_impl(
         }
     });
 
+    let impl_stream_request = args.stream_item.as_ref().map(|item| {
+        quote! {
+            #[automatically_derived]
+            impl #crate_::StreamRequest for #name {
+                type Item = #item;
+            }
+        }
+    });
+
     let impl_debug =
         (args.transparent && args.not.iter().all(|x| x != "Debug")).then(|| gen_impl_debug(&input));
 
@@ -287,6 +333,7 @@ pub fn message_impl(
         const _: () = {
             #impl_message
             #impl_request
+            #impl_stream_request
             #impl_debug
         };
     };
@@ -1,6 +1,6 @@
 use std::{char, collections::HashMap};
 
-use proc_macro2::Span;
+use proc_macro2::{Span, TokenStream};
 use quote::quote_spanned;
 use syn::{
     parse_macro_input, spanned::Spanned, Arm, ExprMatch, Ident, Pat, PatIdent, PatWild, Path, Token,
@@ -218,9 +218,21 @@ fn add_groups(groups: &mut Vec<MessageGroup>, mut arm: Arm) {
 
 /// Implements the `msg!` macro.
 pub fn msg_impl(input: proc_macro::TokenStream, path_to_elfo: Path) -> proc_macro::TokenStream {
-    let crate_ = path_to_elfo;
     let mixed_site = Span::mixed_site();
     let input = parse_macro_input!(input as ExprMatch);
+    let expanded = expand(input, &path_to_elfo);
+
+    // Errors must be checked after expansion, otherwise some errors can be lost.
+    if let Some(errors) = crate::errors::into_tokens() {
+        quote_spanned!(mixed_site=> { #errors #expanded }).into()
+    } else {
+        expanded.into()
+    }
+}
+
+/// Expands `msg!` without checking errors, also used by other macros.
+pub(crate) fn expand(input: ExprMatch, crate_: &Path) -> TokenStream {
+    let mixed_site = Span::mixed_site();
     let mut groups = Vec::<MessageGroup>::with_capacity(input.arms.len());
 
     for arm in input.arms.into_iter() {
@@ -302,19 +314,12 @@ pub fn msg_impl(input: proc_macro::TokenStream, path_to_elfo: Path) -> proc_macr
     let match_expr = input.expr;
 
     // TODO: propagate `input.attrs`?
-    let expanded = quote_spanned!(mixed_site=> {
+    quote_spanned!(mixed_site=> {
         use #crate_::_priv as internal;
         let envelope = #match_expr;
         let type_id = envelope.type_id();
         #[allow(clippy::suspicious_else_formatting)]
         if false { unreachable!(); }
         #(#groups)*
-    });
-
-    // Errors must be checked after expansion, otherwise some errors can be lost.
-    if let Some(errors) = crate::errors::into_tokens() {
-        quote_spanned!(mixed_site=> { #errors #expanded }).into()
-    } else {
-        expanded.into()
-    }
+    })
 }
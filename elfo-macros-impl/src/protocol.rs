@@ -0,0 +1,100 @@
+use quote::quote;
+use syn::{
+    parse::{Error as ParseError, Parse, ParseStream},
+    parse_macro_input,
+    spanned::Spanned,
+    Data, DeriveInput, Fields, Ident, Path, Token,
+};
+
+use crate::errors::emit_error;
+
+#[derive(Debug)]
+struct ProtocolArgs {
+    crate_: Option<Path>,
+}
+
+impl Parse for ProtocolArgs {
+    fn parse(input: ParseStream<'_>) -> Result<Self, ParseError> {
+        let mut args = ProtocolArgs { crate_: None };
+
+        // `#[protocol]`
+        // `#[protocol(elfo = some)]`
+        while !input.is_empty() {
+            let ident: Ident = input.parse()?;
+
+            match ident.to_string().as_str() {
+                "elfo" => {
+                    let _: Token![=] = input.parse()?;
+                    args.crate_ = Some(input.parse()?);
+                }
+                _ => return Err(input.error("unknown attribute")),
+            }
+
+            if !input.is_empty() {
+                let _: Token![,] = input.parse()?;
+            }
+        }
+
+        Ok(args)
+    }
+}
+
+/// Implementation of the `#[protocol]` macro.
+pub fn protocol_impl(
+    args: proc_macro::TokenStream,
+    input: proc_macro::TokenStream,
+    default_path_to_elfo: Path,
+) -> proc_macro::TokenStream {
+    let args = parse_macro_input!(args as ProtocolArgs);
+    let crate_ = args.crate_.unwrap_or(default_path_to_elfo);
+
+    let input = parse_macro_input!(input as DeriveInput);
+    let name = &input.ident;
+
+    if !input.generics.params.is_empty() {
+        emit_error!(input.generics.span(), "protocols cannot be generic");
+    }
+
+    let messages = match &input.data {
+        Data::Enum(data) => data
+            .variants
+            .iter()
+            .filter_map(|variant| match &variant.fields {
+                Fields::Unnamed(fields) if fields.unnamed.len() == 1 => Some(&fields.unnamed[0].ty),
+                _ => {
+                    emit_error!(
+                        variant.span(),
+                        "expected a variant with one unnamed field, e.g. `{0}({0})`",
+                        variant.ident
+                    );
+                    None
+                }
+            })
+            .collect::<Vec<_>>(),
+        _ => {
+            emit_error!(name.span(), "only enums can be protocols");
+            Vec::new()
+        }
+    };
+
+    let expanded = quote! {
+        // The enum only lists messages, it's not required to construct it.
+        #[allow(dead_code)]
+        #input
+
+        #[automatically_derived]
+        impl #crate_::Protocol for #name {}
+
+        #(
+            #[automatically_derived]
+            impl #crate_::Handles<#messages> for #name {}
+        )*
+    };
+
+    // Errors must be checked after expansion, otherwise some errors can be lost.
+    if let Some(errors) = crate::errors::into_tokens() {
+        quote! { #expanded #errors }.into()
+    } else {
+        expanded.into()
+    }
+}
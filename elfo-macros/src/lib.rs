@@ -1,9 +1,9 @@
-//! Contains `msg!` and `message!` proc-macros.
+//! Contains `msg!`, `message!` and `protocol!` proc-macros, and the `#[actor]` attribute.
 
 use proc_macro::TokenStream;
 use syn::parse_quote;
 
-use elfo_macros_impl::{message_impl, msg_impl};
+use elfo_macros_impl::{actor_impl, message_impl, msg_impl, protocol_impl};
 
 /// Matches a message based on the provided envelope.
 #[proc_macro]
@@ -22,9 +22,13 @@ pub fn msg_core(input: TokenStream) -> TokenStream {
 /// Attributes:
 /// * `part` — do not derive `Message`. Useful for parts of messages.
 /// * `ret = SomeType` — also derive `Request` with the provided response type.
+/// * `ret = stream SomeType` — also derive `StreamRequest` responded by a
+///   stream of the provided items.
 /// * `name = "SomeName"` — override a message name.
 /// * `not(Debug)` — do not derive `Debug`. Useful for custom instances.
 /// * `not(Clone)` — the same for `Clone`.
+/// * `priority = "high"` — deliver the message via the high-priority lane of
+///   mailboxes, which is drained before the regular one.
 /// * `elfo = some::path` — override a path to elfo.
 #[proc_macro_attribute]
 pub fn message(attr: TokenStream, input: TokenStream) -> TokenStream {
@@ -36,3 +40,50 @@ pub fn message(attr: TokenStream, input: TokenStream) -> TokenStream {
 pub fn message_core(attr: TokenStream, input: TokenStream) -> TokenStream {
     message_impl(attr, input, parse_quote!(::elfo_core))
 }
+
+/// Turns an enum listing messages into a protocol used by `TypedAddr`.
+///
+/// Every variant must have one unnamed field with a message type.
+///
+/// Attributes:
+/// * `elfo = some::path` — override a path to elfo.
+#[proc_macro_attribute]
+pub fn protocol(attr: TokenStream, input: TokenStream) -> TokenStream {
+    protocol_impl(attr, input, parse_quote!(::elfo))
+}
+
+#[doc(hidden)]
+#[proc_macro_attribute]
+pub fn protocol_core(attr: TokenStream, input: TokenStream) -> TokenStream {
+    protocol_impl(attr, input, parse_quote!(::elfo_core))
+}
+
+/// Generates the `run()` method dispatching incoming messages to handlers,
+/// which are methods of the `impl` block marked by `#[handler]`.
+///
+/// Handlers take `&self` or `&mut self`, optionally a context, and one of:
+/// * `message: SomeMessage` — a regular message.
+/// * `(request, token): (SomeRequest, ResponseToken<SomeRequest>)` — a request.
+/// * `envelope: Envelope` — any other message, replaces the default behavior
+///   of logging unhandled messages.
+///
+/// Handlers can be `async`. Also, there are handlers for configs:
+/// * `#[handler(validate_config)]` takes `&Config` and returns
+///   `Result<(), impl Display>`, which is used as a response to
+///   `ValidateConfig`. Note that `ValidateConfig` must be routed to actors
+///   by the group's router.
+/// * `#[handler(update_config)]` takes `&Config` and is called once the
+///   config is updated.
+///
+/// Attributes:
+/// * `elfo = some::path` — override a path to elfo.
+#[proc_macro_attribute]
+pub fn actor(attr: TokenStream, input: TokenStream) -> TokenStream {
+    actor_impl(attr, input, parse_quote!(::elfo))
+}
+
+#[doc(hidden)]
+#[proc_macro_attribute]
+pub fn actor_core(attr: TokenStream, input: TokenStream) -> TokenStream {
+    actor_impl(attr, input, parse_quote!(::elfo_core))
+}
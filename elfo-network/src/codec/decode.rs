@@ -1,4 +1,4 @@
-use std::{convert::TryFrom, io::Cursor};
+use std::{convert::TryFrom, io::Cursor, time::Duration};
 
 use byteorder::{LittleEndian, ReadBytesExt};
 use eyre::{ensure, eyre, Error, WrapErr};
@@ -8,9 +8,9 @@ use elfo_core::{errors::RequestError, tracing::TraceId, AnyMessage, RequestId};
 use elfo_utils::likely;
 
 use crate::codec::format::{
-    NetworkAddr, NetworkEnvelope, NetworkEnvelopePayload, FLAG_IS_LAST_RESPONSE, KIND_MASK,
-    KIND_REGULAR, KIND_REQUEST_ALL, KIND_REQUEST_ANY, KIND_RESPONSE_FAILED, KIND_RESPONSE_IGNORED,
-    KIND_RESPONSE_OK,
+    NetworkAddr, NetworkEnvelope, NetworkEnvelopePayload, FLAG_HAS_TIMEOUT, FLAG_IS_LAST_RESPONSE,
+    KIND_MASK, KIND_REGULAR, KIND_REQUEST_ALL, KIND_REQUEST_ANY, KIND_RESPONSE_FAILED,
+    KIND_RESPONSE_IGNORED, KIND_RESPONSE_OK, KIND_RESPONSE_TIMEOUT,
 };
 
 #[derive(Default)]
@@ -155,6 +155,15 @@ fn get_request_id(frame: &mut Cursor<&[u8]>) -> eyre::Result<RequestId> {
     Ok(RequestId::from_ffi(frame.read_u64::<LittleEndian>()?))
 }
 
+fn get_timeout(frame: &mut Cursor<&[u8]>, flags: u8) -> eyre::Result<Option<Duration>> {
+    if flags & FLAG_HAS_TIMEOUT == 0 {
+        return Ok(None);
+    }
+
+    let nanos = frame.read_u64::<LittleEndian>()?;
+    Ok(Some(Duration::from_nanos(nanos)))
+}
+
 fn get_message(frame: &mut Cursor<&[u8]>) -> Result<AnyMessage, MessageDecodeError> {
     let protocol = get_str(frame).wrap_err("invalid message protocol")?;
     let name = get_str(frame)
@@ -230,6 +239,7 @@ fn do_decode(frame: &mut Cursor<&[u8]>) -> Result<NetworkEnvelope, DecodeError>
             let request_id = get_request_id(frame)?;
             RequestAny {
                 request_id,
+                timeout: get_timeout(frame, flags)?,
                 message: map_decode_error(get_message(frame), Some(request_id))?,
             }
         }
@@ -237,6 +247,7 @@ fn do_decode(frame: &mut Cursor<&[u8]>) -> Result<NetworkEnvelope, DecodeError>
             let request_id = get_request_id(frame)?;
             RequestAll {
                 request_id,
+                timeout: get_timeout(frame, flags)?,
                 message: map_decode_error(get_message(frame), Some(request_id))?,
             }
         }
@@ -258,6 +269,11 @@ fn do_decode(frame: &mut Cursor<&[u8]>) -> Result<NetworkEnvelope, DecodeError>
             message: Err(RequestError::Ignored),
             is_last: flags & FLAG_IS_LAST_RESPONSE != 0,
         },
+        KIND_RESPONSE_TIMEOUT => Response {
+            request_id: get_request_id(frame)?,
+            message: Err(RequestError::Timeout),
+            is_last: flags & FLAG_IS_LAST_RESPONSE != 0,
+        },
         n => return Err(eyre!("invalid message kind: {n}").into()),
     };
 
@@ -6,8 +6,9 @@ use elfo_core::{errors::RequestError, scope, Message};
 use elfo_utils::likely;
 
 use crate::codec::format::{
-    NetworkEnvelope, NetworkEnvelopePayload, FLAG_IS_LAST_RESPONSE, KIND_REGULAR, KIND_REQUEST_ALL,
-    KIND_REQUEST_ANY, KIND_RESPONSE_FAILED, KIND_RESPONSE_IGNORED, KIND_RESPONSE_OK,
+    NetworkEnvelope, NetworkEnvelopePayload, FLAG_HAS_TIMEOUT, FLAG_IS_LAST_RESPONSE, KIND_REGULAR,
+    KIND_REQUEST_ALL, KIND_REQUEST_ANY, KIND_RESPONSE_FAILED, KIND_RESPONSE_IGNORED,
+    KIND_RESPONSE_OK, KIND_RESPONSE_TIMEOUT,
 };
 
 #[derive(Debug, Display, From)]
@@ -74,16 +75,30 @@ fn do_encode(
     limit: Option<usize>,
 ) -> eyre::Result<()> {
     use NetworkEnvelopePayload::*;
-    let (is_last_response, kind, request_id, message) = match &envelope.payload {
-        Regular { message } => (false, KIND_REGULAR, None, Some(message)),
+    let (is_last_response, kind, request_id, timeout, message) = match &envelope.payload {
+        Regular { message } => (false, KIND_REGULAR, None, None, Some(message)),
         RequestAny {
             request_id,
+            timeout,
             message,
-        } => (false, KIND_REQUEST_ANY, Some(*request_id), Some(message)),
+        } => (
+            false,
+            KIND_REQUEST_ANY,
+            Some(*request_id),
+            *timeout,
+            Some(message),
+        ),
         RequestAll {
             request_id,
+            timeout,
             message,
-        } => (false, KIND_REQUEST_ALL, Some(*request_id), Some(message)),
+        } => (
+            false,
+            KIND_REQUEST_ALL,
+            Some(*request_id),
+            *timeout,
+            Some(message),
+        ),
         Response {
             request_id,
             message,
@@ -94,8 +109,10 @@ fn do_encode(
                 Ok(_) => KIND_RESPONSE_OK,
                 Err(RequestError::Failed) => KIND_RESPONSE_FAILED,
                 Err(RequestError::Ignored) => KIND_RESPONSE_IGNORED,
+                Err(RequestError::Timeout) => KIND_RESPONSE_TIMEOUT,
             },
             Some(*request_id),
+            None,
             message.as_ref().ok(),
         ),
     };
@@ -105,6 +122,9 @@ fn do_encode(
     if is_last_response {
         flags |= FLAG_IS_LAST_RESPONSE;
     }
+    if timeout.is_some() {
+        flags |= FLAG_HAS_TIMEOUT;
+    }
     dst.write_u8(flags | kind)?;
 
     // sender
@@ -121,6 +141,12 @@ fn do_encode(
         dst.write_u64::<LittleEndian>(request_id.to_ffi())?;
     }
 
+    // timeout
+    if let Some(timeout) = timeout {
+        let nanos = u64::try_from(timeout.as_nanos()).unwrap_or(u64::MAX);
+        dst.write_u64::<LittleEndian>(nanos)?;
+    }
+
     if let Some(message) = message {
         let mut put_str = |s: &str| -> eyre::Result<()> {
             let size = s.len();
@@ -5,7 +5,7 @@
 //! │ size of whole frame   │ 32 │                     │
 //! ├───────────────────────┼────┤                     │
 //! │ flags                 │  4 │                     │ flags:
-//! ├───────────────────────┼────┤                     │ - <reserved>       = 1
+//! ├───────────────────────┼────┤                     │ - has timeout      = 1
 //! │ kind                  │  4 │                     │ - <reserved>       = 2
 //! ├───────────────────────┼────┤       always        │ - <reserved>       = 4
 //! │ sender                │ 64 │                     │ - is last response = 8
@@ -16,12 +16,14 @@
 //! ├───────────────────────┼────┼─────────────────────┤ - Regular           = 0
 //! │ request id            │ 64 │ if kind != Regular  │ - RequestAny        = 1
 //! ├───────────────────────┼────┼─────────────────────┤ - RequestAll        = 2
-//! │ protocol's length (P) │  8 │                     │ - Response::Ok      = 3
-//! ├───────────────────────┼────┤                     │ - Response::Failed  = 4
-//! │ protocol              │ 8P │                     │ - Response::Ignored = 5
-//! ├───────────────────────┼────┤ if kind !=          │
-//! │ msg name's length (N) │  8 │ - Response::Failed  │
-//! ├───────────────────────┼────┤ - Response::Ignored │
+//! │ timeout (ns)          │ 64 │ if has timeout      │ - Response::Ok      = 3
+//! ├───────────────────────┼────┼─────────────────────┤ - Response::Failed  = 4
+//! │ protocol's length (P) │  8 │                     │ - Response::Ignored = 5
+//! ├───────────────────────┼────┤                     │ - Response::Timeout = 6
+//! │ protocol              │ 8P │ if kind !=          │
+//! ├───────────────────────┼────┤ - Response::Failed  │
+//! │ msg name's length (N) │  8 │ - Response::Ignored │
+//! ├───────────────────────┼────┤ - Response::Timeout │
 //! │ msg name              │ 8N │                     │
 //! ├───────────────────────┼────┤                     │
 //! │ msg payload           │rest│                     │
@@ -29,9 +31,14 @@
 //! ```
 //!
 //! All fields are encoded using LE ordering.
+//!
+//! The timeout is the time left until the request's deadline at the moment of
+//! encoding. It's used only if both nodes support `Features::DEADLINES`.
 
 // TODO: send message ID instead of protocol/name.
 
+use std::time::Duration;
+
 use derive_more::Display;
 
 use elfo_core::{
@@ -43,6 +50,7 @@ use elfo_core::{
 use elfo_utils::likely;
 
 // Flags are shifted by 4 bits to the left because of the kind.
+pub(crate) const FLAG_HAS_TIMEOUT: u8 = 1 << 4;
 pub(crate) const FLAG_IS_LAST_RESPONSE: u8 = 1 << 7;
 
 pub(crate) const KIND_MASK: u8 = 0xF;
@@ -52,6 +60,7 @@ pub(crate) const KIND_REQUEST_ALL: u8 = 2;
 pub(crate) const KIND_RESPONSE_OK: u8 = 3;
 pub(crate) const KIND_RESPONSE_FAILED: u8 = 4;
 pub(crate) const KIND_RESPONSE_IGNORED: u8 = 5;
+pub(crate) const KIND_RESPONSE_TIMEOUT: u8 = 6;
 
 #[derive(Debug)]
 pub(crate) struct NetworkEnvelope {
@@ -133,10 +142,12 @@ pub(crate) enum NetworkEnvelopePayload {
     },
     RequestAny {
         request_id: RequestId,
+        timeout: Option<Duration>,
         message: AnyMessage,
     },
     RequestAll {
         request_id: RequestId,
+        timeout: Option<Duration>,
         message: AnyMessage,
     },
     Response {
@@ -164,6 +175,10 @@ impl NetworkEnvelopePayload {
                 message: Err(RequestError::Ignored),
                 ..
             } => ("", "RequestError::Ignored"),
+            Self::Response {
+                message: Err(RequestError::Timeout),
+                ..
+            } => ("", "RequestError::Timeout"),
         }
     }
 }
@@ -4,8 +4,10 @@ pub(crate) mod format;
 
 #[cfg(test)]
 mod tests {
-    use elfo_core::{message, tracing::TraceId, Message, _priv::AnyMessage};
-    use std::convert::TryFrom;
+    use elfo_core::{
+        errors::RequestError, message, tracing::TraceId, Message, RequestId, _priv::AnyMessage,
+    };
+    use std::{convert::TryFrom, time::Duration};
 
     use super::{
         decode::{decode, DecodeState},
@@ -149,5 +151,69 @@ mod tests {
         }
     }
 
+    #[test]
+    fn request_timeout() {
+        let mut bytes = Vec::new();
+
+        let request_id = RequestId::from_ffi(42);
+        let timeout = Some(Duration::from_millis(1500));
+        let request = NetworkEnvelope {
+            sender: NetworkAddr::NULL,
+            recipient: NetworkAddr::NULL,
+            trace_id: TraceId::try_from(1).unwrap(),
+            payload: NetworkEnvelopePayload::RequestAny {
+                request_id,
+                timeout,
+                message: AnyMessage::new(SmallMessage(1)),
+            },
+        };
+        let response = NetworkEnvelope {
+            sender: NetworkAddr::NULL,
+            recipient: NetworkAddr::NULL,
+            trace_id: TraceId::try_from(1).unwrap(),
+            payload: NetworkEnvelopePayload::Response {
+                request_id,
+                message: Err(RequestError::Timeout),
+                is_last: true,
+            },
+        };
+
+        encode(&request, &mut bytes, &mut Default::default(), None).unwrap();
+        let request_size = bytes.len();
+        encode(&response, &mut bytes, &mut Default::default(), None).unwrap();
+
+        let Ok(DecodeState::Done { decoded, .. }) = decode(&bytes, &mut Default::default()) else {
+            panic!("expected the request to be decoded successfully");
+        };
+        let NetworkEnvelopePayload::RequestAny {
+            request_id: decoded_request_id,
+            timeout: decoded_timeout,
+            message,
+        } = decoded.payload
+        else {
+            panic!("expected RequestAny");
+        };
+        assert_eq!(decoded_request_id, request_id);
+        assert_eq!(decoded_timeout, timeout);
+        assert_eq!(
+            message.downcast_ref::<SmallMessage>(),
+            Some(&SmallMessage(1))
+        );
+
+        let state = decode(&bytes[request_size..], &mut Default::default());
+        let Ok(DecodeState::Done { decoded, .. }) = state else {
+            panic!("expected the response to be decoded successfully");
+        };
+        let NetworkEnvelopePayload::Response {
+            request_id: decoded_request_id,
+            message: Err(RequestError::Timeout),
+            is_last: true,
+        } = decoded.payload
+        else {
+            panic!("expected Response with Timeout");
+        };
+        assert_eq!(decoded_request_id, request_id);
+    }
+
     // TODO: test errors (including mismatch node_no).
 }
@@ -137,7 +137,7 @@ impl Discovery {
     fn get_capabilities(&self) -> socket::Capabilities {
         let compression = self.get_compression();
 
-        socket::Capabilities::new(compression)
+        socket::Capabilities::new(compression, socket::Features::all())
     }
 
     fn on_update_config(&mut self) {
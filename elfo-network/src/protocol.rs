@@ -69,6 +69,8 @@ pub(crate) mod internode {
     //      UpdateFlow -->
     //                  ...
     //                     <-- UpdateFlow
+    //                  ...
+    //      CancelRequest -->
     //
     //             any connection
     //      ~~~~~~~~~~~~~~~~~~~~~~~~~~~~~
@@ -114,6 +116,14 @@ pub(crate) mod internode {
         pub(crate) addr: NetworkAddr,
     }
 
+    /// The requester doesn't wait for responses anymore.
+    /// Sent only if both nodes support `Features::CANCELLATION`.
+    #[message]
+    pub(crate) struct CancelRequest {
+        pub(crate) requester: NetworkAddr,
+        pub(crate) request_id: u64,
+    }
+
     #[message]
     pub(crate) struct Ping {
         pub(crate) payload: u64,
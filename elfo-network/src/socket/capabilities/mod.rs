@@ -4,24 +4,36 @@ use self::compression::Compression;
 
 pub(crate) mod compression;
 
+bitflags::bitflags! {
+    /// Optional extensions of the internode protocol.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub(crate) struct Features: u8 {
+        /// Requests can contain deadlines and responses can be timed out.
+        const DEADLINES = 1;
+        /// Requesters can cancel requests, see `internode::CancelRequest`.
+        const CANCELLATION = 2;
+    }
+}
+
 /// Things supported by the node.
 // ~
 // Layout:
 // ```text
 //      16 bits       8 bits         8 bits
 // ┌──────────────┬─────────────┬──────────────┐
-// │   Reserved   │ Compression │   Reserved   │
+// │   Reserved   │ Compression │   Features   │
 // └──────────────┴─────────────┴──────────────┘
 // ```
 //
 // 1. [`Compression`] - compression capabilities.
+// 2. [`Features`] - supported protocol extensions.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) struct Capabilities(u32);
 
 impl Capabilities {
-    pub(crate) fn new(compression: Compression) -> Self {
+    pub(crate) fn new(compression: Compression, features: Features) -> Self {
         let compression = compression.into_bits();
-        Self(u32::from(compression) << 8)
+        Self(u32::from(compression) << 8 | u32::from(features.bits()))
     }
 
     pub(crate) const fn from_bits(bits: u32) -> Self {
@@ -34,17 +46,28 @@ impl Capabilities {
 
     pub(crate) fn intersection(self, rhs: Self) -> Self {
         let compr = self.compression().intersection(rhs.compression());
-        Self::new(compr)
+        let features = self.features() & rhs.features();
+        Self::new(compr, features)
     }
 
     pub(crate) const fn compression(self) -> Compression {
         Compression::from_bits((self.0 >> 8) as u8)
     }
+
+    pub(crate) const fn features(self) -> Features {
+        // Unknown bits (features of newer nodes) are skipped.
+        Features::from_bits_truncate(self.0 as u8)
+    }
 }
 
 impl fmt::Display for Capabilities {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "caps(compression={})", self.compression())
+        write!(
+            f,
+            "caps(compression={}, features={:?})",
+            self.compression(),
+            self.features()
+        )
     }
 }
 
@@ -57,17 +80,31 @@ mod tests {
 
     #[test]
     fn format_is_compatible_with_020alpha17() {
-        let caps = Capabilities::new(Compression::new(Algorithms::LZ4, Algorithms::empty()));
+        let caps = Capabilities::new(
+            Compression::new(Algorithms::LZ4, Algorithms::empty()),
+            Features::empty(),
+        );
         let lz4_bit = caps.into_bits() & (1 << 8);
 
         assert_eq!(lz4_bit, 1 << 8);
     }
 
+    #[test]
+    fn features_are_absent_in_020alpha17() {
+        // Nodes of v0.2.0-alpha.17 send only the compression byte.
+        let old = Capabilities::from_bits(1 << 8);
+        let new = Capabilities::new(Compression::empty(), Features::all());
+
+        assert_eq!(old.features(), Features::empty());
+        assert_eq!(new.intersection(old).features(), Features::empty());
+        assert_eq!(new.intersection(new).features(), Features::all());
+    }
+
     #[test]
     fn compression_encoded_right_way() {
         #[track_caller]
         fn case(create: (Algorithms, Algorithms), expect: (Algorithms, Algorithms)) {
-            let caps = Capabilities::new(Compression::new(create.0, create.1));
+            let caps = Capabilities::new(Compression::new(create.0, create.1), Features::all());
             let compr = caps.compression();
 
             assert_eq!(compr.supported(), expect.0);
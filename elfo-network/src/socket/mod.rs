@@ -22,7 +22,7 @@ use crate::{
 
 pub(crate) use self::capabilities::{
     compression::{Algorithms, Compression},
-    Capabilities,
+    Capabilities, Features,
 };
 
 mod capabilities;
@@ -407,7 +407,10 @@ mod tests {
     async fn tcp_read_write_lz4() {
         ensure_read_write(
             "tcp://127.0.0.1:9201",
-            Capabilities::new(Compression::new(Algorithms::empty(), Algorithms::LZ4)),
+            Capabilities::new(
+                Compression::new(Algorithms::empty(), Algorithms::LZ4),
+                Features::empty(),
+            ),
         )
         .await;
     }
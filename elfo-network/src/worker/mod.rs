@@ -14,14 +14,14 @@ use elfo_core::{
     msg, remote, scope,
     stream::Stream,
     time::Interval,
-    Context, Envelope, ResponseToken, Topology,
+    Context, Envelope, RequestId, ResponseToken, Topology,
 };
 use elfo_utils::{likely, time::Instant, unlikely};
 
 use self::{
     flows_rx::RxFlows,
     flows_tx::{Acquire, TryAcquire, TxFlows},
-    requests::OutgoingRequests,
+    requests::{IncomingRequests, OutgoingRequests},
 };
 
 use crate::{
@@ -30,13 +30,14 @@ use crate::{
         format::{
             NetworkAddr, NetworkEnvelope, NetworkEnvelopePayload, KIND_REQUEST_ALL,
             KIND_REQUEST_ANY, KIND_RESPONSE_FAILED, KIND_RESPONSE_IGNORED, KIND_RESPONSE_OK,
+            KIND_RESPONSE_TIMEOUT,
         },
     },
     config::Transport,
     frame::write::FrameState,
     protocol::{internode, ConnectionFailed, ConnectionRole, GroupInfo, HandleConnection},
     rtt::Rtt,
-    socket::{ReadError, ReadHalf, WriteHalf},
+    socket::{Features, ReadError, ReadHalf, WriteHalf},
     NetworkContext,
 };
 
@@ -115,6 +116,7 @@ impl Worker {
             first_message.initial_window,
         )));
         let requests = Arc::new(Mutex::new(OutgoingRequests::default()));
+        let incoming_requests = Arc::new(Mutex::new(IncomingRequests::default()));
         let socket = first_message.socket.take().unwrap();
 
         // Register `RemoteHandle`. Now we can receive messages from local groups.
@@ -134,9 +136,12 @@ impl Worker {
         // Start handling local incoming messages.
         let sw = SocketWriter {
             node_no: self.local.node_no,
+            features: socket.capabilities.features(),
             rx: local_rx,
             tx: socket.write,
+            local_tx: local_tx.clone(),
             requests: requests.clone(),
+            incoming_requests: incoming_requests.clone(),
         };
         self.ctx.attach(Stream::once(sw.exec()));
 
@@ -159,6 +164,7 @@ impl Worker {
             tx_flows: tx_flows.clone(),
             rx_flows: rx_flows.clone(),
             requests,
+            incoming_requests,
         };
         self.ctx.attach(Stream::once(sr.exec()));
 
@@ -225,9 +231,12 @@ impl Worker {
 /// to the socket.
 struct SocketWriter {
     node_no: NodeNo,
+    features: Features,
     rx: kanal::AsyncReceiver<KanalItem>,
     tx: WriteHalf,
+    local_tx: kanal::AsyncSender<KanalItem>,
     requests: Arc<Mutex<OutgoingRequests>>,
+    incoming_requests: Arc<Mutex<IncomingRequests>>,
 }
 
 impl SocketWriter {
@@ -248,7 +257,14 @@ impl SocketWriter {
             // TODO: error handling, metrics.
             let mut item = self.rx.recv().await.unwrap();
             loop {
-                let (network_envelope, response_token) = make_network_envelope(item, self.node_no);
+                // The last response is sent, so the request cannot be cancelled anymore.
+                if let Some(token) = item.token.as_ref().filter(|token| token.is_last()) {
+                    let mut incoming_requests = self.incoming_requests.lock();
+                    incoming_requests.remove(token.sender(), token.request_id());
+                }
+
+                let (network_envelope, response_token) =
+                    make_network_envelope(item, self.node_no, self.features);
                 scope::set_trace_id(network_envelope.trace_id);
 
                 // NOTE: We use `unwrap()` for results from all `self.tx` methods because these
@@ -257,6 +273,10 @@ impl SocketWriter {
                     // Envelope was encoded successfylly, so we can store the response token.
                     // Otherwise, it will be dropped with the `Failed` reason.
                     if let Some(token) = response_token {
+                        if self.features.contains(Features::CANCELLATION) {
+                            self.propagate_cancellation(&token);
+                        }
+
                         self.requests.lock().add_token(token);
                     }
 
@@ -274,12 +294,30 @@ impl SocketWriter {
             self.tx.flush().await.unwrap();
         }
     }
+
+    /// Notifies the remote node once the local requester cancels the request.
+    fn propagate_cancellation(&self, token: &ResponseToken) {
+        let cancellation = ward!(token.cancellation());
+        let local_tx = self.local_tx.clone();
+        let message = internode::CancelRequest {
+            requester: NetworkAddr::from_local(token.sender(), self.node_no),
+            request_id: token.request_id().to_ffi(),
+        };
+
+        cancellation.on_cancel(move || {
+            let envelope = make_system_envelope(message);
+            let _ = local_tx.try_send(KanalItem::simple(NetworkAddr::NULL, envelope));
+        });
+    }
 }
 
 fn make_network_envelope(
     item: KanalItem,
     node_no: NodeNo,
+    features: Features,
 ) -> (NetworkEnvelope, Option<ResponseToken>) {
+    let deadlines = features.contains(Features::DEADLINES);
+
     let (sender, trace_id, payload, token) = match (item.envelope, item.token) {
         // Regular, RequestAny, RequestAll
         (Ok(envelope), None) => {
@@ -292,6 +330,7 @@ fn make_network_envelope(
                 MessageKind::RequestAny(token) => (
                     NetworkEnvelopePayload::RequestAny {
                         request_id: token.request_id(),
+                        timeout: get_timeout(&token, deadlines),
                         message,
                     },
                     Some(token),
@@ -299,6 +338,7 @@ fn make_network_envelope(
                 MessageKind::RequestAll(token) => (
                     NetworkEnvelopePayload::RequestAll {
                         request_id: token.request_id(),
+                        timeout: get_timeout(&token, deadlines),
                         message,
                     },
                     Some(token),
@@ -336,6 +376,12 @@ fn make_network_envelope(
             let sender = Addr::NULL;
             let trace_id = token.trace_id();
 
+            // Old nodes know nothing about timeouts.
+            let err = match err {
+                RequestError::Timeout if !deadlines => RequestError::Failed,
+                err => err,
+            };
+
             let payload = NetworkEnvelopePayload::Response {
                 request_id: token.request_id(),
                 message: Err(err),
@@ -360,6 +406,16 @@ fn make_network_envelope(
     (envelope, token)
 }
 
+/// Returns the time left until the request's deadline.
+fn get_timeout(token: &ResponseToken, deadlines: bool) -> Option<Duration> {
+    if !deadlines {
+        return None;
+    }
+
+    let deadline = token.deadline()?;
+    Some(deadline.saturating_duration_since(tokio::time::Instant::now()))
+}
+
 // === SocketReader ===
 
 /// A subtask that reads messages from the socket and routes them to local
@@ -375,6 +431,7 @@ struct SocketReader {
     tx_flows: Arc<TxFlows>,
     rx_flows: Arc<Mutex<RxFlows>>,
     requests: Arc<Mutex<OutgoingRequests>>,
+    incoming_requests: Arc<Mutex<IncomingRequests>>,
 }
 
 impl SocketReader {
@@ -450,6 +507,7 @@ impl SocketReader {
                 details.sender.into_remote(),
                 details.request_id.expect("bug: request_id is missing"),
                 details.trace_id,
+                None,
                 self.ctx.book().clone(),
             );
 
@@ -461,6 +519,7 @@ impl SocketReader {
         } else if details.kind == KIND_RESPONSE_OK
             || details.kind == KIND_RESPONSE_FAILED
             || details.kind == KIND_RESPONSE_IGNORED
+            || details.kind == KIND_RESPONSE_TIMEOUT
         {
             let Some(token) = self.requests.lock().get_token(
                 details.recipient.into_remote(),
@@ -493,18 +552,24 @@ impl SocketReader {
             }
             NetworkEnvelopePayload::RequestAny {
                 request_id,
+                timeout,
                 message,
             } => {
-                let token =
-                    ResponseToken::new(sender, request_id, trace_id, self.ctx.book().clone());
+                let deadline = timeout.and_then(|t| tokio::time::Instant::now().checked_add(t));
+                let book = self.ctx.book().clone();
+                let token = ResponseToken::new(sender, request_id, trace_id, deadline, book);
+                self.incoming_requests.lock().add_token(&token);
                 (message, MessageKind::RequestAny(token))
             }
             NetworkEnvelopePayload::RequestAll {
                 request_id,
+                timeout,
                 message,
             } => {
-                let token =
-                    ResponseToken::new(sender, request_id, trace_id, self.ctx.book().clone());
+                let deadline = timeout.and_then(|t| tokio::time::Instant::now().checked_add(t));
+                let book = self.ctx.book().clone();
+                let token = ResponseToken::new(sender, request_id, trace_id, deadline, book);
+                self.incoming_requests.lock().add_token(&token);
                 (message, MessageKind::RequestAll(token))
             }
             NetworkEnvelopePayload::Response {
@@ -583,6 +648,11 @@ impl SocketReader {
                 let time_ns = Instant::now().nanos_since(self.time_origin) - msg.payload;
                 self.rtt.push(Duration::from_nanos(time_ns));
             }
+            msg @ internode::CancelRequest => {
+                let requester = msg.requester.into_remote();
+                let request_id = RequestId::from_ffi(msg.request_id);
+                self.incoming_requests.lock().cancel(requester, request_id);
+            }
             _ => return false,
         });
 
@@ -2,7 +2,7 @@ use fxhash::FxHashMap;
 use metrics::{decrement_gauge, increment_gauge};
 use tracing::error;
 
-use elfo_core::{Addr, RequestId, ResponseToken};
+use elfo_core::{Addr, RequestId, ResponseToken, _priv::Cancellation};
 
 #[derive(Default)]
 pub(super) struct OutgoingRequests {
@@ -63,3 +63,33 @@ impl Drop for OutgoingRequests {
         }
     }
 }
+
+/// Requests received from the remote node, which aren't responded yet.
+/// Used to propagate cancellation from remote requesters to local responders.
+#[derive(Default)]
+pub(super) struct IncomingRequests {
+    map: FxHashMap<(Addr, RequestId), Cancellation>,
+}
+
+impl IncomingRequests {
+    pub(super) fn add_token(&mut self, token: &ResponseToken) {
+        let cancellation = ward!(token.cancellation());
+        let (requester, request_id) = (token.sender(), token.request_id());
+
+        debug_assert!(requester.is_remote());
+        debug_assert!(!request_id.is_null());
+
+        self.map
+            .insert((requester, request_id), cancellation.clone());
+    }
+
+    pub(super) fn remove(&mut self, requester: Addr, request_id: RequestId) {
+        self.map.remove(&(requester, request_id));
+    }
+
+    pub(super) fn cancel(&mut self, requester: Addr, request_id: RequestId) {
+        if let Some(cancellation) = self.map.remove(&(requester, request_id)) {
+            cancellation.cancel();
+        }
+    }
+}
@@ -1,11 +1,15 @@
 use std::time::Duration;
 
 use tokio::{select, time};
-use tracing::{debug, info, warn};
+use tracing::{debug, warn};
 
 use elfo_core::{
-    message, messages::Ping, scope, time::Interval, topology::LocalActorGroup, ActorStatus, Addr,
-    Context, Topology,
+    message,
+    messages::{GroupMounted, GroupUnmounted, Ping},
+    msg, scope,
+    time::Interval,
+    topology::LocalActorGroup,
+    ActorStatus, Addr, Context, Topology,
 };
 use elfo_utils::ward;
 
@@ -16,14 +20,11 @@ struct PingTick;
 
 pub(crate) async fn exec(mut ctx: Context<Config>, topology: Topology) {
     let interval = ctx.attach(Interval::new(PingTick));
+    topology.subscribe_to_changes(ctx.group());
 
+    // Groups can be mounted later, so the pinger waits even if there are none.
     let mut groups = collect_groups(&topology, &[ctx.group()]);
-    let group_count = groups.len() as u32;
-
-    if groups.is_empty() {
-        info!("no groups to ping, terminating");
-        return;
-    }
+    let mut group_count = groups.len().max(1) as u32;
 
     let mut is_alarming = false;
     let mut timed_out = 0;
@@ -38,6 +39,16 @@ pub(crate) async fn exec(mut ctx: Context<Config>, topology: Topology) {
         select! {
             envelope = ctx.recv() => {
                 let envelope = ward!(envelope, break);
+
+                // Groups mounted at runtime are picked up on the next round.
+                msg!(match &envelope {
+                    GroupMounted | GroupUnmounted => {
+                        let actual = collect_groups(&topology, &[ctx.group()]);
+                        groups.retain(|group| actual.iter().any(|a| a.addr == group.addr));
+                        group_count = actual.len().max(1) as u32;
+                    }
+                });
+
                 interval.set_period(ctx.config().ping_interval / group_count);
 
                 if !envelope.is::<PingTick>() || pinging.is_some() {
@@ -54,7 +65,7 @@ pub(crate) async fn exec(mut ctx: Context<Config>, topology: Topology) {
                     timed_out = 0;
                 }
 
-                let group = groups.pop().unwrap();
+                let group = ward!(groups.pop(), continue);
                 let warn_threshold = ctx.config().warn_threshold;
 
                 // Expose a current scope to preserve an original trace id.
@@ -101,6 +101,42 @@ impl RateLimiter {
             })
             .is_ok()
     }
+
+    /// Returns the permit acquired by [`RateLimiter::acquire()`],
+    /// e.g. if the operation is rejected by another limiter.
+    pub fn release(&self) {
+        let step = self.step.load(Relaxed);
+
+        // Handle special cases.
+        if step == UNLIMITED || step == DISABLED {
+            return;
+        }
+
+        let _ = self
+            .vtime
+            .fetch_update(Relaxed, Relaxed, |vtime| Some(vtime.saturating_sub(step)));
+    }
+
+    /// Returns the approximate time until the next permit is available.
+    /// Returns [`Duration::MAX`] if the limiter forbids everything.
+    pub fn next_permit_in(&self) -> Duration {
+        let step = self.step.load(Relaxed);
+
+        // Handle special cases.
+        if step == UNLIMITED {
+            return Duration::ZERO;
+        }
+        if step == DISABLED {
+            return Duration::MAX;
+        }
+
+        let period = self.period.load(Relaxed);
+        let now = time::nanos_since_unknown_epoch();
+        let vtime = self.vtime.load(Relaxed);
+
+        // See `acquire()`: a permit is available once `vtime < now + period`.
+        Duration::from_nanos((vtime + 1).saturating_sub(now + period))
+    }
 }
 
 fn calculate_step(max_rate: u64, period: u64) -> u64 {
@@ -207,6 +243,44 @@ mod tests {
         }
     }
 
+    #[test]
+    fn release() {
+        time::with_instant_mock(|_mock| {
+            let limiter = RateLimiter::new(RateLimit::Rps(2));
+            assert!(limiter.acquire());
+            assert!(limiter.acquire());
+            assert!(!limiter.acquire());
+
+            limiter.release();
+            assert!(limiter.acquire());
+            assert!(!limiter.acquire());
+        });
+    }
+
+    #[test]
+    fn next_permit_in() {
+        time::with_instant_mock(|mock| {
+            let limiter = RateLimiter::new(RateLimit::Rps(4));
+            assert_eq!(limiter.next_permit_in(), Duration::ZERO);
+
+            for _ in 0..4 {
+                assert!(limiter.acquire());
+            }
+            assert!(!limiter.acquire());
+
+            let delay = limiter.next_permit_in();
+            assert!(delay > Duration::ZERO && delay <= ns(SEC / 4), "{delay:?}");
+            mock.advance(delay);
+            assert_eq!(limiter.next_permit_in(), Duration::ZERO);
+            assert!(limiter.acquire());
+        });
+
+        let limiter = RateLimiter::new(RateLimit::Rps(0));
+        assert_eq!(limiter.next_permit_in(), Duration::MAX);
+        let limiter = RateLimiter::new(RateLimit::Unlimited);
+        assert_eq!(limiter.next_permit_in(), Duration::ZERO);
+    }
+
     #[test]
     fn reset() {
         time::with_instant_mock(|mock| {
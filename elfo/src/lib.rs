@@ -5,7 +5,7 @@
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
 pub use elfo_core::*;
-pub use elfo_macros::{message, msg};
+pub use elfo_macros::{actor, message, msg, protocol};
 
 #[cfg(feature = "test-util")]
 #[cfg_attr(docsrs, doc(cfg(feature = "test-util")))]
@@ -14,6 +14,10 @@ pub use elfo_test as test;
 
 /// A set of actors for common tasks.
 pub mod batteries {
+    #[cfg(feature = "elfo-cartographer")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "full")))]
+    #[doc(inline)]
+    pub use elfo_cartographer as cartographer;
     #[cfg(feature = "elfo-configurer")]
     #[cfg_attr(docsrs, doc(cfg(feature = "full")))]
     #[doc(inline)]
@@ -22,6 +26,10 @@ pub mod batteries {
     #[cfg_attr(docsrs, doc(cfg(feature = "full")))]
     #[doc(inline)]
     pub use elfo_dumper as dumper;
+    #[cfg(feature = "elfo-journal")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "full")))]
+    #[doc(inline)]
+    pub use elfo_journal as journal;
     #[cfg(feature = "elfo-logger")]
     #[cfg_attr(docsrs, doc(cfg(feature = "full")))]
     #[doc(inline)]
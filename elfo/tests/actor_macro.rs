@@ -0,0 +1,169 @@
+#![allow(missing_docs)]
+#![cfg(feature = "test-util")]
+
+use serde::Deserialize;
+use toml::toml;
+
+use elfo::{
+    actor,
+    config::AnyConfig,
+    messages::{Ping, Terminate, UpdateConfig, ValidateConfig},
+    prelude::*,
+    routers::{MapRouter, Outcome, Singleton},
+    Envelope, Message, ResponseToken,
+};
+
+#[message]
+struct Increment(u32);
+
+#[message]
+struct Reset;
+
+#[message(ret = u32)]
+struct GetValue;
+
+#[message(ret = Vec<String>)]
+struct GetLog;
+
+#[message]
+struct Unrelated;
+
+#[derive(Debug, Clone, Deserialize)]
+struct Config {
+    limit: u32,
+}
+
+#[derive(Default)]
+struct Counter {
+    value: u32,
+    log: Vec<String>,
+}
+
+#[actor]
+impl Counter {
+    #[handler]
+    fn on_increment(&mut self, ctx: &Context<Config>, Increment(delta): Increment) {
+        self.value = (self.value + delta).min(ctx.config().limit);
+    }
+
+    #[handler]
+    async fn on_reset(&mut self, _: Reset) {
+        tokio::task::yield_now().await;
+        self.value = 0;
+    }
+
+    #[handler]
+    fn on_get_value(&self, ctx: &Context<Config>, (_, token): (GetValue, ResponseToken<GetValue>)) {
+        ctx.respond(token, self.value);
+    }
+
+    #[handler]
+    fn on_get_log(&mut self, ctx: &Context<Config>, (_, token): (GetLog, ResponseToken<GetLog>)) {
+        ctx.respond(token, self.log.clone());
+    }
+
+    #[handler(validate_config)]
+    fn validate_config(&mut self, config: &Config) -> Result<(), &'static str> {
+        self.log.push(format!("validate {}", config.limit));
+        if config.limit > 0 {
+            Ok(())
+        } else {
+            Err("limit must be positive")
+        }
+    }
+
+    #[handler(update_config)]
+    fn update_config(&mut self, config: &Config) {
+        self.log.push(format!("update {}", config.limit));
+        self.value = self.value.min(config.limit);
+    }
+
+    // Not a handler, just a regular method.
+    #[allow(dead_code)]
+    fn helper(&self) {}
+}
+
+fn counter() -> Blueprint {
+    ActorGroup::new()
+        .config::<Config>()
+        // `ValidateConfig` isn't routed to actors by default.
+        .router(MapRouter::new(|envelope| {
+            msg!(match envelope {
+                ValidateConfig | Terminate | Ping => Outcome::GentleUnicast(Singleton),
+                _ => Outcome::Unicast(Singleton),
+            })
+        }))
+        .exec(|ctx| Counter::default().run(ctx))
+}
+
+#[tokio::test]
+async fn it_works() {
+    let proxy = elfo::test::proxy(counter(), toml! { limit = 10 }).await;
+
+    proxy.send(Increment(3)).await;
+    proxy.send(Increment(4)).await;
+    assert_eq!(proxy.request(GetValue).await, 7);
+
+    proxy.send(Increment(4)).await;
+    assert_eq!(proxy.request(GetValue).await, 10);
+
+    proxy.send(Reset).await;
+    assert_eq!(proxy.request(GetValue).await, 0);
+
+    // Unhandled messages are discarded.
+    proxy.send(Unrelated).await;
+    assert_eq!(proxy.request(GetValue).await, 0);
+}
+
+#[tokio::test]
+async fn configs() {
+    let proxy = elfo::test::proxy(counter(), toml! { limit = 10 }).await;
+
+    proxy.send(Increment(8)).await;
+
+    let config = AnyConfig::deserialize(toml! { limit = 0 }).unwrap();
+    let res = proxy.request(ValidateConfig::new(config)).await;
+    assert_eq!(res.unwrap_err().reason, "limit must be positive");
+
+    let config = AnyConfig::deserialize(toml! { limit = 5 }).unwrap();
+    let res = proxy.request(ValidateConfig::new(config.clone())).await;
+    assert!(res.is_ok());
+    assert!(proxy.request(UpdateConfig::new(config)).await.is_ok());
+    assert_eq!(proxy.request(GetValue).await, 5);
+
+    proxy.send(Increment(8)).await;
+    assert_eq!(proxy.request(GetValue).await, 5);
+
+    let log = proxy.request(GetLog).await;
+    assert_eq!(log, ["validate 0", "validate 5", "update 5"]);
+}
+
+#[tokio::test]
+async fn fallback() {
+    #[message(ret = String)]
+    struct GetLast;
+
+    #[derive(Default)]
+    struct Recorder {
+        last: String,
+    }
+
+    #[actor]
+    impl Recorder {
+        #[handler]
+        fn on_get_last(&mut self, ctx: &Context, (_, token): (GetLast, ResponseToken<GetLast>)) {
+            ctx.respond(token, self.last.clone());
+        }
+
+        #[handler]
+        fn on_other(&mut self, envelope: Envelope) {
+            self.last = envelope.message().name().into();
+        }
+    }
+
+    let blueprint = ActorGroup::new().exec(|ctx| Recorder::default().run(ctx));
+    let proxy = elfo::test::proxy(blueprint, AnyConfig::default()).await;
+
+    proxy.send(Unrelated).await;
+    assert_eq!(proxy.request(GetLast).await, "Unrelated");
+}
@@ -0,0 +1,131 @@
+#![allow(missing_docs)]
+#![cfg(feature = "test-util")]
+
+use std::fs;
+
+use serde::Deserialize;
+
+use elfo::{
+    _priv::do_start,
+    batteries::configurer::{GetConfigHistory, ReloadConfigs, RollbackConfig},
+    config::Secret,
+    prelude::*,
+    Topology,
+};
+
+mod common;
+
+#[message(ret = u32)]
+struct GetValue;
+
+#[derive(Debug, Clone, Deserialize)]
+struct Config {
+    value: u32,
+    #[allow(dead_code)]
+    password: Secret<String>,
+}
+
+fn testee() -> Blueprint {
+    ActorGroup::new()
+        .config::<Config>()
+        .exec(move |mut ctx| async move {
+            while let Some(envelope) = ctx.recv().await {
+                msg!(match envelope {
+                    (GetValue, token) => ctx.respond(token, ctx.config().value),
+                });
+            }
+        })
+}
+
+#[tokio::test]
+async fn history_and_rollback() {
+    common::setup_logger();
+
+    let dir = std::env::temp_dir().join(format!("elfo-config-history-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("config.toml");
+
+    let write_config = |value: u32, password: &str| {
+        let content = format!(
+            "[system.configurers]\nunmasked_keys = [\"testee.value\"]\n\
+             [testee]\nvalue = {value}\npassword = \"{password}\""
+        );
+        fs::write(&path, content).unwrap();
+    };
+
+    write_config(1, "foo");
+
+    let topology = Topology::empty();
+    let configurers = topology.local("system.configurers").entrypoint();
+    let testee = topology.local("testee");
+    let configurers_addr = configurers.addr();
+    let testee_addr = testee.addr();
+
+    configurers.mount(elfo::batteries::configurer::from_path(&topology, &path));
+    testee.mount(self::testee());
+
+    do_start(topology, false, |ctx, _| async move {
+        write_config(2, "bar");
+        let response = ctx.request_to(configurers_addr, ReloadConfigs::default());
+        response.resolve().await.unwrap().unwrap();
+
+        let value = ctx.request_to(testee_addr, GetValue).resolve().await;
+        assert_eq!(value.unwrap(), 2);
+
+        let request = GetConfigHistory::default();
+        let history = ctx.request_to(configurers_addr, request).resolve().await;
+        let versions = history.unwrap().versions;
+
+        // Configs applied for the first time don't produce changes.
+        assert_eq!(versions.len(), 2);
+        assert_eq!(versions[0].version, 1);
+        assert!(versions[0].changes.is_empty());
+
+        let changes = &versions[1].changes;
+        assert_eq!(versions[1].version, 2);
+        assert_eq!(versions[1].rollback_to, None);
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].group, "testee");
+        assert_eq!(changes[0].path, "password");
+        assert!(changes[0].masked);
+        assert_eq!(changes[0].old.as_deref(), Some("<masked>"));
+        assert_eq!(changes[0].new.as_deref(), Some("<masked>"));
+        assert_eq!(changes[1].path, "value");
+        assert!(!changes[1].masked);
+        assert_eq!(changes[1].old.as_deref(), Some("1"));
+        assert_eq!(changes[1].new.as_deref(), Some("2"));
+
+        // Rolling back to the first version.
+        let response = ctx.request_to(configurers_addr, RollbackConfig::new(1));
+        response.resolve().await.unwrap().unwrap();
+
+        let value = ctx.request_to(testee_addr, GetValue).resolve().await;
+        assert_eq!(value.unwrap(), 1);
+
+        let request = GetConfigHistory::default();
+        let history = ctx.request_to(configurers_addr, request).resolve().await;
+        let versions = history.unwrap().versions;
+
+        assert_eq!(versions.len(), 3);
+        assert_eq!(versions[2].version, 3);
+        assert_eq!(versions[2].rollback_to, Some(1));
+        assert_eq!(versions[2].changes.len(), 2);
+
+        // Unknown versions are rejected.
+        let response = ctx.request_to(configurers_addr, RollbackConfig::new(42));
+        let errors = response.resolve().await.unwrap().unwrap_err().errors;
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].reason, "unknown version 42");
+
+        // The next reloading applies the file again.
+        let response = ctx.request_to(configurers_addr, ReloadConfigs::default());
+        response.resolve().await.unwrap().unwrap();
+
+        let value = ctx.request_to(testee_addr, GetValue).resolve().await;
+        assert_eq!(value.unwrap(), 2);
+    })
+    .await
+    .expect("cannot start");
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,83 @@
+#![allow(missing_docs)]
+#![cfg(feature = "test-util")]
+
+use std::fs;
+
+use serde::Deserialize;
+
+use elfo::{
+    _priv::do_start, batteries::configurer::ReloadConfigs, config::Secret, prelude::*, Topology,
+};
+
+mod common;
+
+#[message(ret = (String, String))]
+struct GetSecrets;
+
+#[derive(Debug, Clone, Deserialize)]
+struct Config {
+    from_file: Secret<String>,
+    from_env: Secret<String>,
+}
+
+fn testee() -> Blueprint {
+    ActorGroup::new()
+        .config::<Config>()
+        .exec(move |mut ctx| async move {
+            while let Some(envelope) = ctx.recv().await {
+                msg!(match envelope {
+                    (GetSecrets, token) => {
+                        let config = ctx.config();
+                        let from_file = String::clone(&config.from_file);
+                        let from_env = String::clone(&config.from_env);
+                        ctx.respond(token, (from_file, from_env));
+                    }
+                });
+            }
+        })
+}
+
+#[tokio::test]
+async fn rotation() {
+    common::setup_logger();
+
+    let dir = std::env::temp_dir().join(format!("elfo-config-secrets-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let secret = dir.join("secret");
+    fs::write(&secret, "foo\n").unwrap();
+    std::env::set_var("ELFO_CONFIG_SECRETS_TEST", "bar");
+
+    let config = format!(
+        "[testee]\nfrom_file = {{ file = {:?} }}\nfrom_env = {{ env = \"ELFO_CONFIG_SECRETS_TEST\" }}",
+        secret.display().to_string()
+    );
+    let path = dir.join("config.toml");
+    fs::write(&path, config).unwrap();
+
+    let topology = Topology::empty();
+    let configurers = topology.local("system.configurers").entrypoint();
+    let testee = topology.local("testee");
+    let configurers_addr = configurers.addr();
+    let testee_addr = testee.addr();
+
+    configurers.mount(elfo::batteries::configurer::from_path(&topology, &path));
+    testee.mount(self::testee());
+
+    do_start(topology, false, |ctx, _| async move {
+        let secrets = ctx.request_to(testee_addr, GetSecrets).resolve().await;
+        assert_eq!(secrets.unwrap(), ("foo".into(), "bar".into()));
+
+        // Rotated secrets are picked up even if the config file isn't changed.
+        fs::write(&secret, "baz").unwrap();
+        std::env::set_var("ELFO_CONFIG_SECRETS_TEST", "qux");
+        let response = ctx.request_to(configurers_addr, ReloadConfigs::default());
+        response.resolve().await.unwrap().unwrap();
+
+        let secrets = ctx.request_to(testee_addr, GetSecrets).resolve().await;
+        assert_eq!(secrets.unwrap(), ("baz".into(), "qux".into()));
+    })
+    .await
+    .expect("cannot start");
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,117 @@
+#![allow(missing_docs)]
+#![cfg(feature = "test-util")]
+
+use std::{fs, path::Path, time::Duration};
+
+use serde::Deserialize;
+
+use elfo::{
+    _priv::do_start,
+    messages::{ActorStatusReport, SubscribeToActorStatuses},
+    prelude::*,
+    ActorStatusKind, Topology,
+};
+
+mod common;
+
+#[message(ret = u32)]
+struct GetValue;
+
+#[derive(Debug, Clone, Deserialize)]
+struct Config {
+    value: u32,
+}
+
+fn testee() -> Blueprint {
+    ActorGroup::new()
+        .config::<Config>()
+        .exec(move |mut ctx| async move {
+            while let Some(envelope) = ctx.recv().await {
+                msg!(match envelope {
+                    (GetValue, token) => ctx.respond(token, ctx.config().value),
+                });
+            }
+        })
+}
+
+fn write_config(path: &Path, value: &str) {
+    let content = format!(
+        r#"
+        [system.configurers]
+        watch.enabled = true
+        watch.interval = "20ms"
+        watch.debounce = "50ms"
+
+        [testee]
+        value = {value}
+        "#
+    );
+
+    // Replace the file atomically, like Kubernetes does.
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, content).unwrap();
+    fs::rename(&tmp, path).unwrap();
+}
+
+async fn last_status(ctx: &mut Context) -> ActorStatusKind {
+    let mut last = None;
+
+    while let Ok(envelope) = ctx.try_recv().await {
+        msg!(match envelope {
+            ActorStatusReport { status, .. } => last = Some(status.kind()),
+        });
+    }
+
+    last.expect("no status reports")
+}
+
+// The file is polled using the real clock, so the test doesn't pause the time.
+#[tokio::test]
+async fn reload_on_change() {
+    common::setup_logger();
+
+    let dir = std::env::temp_dir().join(format!("elfo-config-watching-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("config.toml");
+    write_config(&path, "1");
+
+    let topology = Topology::empty();
+    let configurers = topology.local("system.configurers").entrypoint();
+    let testee = topology.local("testee");
+    let configurers_addr = configurers.addr();
+    let testee_addr = testee.addr();
+
+    configurers.mount(elfo::batteries::configurer::from_path(&topology, &path));
+    testee.mount(self::testee());
+
+    do_start(topology, false, |mut ctx, _| async move {
+        let value = ctx.request_to(testee_addr, GetValue).resolve().await;
+        assert_eq!(value.unwrap(), 1);
+
+        write_config(&path, "2");
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        let value = ctx.request_to(testee_addr, GetValue).resolve().await;
+        assert_eq!(value.unwrap(), 2);
+
+        // Invalid configs aren't applied, the configurer becomes alarming.
+        write_config(&path, "\"oops\"");
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        let value = ctx.request_to(testee_addr, GetValue).resolve().await;
+        assert_eq!(value.unwrap(), 2);
+
+        let subscribe = SubscribeToActorStatuses::default();
+        ctx.send_to(configurers_addr, subscribe).await.unwrap();
+        assert_eq!(last_status(&mut ctx).await, ActorStatusKind::Alarming);
+
+        // Fixing the file makes the configurer normal again.
+        write_config(&path, "3");
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        let value = ctx.request_to(testee_addr, GetValue).resolve().await;
+        assert_eq!(value.unwrap(), 3);
+        assert_eq!(last_status(&mut ctx).await, ActorStatusKind::Normal);
+    })
+    .await
+    .expect("cannot start");
+
+    fs::remove_dir_all(&dir).unwrap();
+}
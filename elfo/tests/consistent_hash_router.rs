@@ -0,0 +1,81 @@
+#![allow(missing_docs)]
+#![cfg(feature = "test-util")]
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use serde::Deserialize;
+use toml::toml;
+
+use elfo::{config::AnyConfig, messages::UpdateConfig, prelude::*, routers::ConsistentHashRouter};
+
+#[message(ret = usize)]
+struct WhoAmI(u32);
+
+#[derive(Debug, Clone, Deserialize)]
+struct Config {
+    shards: usize,
+}
+
+fn config(shards: usize) -> AnyConfig {
+    AnyConfig::deserialize(toml! {
+        shards = shards
+    })
+    .unwrap()
+}
+
+#[tokio::test]
+async fn it_works() {
+    let moved = Arc::new(AtomicUsize::new(0));
+    let moved_1 = moved.clone();
+
+    let hash_router = ConsistentHashRouter::new(|config: &Config| config.shards)
+        .key(|msg: &WhoAmI| msg.0)
+        .on_rebalance(move |rebalance| {
+            assert_eq!(rebalance.old_shards(), 4);
+            assert_eq!(rebalance.new_shards(), 5);
+
+            let count = (0..1000u32).filter(|key| rebalance.moved(key).is_some());
+            moved_1.store(count.count(), Ordering::SeqCst);
+        });
+
+    let blueprint = ActorGroup::new()
+        .config::<Config>()
+        .router(hash_router)
+        .exec(|mut ctx| async move {
+            while let Some(envelope) = ctx.recv().await {
+                msg!(match envelope {
+                    (WhoAmI(_), token) => ctx.respond(token, *ctx.key()),
+                });
+            }
+        });
+
+    let proxy = elfo::test::proxy(blueprint, config(4)).await;
+
+    let mut before = Vec::new();
+    for key in 0..1000 {
+        before.push(proxy.request(WhoAmI(key)).await);
+    }
+    for shard in 0..4 {
+        assert!(before.contains(&shard));
+    }
+    for key in 0..1000 {
+        assert_eq!(proxy.request(WhoAmI(key)).await, before[key as usize]);
+    }
+
+    proxy.send(UpdateConfig::new(config(5))).await;
+
+    let mut actual_moved = 0;
+    for key in 0..1000 {
+        let shard = proxy.request(WhoAmI(key)).await;
+        if shard != before[key as usize] {
+            assert_eq!(shard, 4);
+            actual_moved += 1;
+        }
+    }
+
+    assert!(actual_moved > 0 && actual_moved < 500, "{actual_moved}");
+    assert_eq!(moved.load(Ordering::SeqCst), actual_moved);
+}
@@ -0,0 +1,228 @@
+#![allow(missing_docs)]
+#![cfg(feature = "test-util")]
+
+use std::time::Duration;
+
+use serde::Deserialize;
+use toml::toml;
+
+use elfo::{
+    _priv::do_start,
+    config::AnyConfig,
+    messages::{ActorStatusReport, SubscribeToActorStatuses},
+    prelude::*,
+    ActorStatusKind, Envelope, Topology,
+};
+
+mod common;
+
+#[message(ret = u32)]
+struct GetValue;
+
+#[message]
+struct Emit(u32);
+
+#[message]
+struct Emitted(u32);
+
+#[message(ret = Vec<u32>)]
+struct GetEmitted;
+
+#[message]
+struct Freeze;
+
+#[derive(Debug, Clone, Deserialize)]
+struct Config {
+    value: u32,
+}
+
+fn plugin() -> Blueprint {
+    ActorGroup::new()
+        .config::<Config>()
+        .exec(move |mut ctx| async move {
+            while let Some(envelope) = ctx.recv().await {
+                msg!(match envelope {
+                    (GetValue, token) => ctx.respond(token, ctx.config().value),
+                    Emit(value) => {
+                        let _ = ctx.send(Emitted(value)).await;
+                    }
+                    Freeze => tokio::time::sleep(Duration::from_secs(5)).await,
+                });
+            }
+        })
+}
+
+fn sink() -> Blueprint {
+    ActorGroup::new().exec(move |mut ctx| async move {
+        let mut emitted = Vec::new();
+
+        while let Some(envelope) = ctx.recv().await {
+            msg!(match envelope {
+                Emitted(value) => emitted.push(value),
+                (GetEmitted, token) => ctx.respond(token, emitted.clone()),
+            });
+        }
+    })
+}
+
+fn group_names(topology: &Topology) -> Vec<String> {
+    topology.locals().map(|group| group.name).collect()
+}
+
+#[tokio::test(start_paused = true)]
+async fn mount_and_unmount() {
+    common::setup_logger();
+
+    let topology = Topology::empty();
+    let configurers = topology.local("system.configurers").entrypoint();
+
+    let config = AnyConfig::deserialize(toml! {
+        [plugin]
+        value = 42
+    })
+    .unwrap();
+
+    configurers.mount(elfo::batteries::configurer::fixture(&topology, config));
+
+    do_start(topology, false, |ctx, topology| async move {
+        assert_eq!(group_names(&topology), ["system.configurers"]);
+
+        let addr = topology.mount_dynamic("plugin", plugin());
+        assert_eq!(group_names(&topology), ["system.configurers", "plugin"]);
+        let group = topology.locals().find(|g| g.name == "plugin").unwrap();
+        assert!(group.is_dynamic);
+
+        // The configurer sends the config to the new group.
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let value = ctx.request_to(addr, GetValue).resolve().await;
+        assert_eq!(value.unwrap(), 42);
+
+        let report = topology.unmount("plugin").await;
+        assert!(report.is_clean());
+        assert_eq!(group_names(&topology), ["system.configurers"]);
+        assert!(ctx.request_to(addr, GetValue).resolve().await.is_err());
+
+        // The name can be used again.
+        let addr = topology.mount_dynamic("plugin", plugin());
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let value = ctx.request_to(addr, GetValue).resolve().await;
+        assert_eq!(value.unwrap(), 42);
+    })
+    .await
+    .expect("cannot start");
+}
+
+#[tokio::test(start_paused = true)]
+async fn routes_from_mounted_group() {
+    common::setup_logger();
+
+    let topology = Topology::empty();
+    let configurers = topology.local("system.configurers").entrypoint();
+    let sink = topology.local("sink");
+    let sink_addr = sink.addr();
+
+    let config = AnyConfig::deserialize(toml! {
+        [plugin]
+        value = 42
+    })
+    .unwrap();
+
+    configurers.mount(elfo::batteries::configurer::fixture(&topology, config));
+    sink.mount(self::sink());
+
+    do_start(topology, false, |ctx, topology| async move {
+        let sink = topology.locals().find(|g| g.name == "sink").unwrap();
+
+        let addr = topology.mount_dynamic_with("plugin", plugin(), |plugin| {
+            plugin.route_to(&sink, |envelope: &Envelope| {
+                msg!(match envelope {
+                    Emitted => true,
+                    _ => false,
+                })
+            });
+        });
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        ctx.send_to(addr, Emit(1)).await.unwrap();
+        ctx.send_to(addr, Emit(2)).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let emitted = ctx.request_to(sink_addr, GetEmitted).resolve().await;
+        assert_eq!(emitted.unwrap(), [1, 2]);
+
+        // The route is removed along with the group.
+        assert_eq!(topology.connections().count(), 1);
+        topology.unmount("plugin").await;
+        assert_eq!(topology.connections().count(), 0);
+    })
+    .await
+    .expect("cannot start");
+}
+
+#[tokio::test(start_paused = true)]
+async fn pinging_mounted_group() {
+    common::setup_logger();
+
+    let topology = Topology::empty();
+    let configurers = topology.local("system.configurers").entrypoint();
+    let pingers = topology.local("system.pingers");
+    let pingers_addr = pingers.addr();
+
+    let config = AnyConfig::deserialize(toml! {
+        [plugin]
+        value = 42
+
+        [system.pingers]
+        ping_interval = "1s"
+        warn_threshold = "500ms"
+    })
+    .unwrap();
+
+    configurers.mount(elfo::batteries::configurer::fixture(&topology, config));
+    pingers.mount(elfo::batteries::pinger::new(&topology));
+
+    do_start(topology, false, |mut ctx, topology| async move {
+        ctx.send_to(pingers_addr, SubscribeToActorStatuses::default())
+            .await
+            .unwrap();
+
+        let addr = topology.mount_dynamic("plugin", plugin());
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        // The pinger notices the stuck group.
+        ctx.send_to(addr, Freeze).await.unwrap();
+        tokio::time::sleep(Duration::from_secs(4)).await;
+
+        let mut statuses = Vec::new();
+        while let Ok(envelope) = ctx.try_recv().await {
+            msg!(match envelope {
+                ActorStatusReport { status, .. } => statuses.push(status.kind()),
+            });
+        }
+        assert!(statuses.contains(&ActorStatusKind::Alarming));
+    })
+    .await
+    .expect("cannot start");
+}
+
+#[tokio::test(start_paused = true)]
+#[should_panic(expected = "local group `static` isn't mounted dynamically")]
+async fn unmount_static() {
+    common::setup_logger();
+
+    let topology = Topology::empty();
+    let configurers = topology.local("system.configurers").entrypoint();
+    let static_ = topology.local("static");
+
+    configurers.mount(elfo::batteries::configurer::fixture(
+        &topology,
+        AnyConfig::default(),
+    ));
+    static_.mount(ActorGroup::new().exec(|_| async {}));
+
+    do_start(topology, false, |_, topology| async move {
+        topology.unmount("static").await;
+    })
+    .await
+    .expect("cannot start");
+}
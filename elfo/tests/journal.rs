@@ -0,0 +1,102 @@
+#![allow(missing_docs)]
+#![cfg(all(feature = "test-util", feature = "elfo-journal"))]
+
+use std::time::Duration;
+
+use serde::Deserialize;
+use toml::toml;
+
+use elfo::{
+    batteries::journal::Journal, config::AnyConfig, prelude::*, RestartParams, RestartPolicy,
+};
+
+#[message]
+struct Deposit(u64);
+
+#[message]
+struct Fail;
+
+#[message(ret = (u64, u64))]
+struct GetBalance;
+
+#[message]
+#[derive(Default)]
+struct Balance(u64);
+
+#[derive(Debug, Clone, Deserialize)]
+struct Config {
+    journal: elfo::batteries::journal::config::Config,
+}
+
+fn testee() -> Blueprint {
+    ActorGroup::new()
+        .config::<Config>()
+        .restart_policy(RestartPolicy::on_failure(RestartParams::new(
+            Duration::from_secs(1),
+            Duration::from_secs(1),
+        )))
+        .exec(|mut ctx| async move {
+            let mut journal = Journal::open(&ctx.config().journal).await?;
+            let snapshot = journal.load_snapshot::<Balance>().await?;
+            let mut balance = snapshot.unwrap_or_default();
+
+            let mut replay = journal.replay();
+            while let Some(envelope) = replay.next().await {
+                msg!(match envelope? {
+                    Deposit(amount) => balance.0 += amount,
+                });
+            }
+
+            while let Some(envelope) = ctx.recv().await {
+                journal.record(&envelope).await?;
+
+                msg!(match envelope {
+                    Deposit(amount) => {
+                        balance.0 += amount;
+
+                        if journal.records_since_snapshot() >= 3 {
+                            journal.save_snapshot(&balance).await?;
+                        }
+                    }
+                    (GetBalance, token) => {
+                        ctx.respond(token, (balance.0, ctx.start_info().restart_count));
+                    }
+                    Fail => anyhow::bail!("boom!"),
+                });
+            }
+
+            Ok(())
+        })
+}
+
+#[tokio::test(start_paused = true)]
+async fn replay_after_restart() {
+    let dir = std::env::temp_dir().join(format!("elfo-journal-test-{}", std::process::id()));
+    let path = dir.display().to_string();
+
+    let config = AnyConfig::deserialize(toml! {
+        [journal]
+        path = path
+        rules = [{ message = "Deposit" }]
+    })
+    .unwrap();
+
+    let proxy = elfo::test::proxy(testee(), config).await;
+
+    for amount in 1..=5 {
+        proxy.send(Deposit(amount)).await;
+    }
+    assert_eq!(proxy.request(GetBalance).await, (15, 0));
+
+    // Records after the snapshot are replayed.
+    proxy.send(Fail).await;
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    assert_eq!(proxy.request(GetBalance).await, (15, 1));
+
+    proxy.send(Deposit(10)).await;
+    proxy.send(Fail).await;
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    assert_eq!(proxy.request(GetBalance).await, (25, 2));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,94 @@
+#![allow(missing_docs)]
+#![cfg(feature = "test-util")]
+
+use std::fs;
+
+use serde::Deserialize;
+
+use elfo::{
+    _priv::do_start,
+    batteries::configurer::{Layers, ReloadConfigs},
+    prelude::*,
+    Topology,
+};
+
+mod common;
+
+#[message(ret = (u32, String))]
+struct GetConfig;
+
+#[derive(Debug, Clone, Deserialize)]
+struct Config {
+    value: u32,
+    name: String,
+}
+
+fn testee() -> Blueprint {
+    ActorGroup::new()
+        .config::<Config>()
+        .exec(move |mut ctx| async move {
+            while let Some(envelope) = ctx.recv().await {
+                msg!(match envelope {
+                    (GetConfig, token) => {
+                        let config = ctx.config();
+                        ctx.respond(token, (config.value, config.name.clone()));
+                    }
+                });
+            }
+        })
+}
+
+#[tokio::test]
+async fn layers() {
+    common::setup_logger();
+
+    let dir = std::env::temp_dir().join(format!("elfo-layered-configs-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+
+    let fragment = dir.join("fragment.toml");
+    let base = dir.join("config.toml");
+    let overlay = dir.join("config.prod.toml");
+
+    fs::write(&fragment, "[testee]\nvalue = 1\nname = \"fragment\"").unwrap();
+    fs::write(&base, "include = [\"fragment.toml\"]\n[testee]\nvalue = 2").unwrap();
+    fs::write(&overlay, "[testee]\nvalue = 3").unwrap();
+    std::env::set_var("ELFO_LAYERED_CONFIGS__TESTEE__NAME", "env");
+
+    let topology = Topology::empty();
+    let configurers = topology.local("system.configurers").entrypoint();
+    let testee = topology.local("testee");
+    let configurers_addr = configurers.addr();
+    let testee_addr = testee.addr();
+
+    let layers = Layers::new(&base)
+        .overlay(&overlay)
+        .env_overrides("ELFO_LAYERED_CONFIGS");
+
+    configurers.mount(elfo::batteries::configurer::from_layers(&topology, layers));
+    testee.mount(self::testee());
+
+    do_start(topology, false, |ctx, _| async move {
+        let config = ctx.request_to(testee_addr, GetConfig).resolve().await;
+        assert_eq!(config.unwrap(), (3, "env".into()));
+
+        // Errors contain layers providing values, the latest go first.
+        // Overridden values of the base file and the fragment aren't taken into account.
+        fs::write(&overlay, "[testee]\nvalue = -1").unwrap();
+        let response = ctx.request_to(configurers_addr, ReloadConfigs::default());
+        let errors = response.resolve().await.unwrap().unwrap_err().errors;
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].group, "testee");
+        assert_eq!(
+            errors[0].layers,
+            [
+                "ELFO_LAYERED_CONFIGS__TESTEE__NAME".into(),
+                overlay.display().to_string(),
+            ]
+        );
+    })
+    .await
+    .expect("cannot start");
+
+    fs::remove_dir_all(&dir).unwrap();
+}
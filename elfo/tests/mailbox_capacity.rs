@@ -6,11 +6,7 @@ use std::time::Duration;
 use serde::Deserialize;
 use toml::toml;
 
-use elfo::{
-    config::AnyConfig,
-    messages::{Ping, UpdateConfig},
-    prelude::*,
-};
+use elfo::{config::AnyConfig, messages::UpdateConfig, prelude::*};
 
 #[message]
 struct Dummy;
@@ -21,6 +17,10 @@ struct Freeze;
 #[message]
 struct SetCapacity(Option<usize>);
 
+// `Ping` isn't suitable here, because it's delivered via the high-priority lane.
+#[message(ret = ())]
+struct Flush;
+
 fn testee() -> Blueprint {
     ActorGroup::new().exec(move |mut ctx| async move {
         while let Some(envelope) = ctx.recv().await {
@@ -31,6 +31,7 @@ fn testee() -> Blueprint {
                     tokio::time::sleep(Duration::from_secs(60)).await
                 }
                 SetCapacity(capacity) => ctx.set_mailbox_capacity(capacity),
+                (Flush, token) => ctx.respond(token, ()),
             });
         }
     })
@@ -57,7 +58,7 @@ async fn config() {
         assert!(proxy.try_send(Dummy).is_err(), "should reject [{capacity}]");
 
         // Ensure that all sent messages are handled.
-        proxy.request(Ping::default()).await;
+        proxy.request(Flush).await;
     }
 }
 
@@ -76,7 +77,7 @@ async fn explicit_override() {
         assert!(proxy.try_send(Dummy).is_err(), "should reject [{capacity}]");
 
         // Ensure that all sent messages are handled.
-        proxy.request(Ping::default()).await;
+        proxy.request(Flush).await;
     }
 
     // Reset to the configured value.
@@ -0,0 +1,111 @@
+#![allow(missing_docs)]
+#![cfg(feature = "test-util")]
+
+use std::time::Duration;
+
+use serde::Deserialize;
+use toml::toml;
+
+use elfo::{config::AnyConfig, errors::RequestError, prelude::*};
+
+#[message]
+struct Number(u32);
+
+#[message(ret = ())]
+struct Freeze;
+
+#[message(ret = ())]
+struct Answer;
+
+#[message(ret = Vec<u32>)]
+struct GetLog;
+
+fn testee() -> Blueprint {
+    ActorGroup::new().exec(move |mut ctx| async move {
+        let mut log = Vec::new();
+
+        while let Some(envelope) = ctx.recv().await {
+            msg!(match envelope {
+                Number(no) => log.push(no),
+                (Freeze, token) => {
+                    ctx.respond(token, ());
+                    tokio::time::sleep(Duration::from_secs(60)).await
+                }
+                (Answer, token) => ctx.respond(token, ()),
+                (GetLog, token) => ctx.respond(token, std::mem::take(&mut log)),
+            });
+        }
+    })
+}
+
+fn testee_config(on_overflow: &str) -> AnyConfig {
+    AnyConfig::deserialize(toml! {
+        system.mailbox.capacity = 3
+        system.mailbox.on_overflow = on_overflow
+    })
+    .unwrap()
+}
+
+async fn fill(proxy: &elfo::test::Proxy) {
+    proxy.request(Freeze).await;
+
+    for no in 1..=5 {
+        proxy.send(Number(no)).await;
+    }
+    for no in 6..=8 {
+        assert!(proxy.try_send(Number(no)).is_ok());
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn drop_newest() {
+    let proxy = elfo::test::proxy(testee(), testee_config("DropNewest")).await;
+    fill(&proxy).await;
+
+    // `GetLog` itself is dropped while the actor is frozen.
+    let res = proxy.request_fallible(GetLog).await;
+    assert!(res.is_err());
+
+    // Wait until the actor is unfrozen and handles the mailbox.
+    tokio::time::sleep(Duration::from_secs(61)).await;
+    assert_eq!(proxy.request(GetLog).await, [1, 2, 3]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn drop_oldest() {
+    let proxy = elfo::test::proxy(testee(), testee_config("DropOldest")).await;
+    fill(&proxy).await;
+
+    assert_eq!(proxy.request(GetLog).await, [7, 8]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn reject() {
+    let proxy = elfo::test::proxy(testee(), testee_config("Reject")).await;
+    proxy.request(Freeze).await;
+
+    for no in 1..=3 {
+        assert!(proxy.try_send(Number(no)).is_ok());
+    }
+    assert!(proxy.try_send(Number(4)).unwrap_err().is_full());
+
+    // `send()` doesn't wait for free space.
+    let res = proxy.request_fallible(Answer).await;
+    assert!(matches!(res, Err(RequestError::Failed)), "{res:?}");
+
+    // Wait until the actor is unfrozen and handles the mailbox.
+    tokio::time::sleep(Duration::from_secs(61)).await;
+    assert_eq!(proxy.request(GetLog).await, [1, 2, 3]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn high_priority_lane_isnt_affected() {
+    let proxy = elfo::test::proxy(testee(), testee_config("DropNewest")).await;
+    fill(&proxy).await;
+
+    // `Ping` is delivered via the high-priority lane and blocks as usual.
+    for _ in 0..3 {
+        assert!(proxy.try_send(elfo::messages::Ping::default()).is_ok());
+    }
+    assert!(proxy.try_send(elfo::messages::Ping::default()).is_err());
+}
@@ -0,0 +1,83 @@
+#![allow(missing_docs)]
+#![cfg(feature = "test-util")]
+
+use std::time::Duration;
+
+use serde::Deserialize;
+use toml::toml;
+
+use elfo::{config::AnyConfig, prelude::*};
+
+#[message]
+struct Normal(u32);
+
+#[message(priority = "high")]
+struct Urgent(u32);
+
+#[message(ret = ())]
+struct Freeze;
+
+#[message(ret = Vec<String>)]
+struct GetLog;
+
+fn testee() -> Blueprint {
+    ActorGroup::new().exec(move |mut ctx| async move {
+        let mut log = Vec::new();
+
+        while let Some(envelope) = ctx.recv().await {
+            msg!(match envelope {
+                Normal(no) => log.push(format!("normal {no}")),
+                Urgent(no) => log.push(format!("urgent {no}")),
+                (Freeze, token) => {
+                    ctx.respond(token, ());
+                    tokio::time::sleep(Duration::from_secs(60)).await
+                }
+                (GetLog, token) => ctx.respond(token, std::mem::take(&mut log)),
+            });
+        }
+    })
+}
+
+fn testee_config(capacity: usize) -> AnyConfig {
+    AnyConfig::deserialize(toml! {
+        system.mailbox.capacity = capacity
+    })
+    .unwrap()
+}
+
+#[tokio::test(start_paused = true)]
+async fn high_priority_first() {
+    let proxy = elfo::test::proxy(testee(), testee_config(10)).await;
+
+    proxy.request(Freeze).await;
+    proxy.send(Normal(1)).await;
+    proxy.send(Normal(2)).await;
+    proxy.send(Urgent(1)).await;
+    proxy.send(Normal(3)).await;
+    proxy.send(Urgent(2)).await;
+
+    let log = proxy.request(GetLog).await;
+    assert_eq!(
+        log,
+        ["urgent 1", "urgent 2", "normal 1", "normal 2", "normal 3"]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn lanes_are_bounded_separately() {
+    let capacity = 5;
+    let proxy = elfo::test::proxy(testee(), testee_config(capacity)).await;
+
+    proxy.request(Freeze).await;
+
+    for no in 0..capacity as u32 {
+        assert!(proxy.try_send(Normal(no)).is_ok());
+    }
+    assert!(proxy.try_send(Normal(42)).is_err());
+
+    // The normal lane is full, but the high-priority one isn't.
+    for no in 0..capacity as u32 {
+        assert!(proxy.try_send(Urgent(no)).is_ok());
+    }
+    assert!(proxy.try_send(Urgent(42)).is_err());
+}
@@ -0,0 +1,103 @@
+#![allow(missing_docs)]
+#![cfg(feature = "test-util")]
+
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+use toml::toml;
+
+use elfo::{config::AnyConfig, prelude::*};
+
+#[message]
+struct Number(u32);
+
+#[message(ret = Vec<u32>)]
+struct GetLog;
+
+fn testee() -> Blueprint {
+    ActorGroup::new().exec(move |mut ctx| async move {
+        let mut log = Vec::new();
+
+        while let Some(envelope) = ctx.recv().await {
+            msg!(match envelope {
+                Number(no) => log.push(no),
+                (GetLog, token) => ctx.respond(token, std::mem::take(&mut log)),
+            });
+        }
+    })
+}
+
+// The rate limiter uses the real clock, so these tests don't pause the time.
+
+#[tokio::test]
+async fn drop_exceeding() {
+    let config = AnyConfig::deserialize(toml! {
+        system.mailbox.rate_limit.per_message = { Number = 5 }
+        system.mailbox.rate_limit.on_exceed = "Drop"
+    })
+    .unwrap();
+
+    let proxy = elfo::test::proxy(testee(), config).await;
+
+    for no in 1..=10 {
+        proxy.send(Number(no)).await;
+    }
+    for no in 11..=15 {
+        assert!(proxy.try_send(Number(no)).is_ok());
+    }
+
+    // Other messages aren't limited. The limiter can allow one extra message,
+    // because some time passes between sends.
+    let log = proxy.request(GetLog).await;
+    assert!(
+        log.starts_with(&[1, 2, 3, 4, 5]) && log.len() <= 6,
+        "{log:?}"
+    );
+}
+
+#[tokio::test]
+async fn throttle() {
+    let config = AnyConfig::deserialize(toml! {
+        system.mailbox.rate_limit.per_message = { Number = 10 }
+    })
+    .unwrap();
+
+    let proxy = elfo::test::proxy(testee(), config).await;
+    let started_at = Instant::now();
+
+    for no in 1..=15 {
+        proxy.send(Number(no)).await;
+    }
+
+    // The first 10 messages are sent immediately, others one per 100ms.
+    let elapsed = started_at.elapsed();
+    assert!(elapsed >= Duration::from_millis(400), "{elapsed:?}");
+    assert!(proxy.try_send(Number(16)).unwrap_err().is_full());
+
+    assert_eq!(proxy.request(GetLog).await, (1..=15).collect::<Vec<_>>());
+}
+
+#[tokio::test]
+async fn max_rate() {
+    let config = AnyConfig::deserialize(toml! {
+        system.mailbox.rate_limit.max_rate = 3
+        system.mailbox.rate_limit.on_exceed = "Drop"
+    })
+    .unwrap();
+
+    let proxy = elfo::test::proxy(testee(), config).await;
+
+    for no in 1..=5 {
+        proxy.send(Number(no)).await;
+    }
+
+    // `Ping` is delivered via the high-priority lane and isn't limited.
+    for _ in 0..3 {
+        assert!(proxy.try_send(elfo::messages::Ping::default()).is_ok());
+    }
+
+    // Wait until the limit allows sending `GetLog`.
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    let log = proxy.request(GetLog).await;
+    assert!(log.starts_with(&[1, 2, 3]) && log.len() <= 4, "{log:?}");
+}
@@ -0,0 +1,135 @@
+#![allow(missing_docs)]
+#![cfg(feature = "test-util")]
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use serde::Deserialize;
+use toml::toml;
+
+use elfo::{
+    config::AnyConfig,
+    messages::{ConfigUpdated, UpdateConfig},
+    prelude::*,
+    routers::{Dispatch, PoolRouter},
+};
+
+#[message]
+struct Record(u32);
+
+#[message]
+struct Freeze;
+
+#[derive(Debug, Clone, Deserialize)]
+struct Config {
+    workers: usize,
+}
+
+type Log = Arc<Mutex<Vec<(usize, u32)>>>;
+
+fn pool(dispatch: Dispatch, log: Log) -> Blueprint {
+    ActorGroup::new()
+        .config::<Config>()
+        .router(PoolRouter::new(|config: &Config| config.workers).dispatch(dispatch))
+        .exec(move |mut ctx| {
+            let log = log.clone();
+            async move {
+                while let Some(envelope) = ctx.recv().await {
+                    msg!(match envelope {
+                        Record(no) => log.lock().unwrap().push((*ctx.key(), no)),
+                        Freeze => tokio::time::sleep(Duration::from_secs(60)).await,
+                        ConfigUpdated => {
+                            let workers = ctx.config().workers as u32;
+                            log.lock().unwrap().push((*ctx.key(), 1000 + workers));
+                        }
+                    });
+                }
+            }
+        })
+}
+
+fn config(workers: usize) -> AnyConfig {
+    AnyConfig::deserialize(toml! {
+        workers = workers
+    })
+    .unwrap()
+}
+
+async fn start(dispatch: Dispatch) -> (elfo::test::Proxy, Log) {
+    let log = Log::default();
+    let proxy = elfo::test::proxy(pool(dispatch, log.clone()), config(3)).await;
+    (proxy, log)
+}
+
+async fn collect(log: Log) -> Vec<(usize, u32)> {
+    // Wait until all messages are handled.
+    tokio::time::sleep(Duration::from_secs(61)).await;
+
+    let mut log = log.lock().unwrap().clone();
+    log.sort_by_key(|(_, no)| *no);
+    log
+}
+
+#[tokio::test(start_paused = true)]
+async fn round_robin() {
+    let (proxy, log) = start(Dispatch::RoundRobin).await;
+
+    for no in 0..6 {
+        proxy.send(Record(no)).await;
+    }
+
+    let log = collect(log).await;
+    assert_eq!(log, [(0, 0), (1, 1), (2, 2), (0, 3), (1, 4), (2, 5)]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn random() {
+    let (proxy, log) = start(Dispatch::Random).await;
+
+    for no in 0..100 {
+        proxy.send(Record(no)).await;
+    }
+
+    let log = collect(log).await;
+    assert_eq!(log.len(), 100);
+    for key in 0..3 {
+        assert!(log.iter().any(|(k, _)| *k == key));
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn least_loaded() {
+    let (proxy, log) = start(Dispatch::LeastLoaded).await;
+
+    // All mailboxes are empty, so the first member is chosen.
+    proxy.send(Freeze).await;
+    tokio::time::sleep(Duration::from_millis(1)).await;
+
+    for no in 1..=5 {
+        proxy.send(Record(no)).await;
+    }
+
+    let log = collect(log).await;
+    assert_eq!(log, [(0, 1), (1, 2), (2, 3), (0, 4), (1, 5)]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn shrinking() {
+    let (proxy, log) = start(Dispatch::RoundRobin).await;
+
+    proxy.send(UpdateConfig::new(config(2))).await;
+    for no in 0..4 {
+        proxy.send(Record(no)).await;
+    }
+
+    // The extra member doesn't receive messages, but its config is updated.
+    let (updates, records): (Vec<_>, Vec<_>) = collect(log)
+        .await
+        .into_iter()
+        .partition(|(_, no)| *no >= 1000);
+    assert_eq!(records, [(0, 0), (1, 1), (0, 2), (1, 3)]);
+    assert_eq!(updates.len(), 3);
+    assert!((0..3).all(|key| updates.contains(&(key, 1002))));
+}
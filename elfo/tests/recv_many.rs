@@ -0,0 +1,98 @@
+#![allow(missing_docs)]
+#![cfg(feature = "test-util")]
+
+use std::time::Duration;
+
+use serde::Deserialize;
+use toml::toml;
+
+use elfo::{
+    config::AnyConfig,
+    messages::{ConfigUpdated, Terminate, UpdateConfig},
+    prelude::*,
+};
+
+#[message]
+struct Number(u32);
+
+#[message(ret = ())]
+struct Freeze;
+
+#[message(ret = Vec<Vec<String>>)]
+struct GetBatches;
+
+#[derive(Debug, Clone, Deserialize)]
+struct Config {
+    #[serde(default)]
+    tag: u32,
+}
+
+fn testee(limit: usize) -> Blueprint {
+    ActorGroup::new()
+        .config::<Config>()
+        .exec(move |mut ctx| async move {
+            let mut batches = Vec::new();
+            let mut buffer = Vec::new();
+
+            while ctx.recv_many(&mut buffer, limit).await > 0 {
+                assert!(buffer.len() <= limit);
+
+                let mut batch = Vec::new();
+                for envelope in buffer.drain(..) {
+                    msg!(match envelope {
+                        Number(no) => batch.push(format!("{no}")),
+                        ConfigUpdated => batch.push(format!("tag {}", ctx.config().tag)),
+                        (Freeze, token) => {
+                            ctx.respond(token, ());
+                            tokio::time::sleep(Duration::from_secs(60)).await;
+                        }
+                        (GetBatches, token) => {
+                            ctx.respond(token, std::mem::take(&mut batches));
+                        }
+                    });
+                }
+
+                if !batch.is_empty() {
+                    batches.push(batch);
+                }
+            }
+        })
+}
+
+fn config(tag: u32) -> AnyConfig {
+    AnyConfig::deserialize(toml! {
+        tag = tag
+    })
+    .unwrap()
+}
+
+#[tokio::test(start_paused = true)]
+async fn batches() {
+    let proxy = elfo::test::proxy(testee(3), config(0)).await;
+
+    proxy.request(Freeze).await;
+    for no in 1..=4 {
+        proxy.send(Number(no)).await;
+    }
+    proxy.send(UpdateConfig::new(config(42))).await;
+    proxy.send(Number(5)).await;
+
+    let batches = proxy.request(GetBatches).await;
+    assert_eq!(
+        batches,
+        [vec!["tag 42", "1", "2"], vec!["3", "4", "5"]],
+        "{batches:?}"
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn terminate() {
+    let proxy = elfo::test::proxy(testee(10), config(0)).await;
+
+    proxy.request(Freeze).await;
+    proxy.send(Number(1)).await;
+    proxy.send(Terminate::default()).await;
+
+    // `Terminate` closes the mailbox, so `recv_many()` returns `0` eventually.
+    proxy.finished().await;
+}
@@ -9,12 +9,13 @@ use toml::toml;
 use tracing::{debug, info};
 
 use elfo::{
-    messages::UpdateConfig,
+    messages::{StreamEnded, UpdateConfig},
     prelude::*,
     routers::{MapRouter, Outcome, Singleton},
     stream::Stream,
     time::Interval,
-    topology, Topology,
+    topology::{self, NodeDiscovery},
+    Envelope, Topology,
 };
 
 mod common;
@@ -94,7 +95,9 @@ fn simple() {
         let producers = topology.local("producers");
         let consumers = topology.remote("consumers");
 
-        producers.route_to(&consumers, |_, _| topology::Outcome::Broadcast);
+        producers.route_to(&consumers, |_: &Envelope, _: &NodeDiscovery| {
+            topology::Outcome::Broadcast
+        });
 
         network.mount(elfo::batteries::network::new(&topology));
         configurers.mount(elfo::batteries::configurer::fixture(
@@ -280,7 +283,9 @@ fn remote_actor_terminates_without_waiting_for_response() {
         let producers = topology.local("producers");
         let consumers = topology.remote("consumers");
 
-        producers.route_to(&consumers, |_, _| topology::Outcome::Broadcast);
+        producers.route_to(&consumers, |_: &Envelope, _: &NodeDiscovery| {
+            topology::Outcome::Broadcast
+        });
 
         network.mount(elfo::batteries::network::new(&topology));
         configurers.mount(elfo::batteries::configurer::fixture(
@@ -304,3 +309,553 @@ fn remote_actor_terminates_without_waiting_for_response() {
 
     sim.run().unwrap();
 }
+
+#[test]
+fn streaming_response() {
+    common::setup_logger();
+
+    #[message(ret = stream Item)]
+    struct ListItems;
+
+    #[message]
+    struct Item(u32);
+
+    fn client(notify: Arc<Notify>) -> Blueprint {
+        ActorGroup::new()
+            .router(MapRouter::new(|e| {
+                msg!(match e {
+                    UpdateConfig => Outcome::Unicast(Singleton),
+                    _ => Outcome::Discard,
+                })
+            }))
+            .exec(move |mut ctx| {
+                let notify = notify.clone();
+                async move {
+                    ctx.attach(ctx.request(ListItems).stream());
+
+                    let mut items = vec![];
+                    while let Some(envelope) = ctx.recv().await {
+                        msg!(match envelope {
+                            Item(no) => items.push(no),
+                            StreamEnded { is_complete, .. } => {
+                                if is_complete {
+                                    break;
+                                }
+
+                                // The server isn't discovered yet, try again.
+                                debug!("failed to request items");
+                                assert!(items.is_empty());
+                                tokio::time::sleep(Duration::from_millis(100)).await;
+                                ctx.attach(ctx.request(ListItems).stream());
+                            }
+                        })
+                    }
+
+                    assert_eq!(items, (0..10).collect::<Vec<_>>());
+
+                    // Terminate the test.
+                    // TODO: expose `system.init` and use `send(TerminateSystem)` instead.
+                    notify.notify_one();
+                }
+            })
+    }
+
+    fn server(notify: Arc<Notify>) -> Blueprint {
+        ActorGroup::new()
+            .router(MapRouter::new(|e| {
+                msg!(match e {
+                    ListItems => Outcome::Unicast(Singleton),
+                    _ => Outcome::Discard,
+                })
+            }))
+            .exec(move |mut ctx| {
+                let notify = notify.clone();
+                async move {
+                    while let Some(envelope) = ctx.recv().await {
+                        msg!(match envelope {
+                            (ListItems, token) => {
+                                let mut sink = ctx.response_sink(token).chunk_size(3);
+                                for no in 0..10 {
+                                    sink.send(Item(no)).await.unwrap();
+                                }
+                                sink.close().await.unwrap();
+                                break;
+                            }
+                        });
+                    }
+
+                    // Terminate the test.
+                    notify.notify_one();
+                }
+            })
+    }
+
+    let mut sim = turmoil::Builder::new()
+        .enable_tokio_io()
+        .tick_duration(Duration::from_millis(100))
+        .build();
+
+    sim.client("server", async {
+        let topology = Topology::empty();
+        let configurers = topology.local("system.configurers").entrypoint();
+        let network = topology.local("system.network");
+        let servers = topology.local("servers");
+
+        network.mount(elfo::batteries::network::new(&topology));
+        configurers.mount(elfo::batteries::configurer::fixture(
+            &topology,
+            toml! {
+                [system.network]
+                listen = ["turmoil06://0.0.0.0"]
+                ping_interval = "1s"
+                idle_timeout = "1s"
+            },
+        ));
+
+        let notify = Arc::new(Notify::new());
+        servers.mount(server(notify.clone()));
+
+        Ok(elfo::_priv::do_start(topology, false, |_, _| async move {
+            notify.notified().await;
+        })
+        .await?)
+    });
+
+    sim.client("client", async {
+        let topology = Topology::empty();
+        let configurers = topology.local("system.configurers").entrypoint();
+        let network = topology.local("system.network");
+        let clients = topology.local("clients");
+        let servers = topology.remote("servers");
+
+        clients.route_to(&servers, |_: &Envelope, _: &NodeDiscovery| {
+            topology::Outcome::Broadcast
+        });
+
+        network.mount(elfo::batteries::network::new(&topology));
+        configurers.mount(elfo::batteries::configurer::fixture(
+            &topology,
+            toml! {
+                [system.network]
+                discovery.predefined = ["turmoil06://server"]
+                ping_interval = "1s"
+                idle_timeout = "1s"
+            },
+        ));
+
+        let notify = Arc::new(Notify::new());
+        clients.mount(client(notify.clone()));
+
+        Ok(elfo::_priv::do_start(topology, false, |_, _| async move {
+            notify.notified().await;
+        })
+        .await?)
+    });
+
+    sim.run().unwrap();
+}
+
+#[test]
+fn remote_request_is_cancelled() {
+    common::setup_logger();
+
+    #[message(ret = ())]
+    struct Work;
+
+    fn client(notify: Arc<Notify>) -> Blueprint {
+        ActorGroup::new()
+            .router(MapRouter::new(|e| {
+                msg!(match e {
+                    UpdateConfig => Outcome::Unicast(Singleton),
+                    _ => Outcome::Discard,
+                })
+            }))
+            .exec(move |ctx| {
+                let notify = notify.clone();
+                async move {
+                    loop {
+                        let request = ctx.request(Work).resolve();
+                        match tokio::time::timeout(Duration::from_secs(1), request).await {
+                            // The request is dropped, so it should be cancelled.
+                            Err(_elapsed) => break,
+                            // The server isn't discovered yet, try again.
+                            Ok(res) => {
+                                debug!("failed to request: {res:?}");
+                                tokio::time::sleep(Duration::from_millis(100)).await;
+                            }
+                        }
+                    }
+
+                    // Terminate the test.
+                    // TODO: expose `system.init` and use `send(TerminateSystem)` instead.
+                    notify.notify_one();
+                }
+            })
+    }
+
+    fn server(notify: Arc<Notify>) -> Blueprint {
+        ActorGroup::new()
+            .router(MapRouter::new(|e| {
+                msg!(match e {
+                    Work => Outcome::Unicast(Singleton),
+                    _ => Outcome::Discard,
+                })
+            }))
+            .exec(move |mut ctx| {
+                let notify = notify.clone();
+                async move {
+                    while let Some(envelope) = ctx.recv().await {
+                        msg!(match envelope {
+                            (Work, token) => {
+                                token.cancelled().await;
+                                assert!(token.is_cancelled());
+                                break;
+                            }
+                        });
+                    }
+
+                    // Terminate the test.
+                    notify.notify_one();
+                }
+            })
+    }
+
+    let mut sim = turmoil::Builder::new()
+        .enable_tokio_io()
+        .tick_duration(Duration::from_millis(100))
+        .build();
+
+    sim.client("server", async {
+        let topology = Topology::empty();
+        let configurers = topology.local("system.configurers").entrypoint();
+        let network = topology.local("system.network");
+        let servers = topology.local("servers");
+
+        network.mount(elfo::batteries::network::new(&topology));
+        configurers.mount(elfo::batteries::configurer::fixture(
+            &topology,
+            toml! {
+                [system.network]
+                listen = ["turmoil06://0.0.0.0"]
+                ping_interval = "1s"
+                idle_timeout = "1s"
+            },
+        ));
+
+        let notify = Arc::new(Notify::new());
+        servers.mount(server(notify.clone()));
+
+        Ok(elfo::_priv::do_start(topology, false, |_, _| async move {
+            notify.notified().await;
+        })
+        .await?)
+    });
+
+    sim.client("client", async {
+        let topology = Topology::empty();
+        let configurers = topology.local("system.configurers").entrypoint();
+        let network = topology.local("system.network");
+        let clients = topology.local("clients");
+        let servers = topology.remote("servers");
+
+        clients.route_to(&servers, |_: &Envelope, _: &NodeDiscovery| {
+            topology::Outcome::Broadcast
+        });
+
+        network.mount(elfo::batteries::network::new(&topology));
+        configurers.mount(elfo::batteries::configurer::fixture(
+            &topology,
+            toml! {
+                [system.network]
+                discovery.predefined = ["turmoil06://server"]
+                ping_interval = "1s"
+                idle_timeout = "1s"
+            },
+        ));
+
+        let notify = Arc::new(Notify::new());
+        clients.mount(client(notify.clone()));
+
+        Ok(elfo::_priv::do_start(topology, false, |_, _| async move {
+            notify.notified().await;
+        })
+        .await?)
+    });
+
+    sim.run().unwrap();
+}
+
+#[test]
+fn remote_request_to_several_nodes_is_cancelled() {
+    common::setup_logger();
+
+    #[message(ret = ())]
+    struct IsReady;
+
+    #[message(ret = ())]
+    struct LongWork;
+
+    fn client(notify: Arc<Notify>) -> Blueprint {
+        ActorGroup::new()
+            .router(MapRouter::new(|e| {
+                msg!(match e {
+                    UpdateConfig => Outcome::Unicast(Singleton),
+                    _ => Outcome::Discard,
+                })
+            }))
+            .exec(move |ctx| {
+                let notify = notify.clone();
+                async move {
+                    // Wait until both servers are discovered.
+                    loop {
+                        let responses = ctx.request(IsReady).all().resolve().await;
+                        if responses.iter().filter(|res| res.is_ok()).count() == 2 {
+                            break;
+                        }
+
+                        debug!("not all servers are ready: {responses:?}");
+                        tokio::time::sleep(Duration::from_millis(100)).await;
+                    }
+
+                    // The request is dropped, so it should be cancelled on both nodes.
+                    let request = ctx.request(LongWork).all().resolve();
+                    let res = tokio::time::timeout(Duration::from_secs(1), request).await;
+                    assert!(res.is_err());
+
+                    // Keep connections until servers are notified.
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+
+                    // Terminate the test.
+                    // TODO: expose `system.init` and use `send(TerminateSystem)` instead.
+                    notify.notify_one();
+                }
+            })
+    }
+
+    fn server(notify: Arc<Notify>) -> Blueprint {
+        ActorGroup::new()
+            .router(MapRouter::new(|e| {
+                msg!(match e {
+                    IsReady | LongWork => Outcome::Unicast(Singleton),
+                    _ => Outcome::Discard,
+                })
+            }))
+            .exec(move |mut ctx| {
+                let notify = notify.clone();
+                async move {
+                    while let Some(envelope) = ctx.recv().await {
+                        msg!(match envelope {
+                            (IsReady, token) => ctx.respond(token, ()),
+                            (LongWork, token) => {
+                                token.cancelled().await;
+                                assert!(token.is_cancelled());
+                                break;
+                            }
+                        });
+                    }
+
+                    // Terminate the test.
+                    notify.notify_one();
+                }
+            })
+    }
+
+    let mut sim = turmoil::Builder::new()
+        .enable_tokio_io()
+        .tick_duration(Duration::from_millis(100))
+        .build();
+
+    for host in ["server1", "server2"] {
+        sim.client(host, async {
+            let topology = Topology::empty();
+            let configurers = topology.local("system.configurers").entrypoint();
+            let network = topology.local("system.network");
+            let servers = topology.local("servers");
+
+            network.mount(elfo::batteries::network::new(&topology));
+            configurers.mount(elfo::batteries::configurer::fixture(
+                &topology,
+                toml! {
+                    [system.network]
+                    listen = ["turmoil06://0.0.0.0"]
+                    ping_interval = "1s"
+                    idle_timeout = "1s"
+                },
+            ));
+
+            let notify = Arc::new(Notify::new());
+            servers.mount(server(notify.clone()));
+
+            Ok(elfo::_priv::do_start(topology, false, |_, _| async move {
+                notify.notified().await;
+            })
+            .await?)
+        });
+    }
+
+    sim.client("client", async {
+        let topology = Topology::empty();
+        let configurers = topology.local("system.configurers").entrypoint();
+        let network = topology.local("system.network");
+        let clients = topology.local("clients");
+        let servers = topology.remote("servers");
+
+        clients.route_to(&servers, |_: &Envelope, _: &NodeDiscovery| {
+            topology::Outcome::Broadcast
+        });
+
+        network.mount(elfo::batteries::network::new(&topology));
+        configurers.mount(elfo::batteries::configurer::fixture(
+            &topology,
+            toml! {
+                [system.network]
+                discovery.predefined = ["turmoil06://server1", "turmoil06://server2"]
+                ping_interval = "1s"
+                idle_timeout = "1s"
+            },
+        ));
+
+        let notify = Arc::new(Notify::new());
+        clients.mount(client(notify.clone()));
+
+        Ok(elfo::_priv::do_start(topology, false, |_, _| async move {
+            notify.notified().await;
+        })
+        .await?)
+    });
+
+    sim.run().unwrap();
+}
+
+#[test]
+fn remote_quorum() {
+    common::setup_logger();
+
+    #[message(ret = u32)]
+    struct Read;
+
+    fn client(notify: Arc<Notify>) -> Blueprint {
+        ActorGroup::new()
+            .router(MapRouter::new(|e| {
+                msg!(match e {
+                    UpdateConfig => Outcome::Unicast(Singleton),
+                    _ => Outcome::Discard,
+                })
+            }))
+            .exec(move |ctx| {
+                let notify = notify.clone();
+                async move {
+                    let mut responses = loop {
+                        match ctx.request(Read).all().quorum(2).resolve().await {
+                            Ok(responses) => break responses,
+                            // The server isn't discovered yet, try again.
+                            Err(err) => {
+                                debug!("failed to request: {err:?}");
+                                tokio::time::sleep(Duration::from_millis(100)).await;
+                            }
+                        }
+                    };
+
+                    responses.sort();
+                    assert_eq!(responses, [0, 1]);
+
+                    // Terminate the test.
+                    // TODO: expose `system.init` and use `send(TerminateSystem)` instead.
+                    notify.notify_one();
+                }
+            })
+    }
+
+    fn server(notify: Arc<Notify>) -> Blueprint {
+        ActorGroup::new()
+            .router(MapRouter::new(|e| {
+                msg!(match e {
+                    Read => Outcome::Multicast(vec![0, 1, 2]),
+                    _ => Outcome::Discard,
+                })
+            }))
+            .exec(move |mut ctx| {
+                let notify = notify.clone();
+                async move {
+                    let key = *ctx.key();
+
+                    while let Some(envelope) = ctx.recv().await {
+                        msg!(match envelope {
+                            (Read, token) if key < 2 => ctx.respond(token, key),
+                            // The quorum is reached without this replica.
+                            (Read, token) => {
+                                token.cancelled().await;
+
+                                // Terminate the test.
+                                notify.notify_one();
+                            }
+                        });
+                    }
+                }
+            })
+    }
+
+    let mut sim = turmoil::Builder::new()
+        .enable_tokio_io()
+        .tick_duration(Duration::from_millis(100))
+        .build();
+
+    sim.client("server", async {
+        let topology = Topology::empty();
+        let configurers = topology.local("system.configurers").entrypoint();
+        let network = topology.local("system.network");
+        let servers = topology.local("servers");
+
+        network.mount(elfo::batteries::network::new(&topology));
+        configurers.mount(elfo::batteries::configurer::fixture(
+            &topology,
+            toml! {
+                [system.network]
+                listen = ["turmoil06://0.0.0.0"]
+                ping_interval = "1s"
+                idle_timeout = "1s"
+            },
+        ));
+
+        let notify = Arc::new(Notify::new());
+        servers.mount(server(notify.clone()));
+
+        Ok(elfo::_priv::do_start(topology, false, |_, _| async move {
+            notify.notified().await;
+        })
+        .await?)
+    });
+
+    sim.client("client", async {
+        let topology = Topology::empty();
+        let configurers = topology.local("system.configurers").entrypoint();
+        let network = topology.local("system.network");
+        let clients = topology.local("clients");
+        let servers = topology.remote("servers");
+
+        clients.route_to(&servers, |_: &Envelope, _: &NodeDiscovery| {
+            topology::Outcome::Broadcast
+        });
+
+        network.mount(elfo::batteries::network::new(&topology));
+        configurers.mount(elfo::batteries::configurer::fixture(
+            &topology,
+            toml! {
+                [system.network]
+                discovery.predefined = ["turmoil06://server"]
+                ping_interval = "1s"
+                idle_timeout = "1s"
+            },
+        ));
+
+        let notify = Arc::new(Notify::new());
+        clients.mount(client(notify.clone()));
+
+        Ok(elfo::_priv::do_start(topology, false, |_, _| async move {
+            notify.notified().await;
+        })
+        .await?)
+    });
+
+    sim.run().unwrap();
+}
@@ -0,0 +1,101 @@
+#![allow(missing_docs)]
+#![cfg(feature = "test-util")]
+
+use std::{future::Future, time::Duration};
+
+use elfo::{config::AnyConfig, prelude::*, Addr, Context, Topology, _priv::do_start};
+
+mod common;
+
+#[message(ret = u32)]
+struct Work;
+
+#[message]
+struct Block;
+
+#[message(ret = (u32, u32))]
+struct GetStats;
+
+const TIMEOUT: Duration = Duration::from_secs(1);
+
+fn responder() -> Blueprint {
+    ActorGroup::new().exec(move |mut ctx| async move {
+        let mut handled = 0;
+        let mut cancelled = 0;
+
+        while let Some(envelope) = ctx.recv().await {
+            msg!(match envelope {
+                (Work, token) => {
+                    handled += 1;
+
+                    let is_cancelled = tokio::select! {
+                        _ = token.cancelled() => true,
+                        _ = tokio::time::sleep(10 * TIMEOUT) => false,
+                    };
+
+                    if is_cancelled {
+                        assert!(token.is_cancelled());
+                        cancelled += 1;
+                    } else {
+                        ctx.respond(token, 42);
+                    }
+                }
+                Block => tokio::time::sleep(10 * TIMEOUT).await,
+                (GetStats, token) => ctx.respond(token, (handled, cancelled)),
+            });
+        }
+    })
+}
+
+async fn run<F: Future<Output = ()>>(f: impl FnOnce(Context, Addr) -> F) {
+    common::setup_logger();
+
+    let topology = Topology::empty();
+    let configurers = topology.local("system.configurers").entrypoint();
+    let responders = topology.local("responders");
+    let responder_addr = responders.addr();
+
+    configurers.mount(elfo::batteries::configurer::fixture(
+        &topology,
+        AnyConfig::default(),
+    ));
+    responders.mount(responder());
+
+    do_start(topology, false, |ctx, _| f(ctx, responder_addr))
+        .await
+        .expect("cannot start");
+}
+
+#[tokio::test(start_paused = true)]
+async fn dropped_request_is_cancelled() {
+    run(|ctx, addr| async move {
+        let res = tokio::time::timeout(TIMEOUT, ctx.request_to(addr, Work).resolve()).await;
+        assert!(res.is_err());
+
+        let stats = ctx.request_to(addr, GetStats).resolve().await.unwrap();
+        assert_eq!(stats, (1, 1));
+
+        // Completed requests aren't cancelled.
+        let res = ctx.request_to(addr, Work).resolve().await;
+        assert_eq!(res.unwrap(), 42);
+
+        let stats = ctx.request_to(addr, GetStats).resolve().await.unwrap();
+        assert_eq!(stats, (2, 1));
+    })
+    .await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn cancelled_requests_are_skipped() {
+    run(|ctx, addr| async move {
+        ctx.send_to(addr, Block).await.unwrap();
+
+        let res = tokio::time::timeout(TIMEOUT, ctx.request_to(addr, Work).resolve()).await;
+        assert!(res.is_err());
+
+        // The cancelled request hasn't reached the actor.
+        let stats = ctx.request_to(addr, GetStats).resolve().await.unwrap();
+        assert_eq!(stats, (0, 0));
+    })
+    .await;
+}
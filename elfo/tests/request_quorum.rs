@@ -0,0 +1,137 @@
+#![allow(missing_docs)]
+#![cfg(feature = "test-util")]
+
+use std::{
+    future::Future,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use elfo::{
+    config::AnyConfig,
+    errors::RequestError,
+    prelude::*,
+    routers::{MapRouter, Outcome},
+    Addr, Context, Topology,
+    _priv::do_start,
+};
+
+mod common;
+
+/// Replicas respond in order of their keys, `0` fails.
+#[message(ret = u32)]
+struct Read;
+
+const REPLICAS: u32 = 4;
+const DELAY: Duration = Duration::from_millis(100);
+
+type Log = Arc<Mutex<Vec<String>>>;
+
+fn replicas(log: Log) -> Blueprint {
+    ActorGroup::new()
+        .router(MapRouter::new(|envelope| {
+            msg!(match envelope {
+                Read => Outcome::Multicast((0..REPLICAS).collect()),
+                _ => Outcome::Default,
+            })
+        }))
+        .exec(move |mut ctx| {
+            let log = log.clone();
+            async move {
+                let key = *ctx.key();
+
+                while let Some(envelope) = ctx.recv().await {
+                    msg!(match envelope {
+                        (Read, token) => {
+                            let is_cancelled = tokio::select! {
+                                _ = token.cancelled() => true,
+                                _ = tokio::time::sleep(DELAY * (key + 1)) => false,
+                            };
+
+                            if is_cancelled {
+                                log.lock().unwrap().push(format!("{key} cancelled"));
+                            } else if key > 0 {
+                                ctx.respond(token, key);
+                            }
+                        }
+                    });
+                }
+            }
+        })
+}
+
+async fn run<F: Future<Output = ()>>(f: impl FnOnce(Context, Addr) -> F) -> Vec<String> {
+    common::setup_logger();
+
+    let log = Log::default();
+    let topology = Topology::empty();
+    let configurers = topology.local("system.configurers").entrypoint();
+    let replicas = topology.local("replicas");
+    let replicas_addr = replicas.addr();
+
+    configurers.mount(elfo::batteries::configurer::fixture(
+        &topology,
+        AnyConfig::default(),
+    ));
+    replicas.mount(self::replicas(log.clone()));
+
+    do_start(topology, false, |ctx, _| async move {
+        // Start all replicas.
+        ctx.request_to(replicas_addr, Read).all().resolve().await;
+        f(ctx, replicas_addr).await;
+        tokio::time::sleep(DELAY * (REPLICAS + 1)).await;
+    })
+    .await
+    .expect("cannot start");
+
+    let mut log = log.lock().unwrap().clone();
+    log.sort();
+    log
+}
+
+#[tokio::test(start_paused = true)]
+async fn quorum() {
+    let log = run(|ctx, addr| async move {
+        let res = ctx.request_to(addr, Read).all().quorum(2).resolve().await;
+        assert_eq!(res.unwrap(), [1, 2]);
+    })
+    .await;
+
+    assert_eq!(log, ["3 cancelled"]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn first_ok() {
+    let log = run(|ctx, addr| async move {
+        let res = ctx.request_to(addr, Read).all().first_ok().resolve().await;
+        assert_eq!(res.unwrap(), 1);
+    })
+    .await;
+
+    assert_eq!(log, ["2 cancelled", "3 cancelled"]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn unreachable_quorum() {
+    let log = run(|ctx, addr| async move {
+        let res = ctx
+            .request_to(addr, Read)
+            .all()
+            .quorum(REPLICAS as usize)
+            .resolve()
+            .await;
+        assert!(matches!(res, Err(RequestError::Failed)), "{res:?}");
+
+        let res = ctx
+            .request_to(addr, Read)
+            .all()
+            .quorum(3)
+            .timeout(DELAY * 3)
+            .resolve()
+            .await;
+        assert!(matches!(res, Err(RequestError::Timeout)), "{res:?}");
+    })
+    .await;
+
+    assert!(log.is_empty(), "{log:?}");
+}
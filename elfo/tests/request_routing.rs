@@ -8,7 +8,7 @@ use tracing::info;
 use elfo::{
     prelude::*,
     routers::{MapRouter, Outcome},
-    RestartParams, RestartPolicy, Topology,
+    Envelope, RestartParams, RestartPolicy, Topology,
     _priv::do_start,
 };
 use elfo_core::config::AnyConfig;
@@ -67,7 +67,7 @@ async fn stealing() {
     let thief = topology.local("thief");
 
     requester.route_all_to(&thief);
-    requester.route_to(&responder, |e| {
+    requester.route_to(&responder, |e: &Envelope| {
         msg!(match e {
             TestRequest => true,
             _ => false,
@@ -0,0 +1,144 @@
+#![allow(missing_docs)]
+#![cfg(feature = "test-util")]
+
+use std::{future::Future, time::Duration};
+
+use elfo::{
+    _priv::do_start, config::AnyConfig, errors::RequestError, prelude::*, Addr, Context,
+    ResponseToken, Topology,
+};
+
+mod common;
+
+#[message(ret = u32)]
+struct Answer;
+
+#[message(ret = u32)]
+struct Hold;
+
+#[message(ret = ())]
+struct Release;
+
+#[message]
+struct Block;
+
+#[message(ret = u32)]
+struct GetHandled;
+
+const TIMEOUT: Duration = Duration::from_secs(1);
+
+fn responder() -> Blueprint {
+    ActorGroup::new().exec(move |mut ctx| async move {
+        let mut held = Vec::<ResponseToken<Hold>>::new();
+        let mut handled = 0;
+
+        while let Some(envelope) = ctx.recv().await {
+            msg!(match envelope {
+                (Answer, token) => {
+                    handled += 1;
+                    ctx.respond(token, 42);
+                }
+                (Hold, token) => {
+                    handled += 1;
+                    held.push(token);
+                }
+                (Release, token) => {
+                    for token in held.drain(..) {
+                        ctx.respond(token, 0);
+                    }
+                    ctx.respond(token, ());
+                }
+                Block => tokio::time::sleep(10 * TIMEOUT).await,
+                (GetHandled, token) => ctx.respond(token, handled),
+            });
+        }
+    })
+}
+
+async fn run<F: Future<Output = ()>>(f: impl FnOnce(Context, Addr) -> F) {
+    common::setup_logger();
+
+    let topology = Topology::empty();
+    let configurers = topology.local("system.configurers").entrypoint();
+    let responders = topology.local("responders");
+    let responder_addr = responders.addr();
+
+    configurers.mount(elfo::batteries::configurer::fixture(
+        &topology,
+        AnyConfig::default(),
+    ));
+    responders.mount(responder());
+
+    do_start(topology, false, |ctx, _| f(ctx, responder_addr))
+        .await
+        .expect("cannot start");
+}
+
+#[tokio::test(start_paused = true)]
+async fn any() {
+    run(|ctx, addr| async move {
+        let res = ctx.request_to(addr, Hold).timeout(TIMEOUT).resolve().await;
+        assert!(matches!(res, Err(RequestError::Timeout)), "{res:?}");
+
+        let res = ctx
+            .request_to(addr, Answer)
+            .timeout(TIMEOUT)
+            .resolve()
+            .await;
+        assert_eq!(res.unwrap(), 42);
+
+        // Late responses are discarded.
+        ctx.request_to(addr, Release).resolve().await.unwrap();
+        let res = ctx.request_to(addr, Answer).resolve().await;
+        assert_eq!(res.unwrap(), 42);
+    })
+    .await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn all() {
+    run(|ctx, addr| async move {
+        let res = ctx
+            .request_to(addr, Hold)
+            .all()
+            .timeout(TIMEOUT)
+            .resolve()
+            .await;
+        assert_eq!(res.len(), 1);
+        assert!(matches!(res[0], Err(RequestError::Timeout)), "{res:?}");
+
+        let res = ctx
+            .request_to(addr, Answer)
+            .all()
+            .timeout(TIMEOUT)
+            .resolve()
+            .await;
+        assert_eq!(res.len(), 1);
+        assert_eq!(*res[0].as_ref().unwrap(), 42);
+
+        ctx.request_to(addr, Release).resolve().await.unwrap();
+    })
+    .await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn expired_requests_are_skipped() {
+    run(|ctx, addr| async move {
+        ctx.send_to(addr, Block).await.unwrap();
+
+        let res = ctx
+            .request_to(addr, Answer)
+            .timeout(TIMEOUT)
+            .resolve()
+            .await;
+        assert!(matches!(res, Err(RequestError::Timeout)), "{res:?}");
+
+        // Requests without a deadline are handled as usual.
+        let res = ctx.request_to(addr, Answer).resolve().await;
+        assert_eq!(res.unwrap(), 42);
+
+        // The expired request hasn't reached the actor.
+        assert_eq!(ctx.request_to(addr, GetHandled).resolve().await.unwrap(), 1);
+    })
+    .await;
+}
@@ -0,0 +1,181 @@
+#![allow(missing_docs)]
+#![cfg(feature = "test-util")]
+
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use elfo::{
+    _priv::do_start, config::AnyConfig, messages::StreamEnded, prelude::*, Envelope, Topology,
+};
+
+#[message(ret = stream Item)]
+struct ListItems {
+    count: usize,
+    fail: bool,
+}
+
+#[message]
+struct Item(usize);
+
+const CHUNK_SIZE: usize = 3;
+
+fn responder(sent: Arc<AtomicUsize>) -> Blueprint {
+    ActorGroup::new().exec(move |mut ctx| {
+        let sent = sent.clone();
+        async move {
+            while let Some(envelope) = ctx.recv().await {
+                msg!(match envelope {
+                    (ListItems { count, fail }, token) => {
+                        let mut sink = ctx.response_sink(token).chunk_size(CHUNK_SIZE);
+
+                        for no in 0..count {
+                            sent.fetch_add(1, Ordering::SeqCst);
+                            sink.send(Item(no)).await.unwrap();
+                        }
+
+                        if !fail {
+                            sink.close().await.unwrap();
+                        }
+                    }
+                });
+            }
+        }
+    })
+}
+
+type Log = Arc<Mutex<Vec<String>>>;
+
+fn requester(request: ListItems, sent: Arc<AtomicUsize>, log: Log) -> Blueprint {
+    ActorGroup::new().exec(move |mut ctx| {
+        let request = request.clone();
+        let sent = sent.clone();
+        let log = log.clone();
+        async move {
+            ctx.attach(ctx.request(request).stream());
+
+            let mut received = 0;
+            while let Some(envelope) = ctx.recv().await {
+                msg!(match envelope {
+                    Item(no) => {
+                        received += 1;
+
+                        // The responder is blocked until previous chunks are handled.
+                        let in_flight = sent.load(Ordering::SeqCst) - received;
+                        assert!(in_flight < 2 * CHUNK_SIZE, "{in_flight}");
+
+                        log.lock().unwrap().push(format!("{no}"));
+                        tokio::time::sleep(Duration::from_millis(10)).await;
+                    }
+                    StreamEnded {
+                        request,
+                        is_complete,
+                        ..
+                    } => {
+                        log.lock().unwrap().push(format!("{request} {is_complete}"));
+                    }
+                });
+            }
+        }
+    })
+}
+
+async fn run(request: ListItems) -> Vec<String> {
+    let sent = Arc::new(AtomicUsize::new(0));
+    let log = Log::default();
+
+    let topology = Topology::empty();
+    let configurers = topology.local("system.configurers").entrypoint();
+    let requesters = topology.local("requesters");
+    let responders = topology.local("responders");
+
+    requesters.route_to(&responders, |e: &Envelope| {
+        msg!(match e {
+            ListItems => true,
+            _ => false,
+        })
+    });
+
+    configurers.mount(elfo::batteries::configurer::fixture(
+        &topology,
+        AnyConfig::default(),
+    ));
+    requesters.mount(requester(request, sent.clone(), log.clone()));
+    responders.mount(responder(sent));
+
+    do_start(topology, false, |_, _| async {
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    })
+    .await
+    .expect("cannot start");
+
+    let log = log.lock().unwrap().clone();
+    log
+}
+
+#[tokio::test(start_paused = true)]
+async fn it_works() {
+    let log = run(ListItems {
+        count: 10,
+        fail: false,
+    })
+    .await;
+
+    let expected = (0..10).map(|no| no.to_string());
+    let expected = expected
+        .chain(["ListItems true".into()])
+        .collect::<Vec<_>>();
+    assert_eq!(log, expected);
+}
+
+#[tokio::test(start_paused = true)]
+async fn dropped_sink() {
+    let log = run(ListItems {
+        count: 4,
+        fail: true,
+    })
+    .await;
+
+    // Buffered items are lost.
+    assert_eq!(log, ["0", "1", "2", "ListItems false"]);
+}
+
+// `stream` in `ret` is a keyword only if followed by a type.
+fn assert_response<R: elfo::Request<Response = T>, T>(_: R) {}
+
+mod stream_path {
+    use elfo::message;
+
+    mod stream {
+        #[elfo::message]
+        pub(super) struct Reply;
+    }
+
+    #[message(ret = stream::Reply)]
+    struct AskModule;
+
+    #[test]
+    fn regular_response() {
+        super::assert_response::<_, stream::Reply>(AskModule);
+    }
+}
+
+mod stream_generic {
+    use elfo::message;
+
+    #[allow(non_camel_case_types)]
+    #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+    struct stream<T>(T);
+
+    #[message(ret = stream<u32>)]
+    struct AskGeneric;
+
+    #[test]
+    fn regular_response() {
+        super::assert_response::<_, stream<u32>>(AskGeneric);
+    }
+}
@@ -3,16 +3,21 @@
 #![allow(clippy::never_loop)]
 
 use std::{
-    sync::{Arc, Mutex},
+    num::NonZeroU64,
+    sync::{
+        atomic::{AtomicBool, AtomicU32, Ordering},
+        Arc, Mutex,
+    },
     time::Duration,
 };
 
 use toml::toml;
 
 use elfo::{
+    messages::{ActorStatusReport, SubscribeToActorStatuses},
     prelude::*,
     routers::{MapRouter, Outcome, Singleton},
-    RestartParams, RestartPolicy,
+    CircuitState, RestartParams, RestartPolicy,
 };
 
 #[tokio::test]
@@ -82,6 +87,78 @@ async fn actor_restarts_with_timeout_after_failures() {
     }
 }
 
+#[tokio::test(start_paused = true)]
+async fn circuit_breaker() {
+    #[message]
+    struct Spawn;
+
+    let starts = Arc::new(AtomicU32::new(0));
+    let healthy = Arc::new(AtomicBool::new(false));
+    let starts_1 = starts.clone();
+    let healthy_1 = healthy.clone();
+
+    let blueprint = ActorGroup::new()
+        .router(MapRouter::new(|e| {
+            msg!(match e {
+                Spawn => Outcome::Unicast(Singleton),
+                _ => Outcome::Default,
+            })
+        }))
+        .restart_policy(RestartPolicy::on_failure(
+            RestartParams::new(Duration::from_secs(1), Duration::from_secs(1))
+                .max_retries(NonZeroU64::new(2).unwrap())
+                .cool_down(Duration::from_secs(60)),
+        ))
+        .exec(move |mut ctx| {
+            let starts = starts_1.clone();
+            let healthy = healthy_1.clone();
+            async move {
+                starts.fetch_add(1, Ordering::SeqCst);
+                if !healthy.load(Ordering::SeqCst) {
+                    anyhow::bail!("boom!");
+                }
+                while ctx.recv().await.is_some() {}
+                Ok(())
+            }
+        });
+
+    let mut proxy = elfo::test::proxy(blueprint, elfo::config::AnyConfig::default()).await;
+    proxy.send(SubscribeToActorStatuses::default()).await;
+    proxy.send(Spawn).await;
+
+    // The first start and two retries, then the circuit is open.
+    tokio::time::sleep(Duration::from_secs(10)).await;
+    assert_eq!(starts.load(Ordering::SeqCst), 3);
+    assert!(proxy.try_send(Spawn).is_err());
+
+    // After the cool-down, the actor is started once more.
+    healthy.store(true, Ordering::SeqCst);
+    tokio::time::sleep(Duration::from_secs(55)).await;
+    assert_eq!(starts.load(Ordering::SeqCst), 4);
+    assert!(proxy.try_send(Spawn).is_ok());
+
+    // The actor is considered healthy after `auto_reset`.
+    tokio::time::sleep(Duration::from_secs(2)).await;
+
+    let mut circuits = Vec::new();
+    while let Some(envelope) = proxy.try_recv().await {
+        msg!(match envelope {
+            ActorStatusReport { circuit, .. } => circuits.push(circuit),
+        });
+    }
+    circuits.dedup();
+
+    assert_eq!(
+        circuits,
+        [
+            CircuitState::Closed,
+            CircuitState::Open,
+            CircuitState::HalfOpen,
+            CircuitState::Closed
+        ]
+    );
+}
+
 #[tokio::test(start_paused = true)]
 async fn restart_policy_overriding() {
     #[message]
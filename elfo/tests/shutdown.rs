@@ -0,0 +1,144 @@
+#![allow(missing_docs)]
+#![cfg(feature = "test-util")]
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use serde::Deserialize;
+use tokio::time::Instant;
+use toml::toml;
+
+use elfo::{
+    _priv::{do_start, do_terminate},
+    config::AnyConfig,
+    prelude::*,
+    Topology,
+};
+
+mod common;
+
+#[message]
+struct Work;
+
+type Log = Arc<Mutex<Vec<String>>>;
+
+fn worker(log: Log, work_time: Duration) -> Blueprint {
+    ActorGroup::new().exec(move |mut ctx| {
+        let log = log.clone();
+        async move {
+            let started_at = Instant::now();
+
+            while let Some(envelope) = ctx.recv().await {
+                msg!(match envelope {
+                    Work => tokio::time::sleep(work_time).await,
+                });
+            }
+
+            let elapsed = started_at.elapsed().as_secs();
+            let group = &elfo::scope::meta().group;
+            log.lock()
+                .unwrap()
+                .push(format!("{group} finished at {elapsed}s"));
+        }
+    })
+}
+
+#[tokio::test(start_paused = true)]
+async fn drain_deadline_and_phases() {
+    common::setup_logger();
+
+    let log = Log::default();
+    let topology = Topology::empty();
+    let configurers = topology.local("system.configurers").entrypoint();
+    let fast = topology.local("fast");
+    let slow = topology.local("slow");
+    let late = topology.local("late");
+    let slow_addr = slow.addr();
+
+    let config = AnyConfig::deserialize(toml! {
+        [slow]
+        system.shutdown.drain_timeout = "1s"
+
+        [late]
+        system.shutdown.phase = 1
+    })
+    .unwrap();
+
+    configurers.mount(elfo::batteries::configurer::fixture(&topology, config));
+    fast.mount(worker(log.clone(), Duration::from_millis(100)));
+    slow.mount(worker(log.clone(), Duration::from_secs(5)));
+    late.mount(worker(log.clone(), Duration::from_millis(100)));
+
+    let report = do_start(topology, false, |ctx, topology| async move {
+        for _ in 0..3 {
+            ctx.send_to(slow_addr, Work).await.unwrap();
+        }
+
+        let (report, rejected) = tokio::join!(do_terminate(ctx.pruned(), topology), async {
+            tokio::time::sleep(Duration::from_millis(500)).await;
+            // The draining mailbox rejects new messages.
+            ctx.send_to(slow_addr, Work).await.is_err()
+        });
+
+        assert!(rejected);
+        report
+    })
+    .await
+    .expect("cannot start");
+
+    // `slow` misses the deadline, but finishes the current message,
+    // `late` is stopped only after that.
+    let log = log.lock().unwrap().clone();
+    assert_eq!(
+        log,
+        [
+            "fast finished at 0s",
+            "slow finished at 5s",
+            "late finished at 5s",
+        ]
+    );
+
+    assert!(!report.is_clean());
+    assert_eq!(report.missed_deadline.len(), 1);
+    assert_eq!(report.missed_deadline[0].group, "slow");
+
+    assert_eq!(report.undelivered.len(), 1);
+    assert_eq!(report.undelivered[0].recipient.group, "slow");
+    assert_eq!(report.undelivered[0].name, "Work");
+    assert_eq!(report.undelivered[0].count, 2);
+    assert_eq!(report.undelivered_count(), 2);
+}
+
+#[tokio::test(start_paused = true)]
+async fn clean_shutdown() {
+    common::setup_logger();
+
+    let log = Log::default();
+    let topology = Topology::empty();
+    let configurers = topology.local("system.configurers").entrypoint();
+    let workers = topology.local("workers");
+    let workers_addr = workers.addr();
+
+    configurers.mount(elfo::batteries::configurer::fixture(
+        &topology,
+        AnyConfig::default(),
+    ));
+    workers.mount(worker(log.clone(), Duration::from_secs(1)));
+
+    let report = do_start(topology, false, |ctx, topology| async move {
+        for _ in 0..3 {
+            ctx.send_to(workers_addr, Work).await.unwrap();
+        }
+
+        do_terminate(ctx.pruned(), topology).await
+    })
+    .await
+    .expect("cannot start");
+
+    // Queued messages are handled before the default deadline.
+    assert_eq!(*log.lock().unwrap(), ["workers finished at 3s"]);
+    assert!(report.is_clean());
+    assert_eq!(report.undelivered_count(), 0);
+}
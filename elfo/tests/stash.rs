@@ -0,0 +1,97 @@
+#![allow(missing_docs)]
+#![cfg(feature = "test-util")]
+
+use std::time::Duration;
+
+use elfo::{
+    config::AnyConfig,
+    prelude::*,
+    stash::{FileStash, StashBackend},
+    RestartParams, RestartPolicy,
+};
+
+#[message]
+#[derive(Default)]
+struct Total(u32);
+
+#[message]
+struct Add(u32);
+
+#[message]
+struct Fail;
+
+#[message]
+struct Stop;
+
+#[message(ret = (u32, u64))]
+struct GetTotal;
+
+fn testee(backend: impl StashBackend) -> Blueprint {
+    ActorGroup::new()
+        .restart_policy(RestartPolicy::on_failure(RestartParams::new(
+            Duration::from_secs(1),
+            Duration::from_secs(1),
+        )))
+        .stash_backend(backend)
+        .exec(|mut ctx| async move {
+            let mut total = ctx
+                .start_info()
+                .recovered_state::<Total>()
+                .unwrap_or_default();
+
+            while let Some(envelope) = ctx.recv().await {
+                msg!(match envelope {
+                    Add(n) => {
+                        total.0 += n;
+                        ctx.stash_state(total.clone());
+                    }
+                    (GetTotal, token) => {
+                        ctx.respond(token, (total.0, ctx.start_info().restart_count));
+                    }
+                    Fail => anyhow::bail!("boom!"),
+                    Stop => break,
+                });
+            }
+
+            Ok(())
+        })
+}
+
+async fn check(backend: impl StashBackend) {
+    let proxy = elfo::test::proxy(testee(backend), AnyConfig::default()).await;
+
+    proxy.send(Add(5)).await;
+    proxy.send(Add(7)).await;
+    assert_eq!(proxy.request(GetTotal).await, (12, 0));
+
+    // The state is recovered after a failure.
+    proxy.send(Fail).await;
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    assert_eq!(proxy.request(GetTotal).await, (12, 1));
+
+    proxy.send(Add(1)).await;
+    proxy.send(Fail).await;
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    assert_eq!(proxy.request(GetTotal).await, (13, 2));
+
+    // The state is discarded once the actor isn't restarted.
+    proxy.send(Stop).await;
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    assert_eq!(proxy.request(GetTotal).await, (0, 0));
+}
+
+#[tokio::test(start_paused = true)]
+async fn memory() {
+    check(elfo::stash::MemoryStash::default()).await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn file() {
+    let dir = std::env::temp_dir().join(format!("elfo-stash-test-{}", std::process::id()));
+    check(FileStash::new(&dir)).await;
+
+    // All files are removed after discarding.
+    let files = std::fs::read_dir(&dir).unwrap().count();
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(files, 0);
+}
@@ -0,0 +1,209 @@
+#![allow(missing_docs)]
+#![cfg(feature = "test-util")]
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use elfo::{
+    config::AnyConfig,
+    prelude::*,
+    routers::{MapRouter, Outcome},
+    RestartParams, RestartPolicy, SupervisionStrategy, Topology,
+    _priv::{do_start, do_terminate},
+};
+
+#[message]
+struct Spawn(u32);
+
+#[message]
+struct Fail(u32);
+
+type Log = Arc<Mutex<Vec<String>>>;
+
+fn testee(strategy: SupervisionStrategy, log: Log) -> Blueprint {
+    ActorGroup::new()
+        .router(MapRouter::new(|envelope| {
+            msg!(match envelope {
+                Spawn(key) | Fail(key) => Outcome::Unicast(*key),
+                _ => Outcome::Default,
+            })
+        }))
+        .restart_policy(RestartPolicy::on_failure(RestartParams::new(
+            Duration::from_secs(1),
+            Duration::from_secs(1),
+        )))
+        .supervision_strategy(strategy)
+        .exec(move |mut ctx| {
+            let log = log.clone();
+            async move {
+                let info = ctx.start_info();
+                let record = format!("{} {:?} {}", ctx.key(), info.cause, info.restart_count);
+                log.lock().unwrap().push(record);
+
+                while let Some(envelope) = ctx.recv().await {
+                    msg!(match envelope {
+                        Spawn => {}
+                        Fail => anyhow::bail!("boom!"),
+                    });
+                }
+
+                Ok(())
+            }
+        })
+}
+
+async fn run(strategy: SupervisionStrategy) -> Vec<String> {
+    let log = Log::default();
+    let proxy = elfo::test::proxy(testee(strategy, log.clone()), AnyConfig::default()).await;
+
+    for key in 0..3 {
+        proxy.send(Spawn(key)).await;
+    }
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    proxy.send(Fail(1)).await;
+    tokio::time::sleep(Duration::from_secs(2)).await;
+
+    // Actors start concurrently, so only the order of phases is checked.
+    let mut log = log.lock().unwrap().clone();
+    log[..3].sort();
+    log[3..].sort();
+    log
+}
+
+#[tokio::test(start_paused = true)]
+async fn one_for_one() {
+    let log = run(SupervisionStrategy::OneForOne).await;
+    assert_eq!(
+        log,
+        [
+            "0 OnMessage 0",
+            "1 OnMessage 0",
+            "2 OnMessage 0",
+            "1 Restarted 1",
+        ]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn one_for_all() {
+    let log = run(SupervisionStrategy::OneForAll).await;
+    assert_eq!(
+        log,
+        [
+            "0 OnMessage 0",
+            "1 OnMessage 0",
+            "2 OnMessage 0",
+            "0 SiblingFailed { key: \"1\" } 1",
+            "1 Restarted 1",
+            "2 SiblingFailed { key: \"1\" } 1",
+        ]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn rest_for_one() {
+    let log = run(SupervisionStrategy::RestForOne).await;
+    assert_eq!(
+        log,
+        [
+            "0 OnMessage 0",
+            "1 OnMessage 0",
+            "2 OnMessage 0",
+            "1 Restarted 1",
+            "2 SiblingFailed { key: \"1\" } 1",
+        ]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn escalation() {
+    let log = Log::default();
+    let log_1 = log.clone();
+
+    let connections_blueprint = ActorGroup::new().exec(|_ctx| async {
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        anyhow::bail!("boom!");
+    });
+
+    let sessions_blueprint = ActorGroup::new().exec(move |mut ctx| {
+        let log = log_1.clone();
+        async move {
+            let info = ctx.start_info();
+            let record = format!("{:?} {}", info.cause, info.restart_count);
+            log.lock().unwrap().push(record);
+            while ctx.recv().await.is_some() {}
+        }
+    });
+
+    let topology = Topology::empty();
+    let configurers = topology.local("system.configurers").entrypoint();
+    let connections = topology.local("connections");
+    let sessions = topology.local("sessions");
+
+    connections.escalate_to(&sessions);
+
+    configurers.mount(elfo::batteries::configurer::fixture(
+        &topology,
+        AnyConfig::default(),
+    ));
+    connections.mount(connections_blueprint);
+    sessions.mount(sessions_blueprint);
+
+    do_start(topology, false, |_, _| async {
+        tokio::time::sleep(Duration::from_secs(2)).await;
+    })
+    .await
+    .expect("cannot start");
+
+    assert_eq!(
+        *log.lock().unwrap(),
+        ["GroupMounted 0", "Escalated { group: \"connections\" } 1"]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn no_escalation_on_termination() {
+    let log = Log::default();
+    let log_1 = log.clone();
+
+    let connections_blueprint = ActorGroup::new().exec(|mut ctx| async move {
+        while ctx.recv().await.is_some() {}
+        anyhow::bail!("boom!");
+    });
+
+    // Sessions are terminated after connections.
+    let sessions_blueprint = ActorGroup::new().stop_order(1).exec(move |mut ctx| {
+        let log = log_1.clone();
+        async move {
+            let info = ctx.start_info();
+            let record = format!("{:?} {}", info.cause, info.restart_count);
+            log.lock().unwrap().push(record);
+            while ctx.recv().await.is_some() {}
+        }
+    });
+
+    let topology = Topology::empty();
+    let configurers = topology.local("system.configurers").entrypoint();
+    let connections = topology.local("connections");
+    let sessions = topology.local("sessions");
+
+    connections.escalate_to(&sessions);
+
+    configurers.mount(elfo::batteries::configurer::fixture(
+        &topology,
+        AnyConfig::default(),
+    ));
+    connections.mount(connections_blueprint);
+    sessions.mount(sessions_blueprint);
+
+    do_start(topology, false, |ctx, topology| async move {
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        do_terminate(ctx.pruned(), topology).await;
+    })
+    .await
+    .expect("cannot start");
+
+    assert_eq!(*log.lock().unwrap(), ["GroupMounted 0"]);
+}
@@ -0,0 +1,75 @@
+#![allow(missing_docs)]
+#![cfg(feature = "test-util")]
+
+use static_assertions::*;
+
+use elfo::{
+    config::AnyConfig, prelude::*, protocol, Addr, Handles, Protocol, Topology, TypedAddr,
+    _priv::do_start,
+};
+
+mod common;
+
+#[message]
+struct Increment(u32);
+
+#[message(ret = u32)]
+struct GetValue;
+
+#[message]
+struct Unrelated;
+
+#[protocol]
+enum Counter {
+    Increment(Increment),
+    GetValue(GetValue),
+}
+
+assert_impl_all!(Counter: Protocol, Handles<Increment>, Handles<GetValue>);
+assert_not_impl_any!(Counter: Handles<Unrelated>);
+assert_eq_size!(TypedAddr<Counter>, Addr);
+
+fn counter() -> Blueprint {
+    ActorGroup::new().exec(move |mut ctx| async move {
+        let mut value = 0;
+
+        while let Some(envelope) = ctx.recv().await {
+            msg!(match envelope {
+                Increment(delta) => value += delta,
+                (GetValue, token) => ctx.respond(token, value),
+            });
+        }
+    })
+}
+
+#[tokio::test(start_paused = true)]
+async fn it_works() {
+    common::setup_logger();
+
+    let topology = Topology::empty();
+    let configurers = topology.local("system.configurers").entrypoint();
+    let counters = topology.local("counters");
+    let counter_addr = TypedAddr::<Counter>::new(counters.addr());
+
+    configurers.mount(elfo::batteries::configurer::fixture(
+        &topology,
+        AnyConfig::default(),
+    ));
+    counters.mount(counter());
+
+    do_start(topology, false, |ctx, _| async move {
+        ctx.send_to(counter_addr, Increment(2)).await.unwrap();
+        ctx.try_send_to(counter_addr, Increment(3)).unwrap();
+        ctx.unbounded_send_to(counter_addr, Increment(5)).unwrap();
+
+        let value = ctx.request_to(counter_addr, GetValue).resolve().await;
+        assert_eq!(value.unwrap(), 10);
+
+        // Untyped addresses are still accepted.
+        let addr = counter_addr.addr();
+        let value = ctx.request_to(addr, GetValue).resolve().await;
+        assert_eq!(value.unwrap(), 10);
+    })
+    .await
+    .expect("cannot start");
+}
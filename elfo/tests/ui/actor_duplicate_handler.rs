@@ -0,0 +1,20 @@
+use elfo::{actor, message, Context};
+
+#[message]
+struct SomeMessage;
+
+struct SomeActor;
+
+#[actor]
+impl SomeActor {
+    #[handler]
+    fn on_message(&mut self, _ctx: &Context, _message: SomeMessage) {}
+
+    #[handler]
+    fn on_message_again(&mut self, _message: SomeMessage) {}
+
+    #[handler]
+    fn on_nothing(&mut self) {}
+}
+
+fn main() {}
@@ -0,0 +1,24 @@
+use elfo::{message, protocol, Context, TypedAddr};
+
+#[message]
+struct Handled;
+
+#[message(ret = u32)]
+struct HandledRequest;
+
+#[message]
+struct Unhandled;
+
+#[protocol]
+enum SomeProtocol {
+    Handled(Handled),
+    HandledRequest(HandledRequest),
+}
+
+async fn test(ctx: Context, addr: TypedAddr<SomeProtocol>) {
+    let _ = ctx.send_to(addr, Handled).await;
+    let _ = ctx.request_to(addr, HandledRequest).resolve().await;
+    let _ = ctx.send_to(addr, Unhandled).await;
+}
+
+fn main() {}
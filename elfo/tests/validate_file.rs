@@ -0,0 +1,112 @@
+#![allow(missing_docs)]
+#![cfg(feature = "test-util")]
+
+use std::fs;
+
+use serde::Deserialize;
+
+use elfo::{config::Secret, prelude::*, Topology};
+
+#[derive(Debug, Deserialize)]
+#[allow(dead_code)]
+struct Config {
+    value: u32,
+}
+
+fn testee() -> Blueprint {
+    ActorGroup::new()
+        .config::<Config>()
+        .exec(|_| async { unreachable!("actors must not be started") })
+}
+
+#[test]
+fn validate_file() {
+    let dir = std::env::temp_dir().join(format!("elfo-validate-file-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("config.toml");
+
+    let topology = Topology::empty();
+    let configurers = topology.local("system.configurers").entrypoint();
+    configurers.mount(elfo::batteries::configurer::from_path(&topology, &path));
+    topology.local("valid").mount(testee());
+    topology.local("invalid").mount(testee());
+    topology.local("invalid_system").mount(testee());
+    topology.local("from_common").mount(testee());
+
+    let validate = |content: &str| {
+        fs::write(&path, content).unwrap();
+        elfo::batteries::configurer::validate_file(&topology, &path)
+    };
+
+    let content = r#"
+        [common]
+        value = 1
+
+        [invalid]
+        value = "oops"
+
+        [invalid_system]
+        system.mailbox.capacity = "oops"
+    "#;
+
+    let mut errors = validate(content).unwrap_err();
+    errors.sort_by(|a, b| a.group.cmp(&b.group));
+
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0].group, "invalid");
+    assert_eq!(errors[1].group, "invalid_system");
+
+    for error in &errors {
+        let reason = &error.reason;
+        assert!(reason.contains("invalid type"), "{reason}");
+    }
+
+    assert!(validate("[common]\nvalue = 1").is_ok());
+
+    // Errors of the file itself are reported for the entrypoint.
+    let errors = validate("[common").unwrap_err();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].group, "system.configurers");
+    assert!(errors[0].reason.starts_with(&path.display().to_string()));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn validate_file_with_secrets() {
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Config {
+        password: Secret<String>,
+    }
+
+    let dir = std::env::temp_dir().join(format!("elfo-validate-secrets-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("config.toml");
+
+    let topology = Topology::empty();
+    let configurers = topology.local("system.configurers").entrypoint();
+    configurers.mount(elfo::batteries::configurer::from_path(&topology, &path));
+    topology.local("testee").mount(
+        ActorGroup::new()
+            .config::<Config>()
+            .exec(|_| async { unreachable!("actors must not be started") }),
+    );
+
+    let content = r#"
+        [testee]
+        password = { file = "/nonexistent/elfo/password" }
+    "#;
+    fs::write(&path, content).unwrap();
+
+    let validate = || elfo::batteries::configurer::validate_file(&topology, &path);
+
+    let errors = validate().unwrap_err();
+    assert_eq!(errors.len(), 1);
+    let reason = &errors[0].reason;
+    assert!(reason.contains("cannot read the secret"), "{reason}");
+
+    assert!(elfo::config::skip_secrets(validate).is_ok());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
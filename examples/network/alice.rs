@@ -1,6 +1,11 @@
 use std::time::Duration;
 
-use elfo::{prelude::*, time::Interval, topology::Outcome};
+use elfo::{
+    prelude::*,
+    time::Interval,
+    topology::{NodeDiscovery, Outcome},
+    Envelope,
+};
 use tracing::{info, warn};
 
 use crate::protocol::{AskName, Hello};
@@ -54,7 +59,9 @@ pub(crate) fn topology() -> elfo::Topology {
     // Remote user groups.
     let consumers = topology.remote("consumers");
 
-    producers.route_to(&consumers, |_, _| Outcome::Broadcast);
+    producers.route_to(&consumers, |_: &Envelope, _: &NodeDiscovery| {
+        Outcome::Broadcast
+    });
 
     loggers.mount(logger);
     telemeters.mount(telemeter);